@@ -0,0 +1,22 @@
+use anyhow::Result;
+use std::{env, net::TcpListener};
+
+use simpledb::{
+    client::driver::DEFAULT_ADDR,
+    server::{network_server::NetworkServer, simpledb::SimpleDB},
+};
+
+const DEFAULT_DB_DIR: &str = "simpledb_data";
+
+/// Serve a database directory over TCP.
+/// Usage: `server [db_dir] [addr]`.
+fn main() -> Result<()> {
+    let mut args = env::args().skip(1);
+    let db_dir = args.next().unwrap_or_else(|| DEFAULT_DB_DIR.to_string());
+    let addr = args.next().unwrap_or_else(|| DEFAULT_ADDR.to_string());
+
+    let server = NetworkServer::new(SimpleDB::open(&db_dir)?);
+    let listener = TcpListener::bind(&addr)?;
+    println!("serving {} on {}", db_dir, listener.local_addr()?);
+    server.serve(listener)
+}
@@ -1,2 +1,4 @@
+#[allow(clippy::module_inception)]
 pub mod buffer;
 pub mod manager;
+pub mod needs;
@@ -1,5 +1,8 @@
 use anyhow::{Ok, Result};
-use std::sync::{Arc, Mutex};
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc, Mutex,
+};
 
 use crate::{
     file::{block_id::BlockId, manager::FileManager, page::Page},
@@ -7,32 +10,37 @@ use crate::{
     Lsn,
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct Buffer {
-    file_manager: Arc<Mutex<FileManager>>,
+    file_manager: Arc<FileManager>,
     log_manager: Arc<Mutex<LogManager>>,
     contents: Page,
     block: Option<BlockId>,
-    pins: u32,
+    // pins are counted atomically, so that pinning a buffer only needs shared access to it;
+    // the buffer manager changes them while holding the pool's mutex
+    pins: AtomicU32,
     pub txnum: i32,
     lsn: Lsn,
 }
 
 impl Buffer {
-    pub fn new(file_manager: Arc<Mutex<FileManager>>, log_manager: Arc<Mutex<LogManager>>) -> Self {
-        let block_size = file_manager.lock().unwrap().block_size();
+    pub fn new(file_manager: Arc<FileManager>, log_manager: Arc<Mutex<LogManager>>) -> Self {
         Self {
+            contents: file_manager.new_page(),
             file_manager,
             log_manager,
-            contents: Page::new(block_size),
             block: None,
-            pins: 0,
+            pins: AtomicU32::new(0),
             txnum: -1,
             lsn: -1,
         }
     }
 
-    pub fn contents(&mut self) -> &mut Page {
+    pub fn contents(&self) -> &Page {
+        &self.contents
+    }
+
+    pub fn contents_mut(&mut self) -> &mut Page {
         &mut self.contents
     }
 
@@ -53,89 +61,88 @@ impl Buffer {
         if self.txnum >= 0 {
             self.log_manager.lock().unwrap().flush(self.lsn)?;
             if let Some(block) = &self.block {
-                self.file_manager
-                    .lock()
-                    .unwrap()
-                    .write(block, &mut self.contents)?;
+                self.contents.set_lsn(self.lsn);
+                self.file_manager.write(block, &self.contents)?;
             }
             self.txnum = -1;
         }
         Ok(())
     }
 
+    /// Flush the modified buffers as a single batch: the log is flushed up to the latest LSN of the buffers,
+    /// and then the file manager writes all of the pages, through its double-write area if it is enabled.
+    pub fn flush_batch<'a>(buffers: impl IntoIterator<Item = &'a mut Buffer>) -> Result<()> {
+        let mut buffers: Vec<_> = buffers
+            .into_iter()
+            .filter(|buf| buf.txnum >= 0 && buf.block.is_some())
+            .collect();
+        let Some(first) = buffers.first() else {
+            return Ok(());
+        };
+        let file_manager = Arc::clone(&first.file_manager);
+        let log_manager = Arc::clone(&first.log_manager);
+
+        let max_lsn = buffers.iter().map(|buf| buf.lsn).max().unwrap_or(-1);
+        log_manager.lock().unwrap().flush(max_lsn)?;
+        for buf in buffers.iter_mut() {
+            buf.contents.set_lsn(buf.lsn);
+        }
+        let pages: Vec<_> = buffers
+            .iter()
+            .filter_map(|buf| buf.block.as_ref().map(|block| (block, &buf.contents)))
+            .collect();
+        file_manager.write_batch(&pages)?;
+        for buf in buffers {
+            buf.txnum = -1;
+        }
+        Ok(())
+    }
+
+    /// Read the block into the buffer, after flushing the buffer's previous contents.
+    /// If the read fails, for example because the block is corrupted, the buffer is left unassigned.
     pub fn assign_to_block(&mut self, block: &BlockId) -> Result<()> {
         self.flush()?;
-        self.file_manager
-            .lock()
-            .unwrap()
-            .read(block, &mut self.contents)?;
+        self.block = None;
+        self.file_manager.read(block, &mut self.contents)?;
         self.block = Some(block.clone());
-        self.pins = 0;
+        *self.pins.get_mut() = 0;
         Ok(())
     }
 
     pub fn is_pinned(&self) -> bool {
-        self.pins > 0
+        self.pins.load(Ordering::Relaxed) > 0
     }
 
-    pub fn pin(&mut self) {
-        self.pins += 1;
+    pub fn pin(&self) {
+        self.pins.fetch_add(1, Ordering::Relaxed);
     }
 
-    pub fn unpin(&mut self) {
-        self.pins -= 1;
+    pub fn unpin(&self) {
+        self.pins.fetch_sub(1, Ordering::Relaxed);
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::sync::{Arc, Mutex};
-
     use tempfile::tempdir;
 
-    use crate::{
-        buffer::manager::BufferManager,
-        file::{block_id::BlockId, manager::FileManager},
-        log::manager::LogManager,
-    };
+    use crate::{file::block_id::BlockId, server::simpledb::SimpleDB};
 
     #[test]
     fn test_buffer() {
         let temp_dir = tempdir().unwrap();
-        let db_dir = temp_dir.path().to_str().unwrap();
-        let test_log_file = temp_dir
-            .path()
-            .join("simpledb.log")
-            .to_str()
-            .unwrap()
-            .to_string();
-
-        let block_size = 400;
-        let num_buffers = 3; // only 3 buffers
-
-        let file_manager = Arc::new(Mutex::new(FileManager::new(db_dir, block_size).unwrap()));
-        let log_manager = Arc::new(Mutex::new(
-            LogManager::new(Arc::clone(&file_manager), &test_log_file).unwrap(),
-        ));
-        let buffer_manager = BufferManager::new(
-            Arc::clone(&file_manager),
-            Arc::clone(&log_manager),
-            num_buffers,
-        );
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 3).unwrap(); // only 3 buffers
+        let buffer_manager = db.buffer_manager();
 
         // Test pinning and modifying first buffer
         let idx1 = buffer_manager.pin(&BlockId::new("testfile", 1)).unwrap();
 
         {
-            let (lock, _) = &*buffer_manager.state;
-            let mut state = lock.lock().unwrap();
-
-            let n = state.buffer_pool[idx1].contents().get_int(80).unwrap();
-            state.buffer_pool[idx1]
-                .contents()
-                .set_int(80, n + 1)
-                .unwrap();
-            state.buffer_pool[idx1].set_modified(1, 0).unwrap(); // placeholder values
+            let mut buffer = buffer_manager.buffer_mut(idx1);
+
+            let n = buffer.contents().get_int(80).unwrap();
+            buffer.contents_mut().set_int(80, n + 1).unwrap();
+            buffer.set_modified(1, 0).unwrap(); // placeholder values
             assert_eq!(1, n + 1);
         }
 
@@ -152,14 +159,10 @@ mod tests {
         idx2 = buffer_manager.pin(&BlockId::new("testfile", 1)).unwrap();
 
         {
-            let (lock, _) = &*buffer_manager.state;
-            let mut state = lock.lock().unwrap();
-
-            state.buffer_pool[idx2]
-                .contents()
-                .set_int(80, 9999)
-                .unwrap();
-            state.buffer_pool[idx2].set_modified(1, 0).unwrap(); // This modification won't get written to disk
+            let mut buffer = buffer_manager.buffer_mut(idx2);
+
+            buffer.contents_mut().set_int(80, 9999).unwrap();
+            buffer.set_modified(1, 0).unwrap(); // This modification won't get written to disk
         }
 
         // Cleanup
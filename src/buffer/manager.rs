@@ -1,6 +1,6 @@
 use core::fmt;
 use std::{
-    sync::{Arc, Condvar, Mutex},
+    sync::{Arc, Condvar, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard},
     time::Duration,
 };
 
@@ -32,32 +32,44 @@ impl fmt::Display for BufferManagerError {
 
 #[derive(Debug)]
 pub struct BufferPoolState {
-    pub buffer_pool: Vec<Buffer>,
     num_available: usize,
 }
 
-#[derive(Debug)]
+/// The buffer manager pins blocks to the buffers of a fixed-size pool.
+/// The pool's mutex only guards pinning and unpinning, while each buffer has its own read-write lock,
+/// so that transactions reading pinned buffers share access and do not serialize on the pool.
+/// Pinning a buffer that is already assigned to the block only needs shared access to it.
+/// The pool's mutex is always taken before a buffer's lock.
+#[derive(Debug, Clone)]
 pub struct BufferManager {
+    buffer_pool: Arc<[RwLock<Buffer>]>,
     pub state: Arc<(Mutex<BufferPoolState>, Condvar)>,
+    file_manager: Arc<FileManager>,
 }
 
 impl BufferManager {
     pub fn new(
-        file_manager: Arc<Mutex<FileManager>>,
+        file_manager: Arc<FileManager>,
         log_manager: Arc<Mutex<LogManager>>,
         num_buffers: usize,
     ) -> Self {
-        let buffers = (0..num_buffers)
-            .map(|_| Buffer::new(Arc::clone(&file_manager), Arc::clone(&log_manager)))
+        let buffer_pool = (0..num_buffers)
+            .map(|_| {
+                RwLock::new(Buffer::new(
+                    Arc::clone(&file_manager),
+                    Arc::clone(&log_manager),
+                ))
+            })
             .collect();
 
         let state = BufferPoolState {
-            buffer_pool: buffers,
             num_available: num_buffers,
         };
 
         Self {
+            buffer_pool,
             state: Arc::new((Mutex::new(state), Condvar::new())),
+            file_manager,
         }
     }
 
@@ -67,12 +79,23 @@ impl BufferManager {
         state.num_available
     }
 
+    /// Return shared access to a pinned buffer, for reading its contents.
+    pub fn buffer(&self, idx: usize) -> RwLockReadGuard<'_, Buffer> {
+        self.buffer_pool[idx].read().unwrap()
+    }
+
+    /// Return exclusive access to a pinned buffer, for modifying its contents.
+    pub fn buffer_mut(&self, idx: usize) -> RwLockWriteGuard<'_, Buffer> {
+        self.buffer_pool[idx].write().unwrap()
+    }
+
     pub fn unpin(&self, idx: usize) -> Result<()> {
         let (lock, cvar) = &*self.state;
         let mut state = lock.lock().unwrap();
 
-        state.buffer_pool[idx].unpin();
-        if !state.buffer_pool[idx].is_pinned() {
+        let buffer = self.buffer(idx);
+        buffer.unpin();
+        if !buffer.is_pinned() {
             state.num_available += 1;
             cvar.notify_one();
         }
@@ -84,7 +107,7 @@ impl BufferManager {
         let mut state = lock.lock().unwrap();
 
         loop {
-            if let Some(idx) = self.try_to_pin(block, &mut state) {
+            if let Some(idx) = self.try_to_pin(block, &mut state)? {
                 return Ok(idx);
             }
 
@@ -100,15 +123,21 @@ impl BufferManager {
         }
     }
 
+    /// Write the buffers modified by the transaction, and sync every dirty file.
+    /// The sync also covers pages of the transaction that were written when their buffers were replaced,
+    /// so they are all on disk before the transaction's commit record is written.
     pub fn flush_all(&self, txnum: i32) -> Result<()> {
         let (lock, cvar) = &*self.state;
-        let mut state = lock.lock().unwrap();
+        let _state = lock.lock().unwrap();
 
-        state
+        let mut buffers: Vec<_> = self
             .buffer_pool
-            .iter_mut()
+            .iter()
+            .map(|buf| buf.write().unwrap())
             .filter(|buf| buf.txnum == txnum)
-            .try_for_each(|buf| buf.flush())
+            .collect();
+        Buffer::flush_batch(buffers.iter_mut().map(|buf| &mut **buf))
+            .and_then(|_| self.file_manager.sync_all_dirty())
             .map_or_else(
                 |err| {
                     cvar.notify_all();
@@ -121,88 +150,64 @@ impl BufferManager {
             )
     }
 
-    fn try_to_pin<'a>(&self, block: &'a BlockId, state: &'a mut BufferPoolState) -> Option<usize> {
-        if let Some(idx) = self.find_existing_buffer(block, state) {
-            if !state.buffer_pool[idx].is_pinned() {
+    /// Pin a buffer to the block, or return `None` if every buffer is pinned.
+    /// An error reading the block, such as a checksum mismatch, is returned without pinning any buffer.
+    fn try_to_pin(&self, block: &BlockId, state: &mut BufferPoolState) -> Result<Option<usize>> {
+        if let Some(idx) = self.find_existing_buffer(block) {
+            let buffer = self.buffer(idx);
+            if !buffer.is_pinned() {
                 state.num_available -= 1;
             }
-            state.buffer_pool[idx].pin();
-            return Some(idx);
+            buffer.pin();
+            return Ok(Some(idx));
         }
 
-        if let Some(idx) = self.find_unpinned_buffer(state) {
-            state.buffer_pool[idx].assign_to_block(block).unwrap();
+        if let Some(idx) = self.find_unpinned_buffer() {
+            let mut buffer = self.buffer_mut(idx);
+            buffer.assign_to_block(block)?;
             state.num_available -= 1;
-            state.buffer_pool[idx].pin();
-            return Some(idx);
+            buffer.pin();
+            return Ok(Some(idx));
         }
 
-        None
+        Ok(None)
     }
 
-    fn find_existing_buffer<'a>(
-        &self,
-        block: &'a BlockId,
-        state: &'a BufferPoolState,
-    ) -> Option<usize> {
-        state
-            .buffer_pool
+    fn find_existing_buffer(&self, block: &BlockId) -> Option<usize> {
+        self.buffer_pool
             .iter()
-            .enumerate()
-            .find(|(_, buffer)| buffer.block().contains(block))
-            .map(|(idx, _)| idx)
+            .position(|buffer| buffer.read().unwrap().block().contains(block))
     }
 
-    fn find_unpinned_buffer(&self, state: &BufferPoolState) -> Option<usize> {
-        state
-            .buffer_pool
+    fn find_unpinned_buffer(&self) -> Option<usize> {
+        self.buffer_pool
             .iter()
-            .enumerate()
-            .find(|(_, buffer)| !buffer.is_pinned())
-            .map(|(idx, _)| idx)
+            .position(|buffer| !buffer.read().unwrap().is_pinned())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{
-        collections::HashMap,
-        sync::{Arc, Mutex},
-    };
+    use std::collections::HashMap;
 
     use tempfile::tempdir;
 
     use crate::{
-        buffer::manager::BufferManager,
-        file::{block_id::BlockId, manager::FileManager},
-        log::manager::LogManager,
+        file::{
+            block_id::BlockId,
+            manager::{FileManager, FileManagerError, BLOCK_HEADER_SIZE},
+            page::Page,
+        },
+        server::simpledb::SimpleDB,
     };
 
     #[test]
     fn test_buffer_manager() {
         let temp_dir = tempdir().unwrap();
-        let db_dir = temp_dir.path().to_str().unwrap();
-        let test_file = temp_dir
-            .path()
-            .join("simpledb.log")
-            .to_str()
-            .unwrap()
-            .to_string();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 3).unwrap(); // only 3 buffers
+        let buffer_manager = db.buffer_manager();
 
-        let block_size = 400;
-        let num_buffers = 3;
-
-        let file_manager = Arc::new(Mutex::new(FileManager::new(db_dir, block_size).unwrap()));
-        let log_manager = Arc::new(Mutex::new(
-            LogManager::new(Arc::clone(&file_manager), &test_file).unwrap(),
-        ));
-        let buffer_manager = BufferManager::new(
-            Arc::clone(&file_manager),
-            Arc::clone(&log_manager),
-            num_buffers,
-        );
-
-        let mut buffers = vec![0usize; 6];
+        let mut buffers = [0usize; 6];
         buffers[0] = buffer_manager.pin(&BlockId::new("testfile", 0)).unwrap();
         buffers[1] = buffer_manager.pin(&BlockId::new("testfile", 1)).unwrap();
         buffers[2] = buffer_manager.pin(&BlockId::new("testfile", 2)).unwrap();
@@ -228,27 +233,57 @@ mod tests {
             (5, BlockId::new("testfile", 3)),
         ]);
 
-        {
-            let (lock, _) = &*buffer_manager.state;
-            let mut state = lock.lock().unwrap();
-
-            for (i, &idx) in buffers.iter().enumerate() {
-                if idx != 42 {
-                    let x: Vec<Option<BlockId>> = state
-                        .buffer_pool
-                        .iter_mut()
-                        .map(|buf| buf.block().clone())
-                        .collect();
-                    println!("{:?}", x);
-
-                    let actual = state.buffer_pool[idx].block().as_ref();
-                    println!("i: {}, idx: {}", i, idx);
-                    println!("buff[{}] pinned to block {:?}", i, actual.unwrap());
-                    assert_eq!(expected.get(&i).unwrap(), actual.unwrap());
-                } else {
-                    assert!(i == 1 || i == 2);
-                }
+        for (i, &idx) in buffers.iter().enumerate() {
+            if idx != 42 {
+                let x: Vec<Option<BlockId>> = buffer_manager
+                    .buffer_pool
+                    .iter()
+                    .map(|buf| buf.read().unwrap().block().clone())
+                    .collect();
+                println!("{:?}", x);
+
+                let buffer = buffer_manager.buffer(idx);
+                let actual = buffer.block().as_ref();
+                println!("i: {}, idx: {}", i, idx);
+                println!("buff[{}] pinned to block {:?}", i, actual.unwrap());
+                assert_eq!(expected.get(&i).unwrap(), actual.unwrap());
+            } else {
+                assert!(i == 1 || i == 2);
             }
         }
     }
+
+    #[test]
+    fn test_pin_corrupted_block() {
+        let temp_dir = tempdir().unwrap();
+        let file_manager = FileManager::new(temp_dir.path().to_str().unwrap(), 800)
+            .unwrap()
+            .with_checksums(true);
+        let db = SimpleDB::with_file_manager(file_manager, 3).unwrap();
+        let buffer_manager = db.buffer_manager();
+
+        let block = BlockId::new("testfile", 0);
+        let mut page = Page::new(800);
+        page.set_int(0, 42).unwrap();
+        db.file_manager().write(&block, &page).unwrap();
+        let path = temp_dir.path().join("testfile");
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[BLOCK_HEADER_SIZE] ^= 0xff;
+        std::fs::write(&path, bytes).unwrap();
+
+        let err = buffer_manager.pin(&block).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<FileManagerError>(),
+            Some(FileManagerError::Corrupted { .. })
+        ));
+        assert_eq!(buffer_manager.available(), 3);
+        assert!(buffer_manager
+            .buffer_pool
+            .iter()
+            .all(|buf| buf.read().unwrap().block().is_none()));
+
+        // the pool is still usable
+        let idx = buffer_manager.pin(&BlockId::new("testfile", 1)).unwrap();
+        buffer_manager.unpin(idx).unwrap();
+    }
 }
@@ -0,0 +1,57 @@
+/// The number of available buffers that the heuristics leave for the other scans of the query.
+const RESERVED_BUFFERS: usize = 2;
+
+/// Return the largest number k of buffers, at most the number of available buffers less a couple,
+/// such that k is a root of the specified size: k = ceil(size^(1/i)) for some integer i.
+/// This is the number of buffers to use for an operator that processes the size in several passes of k-way steps.
+pub fn best_root(available: usize, size: usize) -> usize {
+    let avail = available.saturating_sub(RESERVED_BUFFERS);
+    if avail <= 1 {
+        return 1;
+    }
+    let mut k = usize::MAX;
+    let mut i = 1.0;
+    while k > avail {
+        i += 1.0;
+        k = (size as f64).powf(1.0 / i).ceil() as usize;
+    }
+    k
+}
+
+/// Return the largest number k of buffers, at most the number of available buffers less a couple,
+/// such that k is a factor of the specified size: k = ceil(size/i) for some integer i.
+/// This is the number of buffers to use for an operator that processes the size in chunks of k blocks.
+pub fn best_factor(available: usize, size: usize) -> usize {
+    let avail = available.saturating_sub(RESERVED_BUFFERS);
+    if avail <= 1 {
+        return 1;
+    }
+    let mut k = size.max(1);
+    let mut i = 1;
+    while k > avail {
+        i += 1;
+        k = size.div_ceil(i);
+    }
+    k
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{best_factor, best_root};
+
+    #[test]
+    fn test_buffer_needs() {
+        assert_eq!(best_factor(10, 5), 5);
+        assert_eq!(best_factor(10, 100), 8);
+        assert_eq!(best_factor(10, 30), 8);
+        assert_eq!(best_factor(10, 31), 8);
+        assert_eq!(best_factor(10, 33), 7);
+        assert_eq!(best_factor(3, 100), 1);
+        assert_eq!(best_factor(10, 0), 1);
+
+        assert_eq!(best_root(10, 5), 3);
+        assert_eq!(best_root(10, 100), 5);
+        assert_eq!(best_root(10, 1000), 6);
+        assert_eq!(best_root(3, 1000), 1);
+    }
+}
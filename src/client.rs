@@ -0,0 +1,4 @@
+pub mod connection;
+pub mod driver;
+pub mod result_set;
+pub mod statement;
@@ -0,0 +1,106 @@
+use anyhow::{bail, Ok, Result};
+use core::fmt;
+use std::{
+    io::{BufReader, BufWriter},
+    net::{TcpStream, ToSocketAddrs},
+    sync::{Arc, Mutex},
+};
+
+use crate::server::protocol::{read_frame, write_frame, Request, Response, PROTOCOL_VERSION};
+
+use super::statement::Statement;
+
+#[derive(Debug)]
+enum ConnectionError {
+    Server(String),
+    Disconnected,
+    UnexpectedResponse(Response),
+}
+
+impl std::error::Error for ConnectionError {}
+impl fmt::Display for ConnectionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConnectionError::Server(msg) => write!(f, "server error: {}", msg),
+            ConnectionError::Disconnected => write!(f, "the server closed the connection"),
+            ConnectionError::UnexpectedResponse(response) => {
+                write!(f, "unexpected response {:?}", response)
+            }
+        }
+    }
+}
+
+/// The client end of the socket, shared by a connection and its statements and result sets.
+#[derive(Debug)]
+pub(crate) struct Channel {
+    reader: BufReader<TcpStream>,
+    writer: BufWriter<TcpStream>,
+}
+
+impl Channel {
+    /// Send a request and wait for its response.
+    /// An error response from the server becomes an error.
+    pub(crate) fn call(&mut self, request: &Request) -> Result<Response> {
+        write_frame(&mut self.writer, &request.encode())?;
+        let frame = read_frame(&mut self.reader)?.ok_or(ConnectionError::Disconnected)?;
+        match Response::decode(&frame)? {
+            Response::Error(msg) => bail!(ConnectionError::Server(msg)),
+            response => Ok(response),
+        }
+    }
+
+    /// Send a request that expects a plain acknowledgement.
+    pub(crate) fn call_ok(&mut self, request: &Request) -> Result<()> {
+        match self.call(request)? {
+            Response::Ok => Ok(()),
+            response => bail!(ConnectionError::UnexpectedResponse(response)),
+        }
+    }
+}
+
+pub(crate) fn unexpected(response: Response) -> anyhow::Error {
+    ConnectionError::UnexpectedResponse(response).into()
+}
+
+/// A connection to a SimpleDB network server.
+/// Statements run in the connection's current transaction, which lasts until
+/// `commit` or `rollback`. A failed statement rolls the transaction back.
+#[derive(Debug)]
+pub struct Connection {
+    channel: Arc<Mutex<Channel>>,
+}
+
+impl Connection {
+    pub(crate) fn open(addr: impl ToSocketAddrs) -> Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        let mut channel = Channel {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: BufWriter::new(stream),
+        };
+        channel.call_ok(&Request::Connect {
+            version: PROTOCOL_VERSION,
+        })?;
+
+        Ok(Self {
+            channel: Arc::new(Mutex::new(channel)),
+        })
+    }
+
+    pub fn create_statement(&self) -> Statement {
+        Statement::new(Arc::clone(&self.channel))
+    }
+
+    pub fn commit(&self) -> Result<()> {
+        self.channel.lock().unwrap().call_ok(&Request::Commit)
+    }
+
+    pub fn rollback(&self) -> Result<()> {
+        self.channel.lock().unwrap().call_ok(&Request::Rollback)
+    }
+
+    /// Commit the current transaction and close the connection.
+    pub fn close(self) -> Result<()> {
+        self.channel.lock().unwrap().call_ok(&Request::Close)
+    }
+}
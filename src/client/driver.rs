@@ -0,0 +1,22 @@
+use anyhow::Result;
+use std::net::ToSocketAddrs;
+
+use super::connection::Connection;
+
+/// The default address that the network server listens on.
+pub const DEFAULT_ADDR: &str = "127.0.0.1:1099";
+
+/// Creates connections to a SimpleDB network server.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Driver;
+
+impl Driver {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Connect to the server listening on the specified address.
+    pub fn connect(&self, addr: impl ToSocketAddrs) -> Result<Connection> {
+        Connection::open(addr)
+    }
+}
@@ -0,0 +1,125 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    query::constant::Constant,
+    record::schema::Schema,
+    server::protocol::{Request, Response},
+};
+
+use super::connection::{unexpected, Channel};
+
+const FETCH_SIZE: u32 = 100;
+
+#[derive(Debug)]
+enum ResultSetError {
+    NoCurrentRow,
+    FieldNotFound(String),
+    WrongType(String),
+}
+
+impl std::error::Error for ResultSetError {}
+impl fmt::Display for ResultSetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ResultSetError::NoCurrentRow => write!(f, "result set is not positioned on a row"),
+            ResultSetError::FieldNotFound(field_name) => {
+                write!(f, "field {} not found", field_name)
+            }
+            ResultSetError::WrongType(field_name) => {
+                write!(f, "field {} has a different type", field_name)
+            }
+        }
+    }
+}
+
+/// The rows of a query, fetched from the server in batches.
+#[derive(Debug)]
+pub struct ResultSet {
+    channel: Arc<Mutex<Channel>>,
+    id: u32,
+    schema: Schema,
+    rows: VecDeque<Vec<Constant>>,
+    current: Option<Vec<Constant>>,
+    done: bool,
+}
+
+impl ResultSet {
+    pub(crate) fn new(channel: Arc<Mutex<Channel>>, id: u32, schema: Schema) -> Self {
+        Self {
+            channel,
+            id,
+            schema,
+            rows: VecDeque::new(),
+            current: None,
+            done: false,
+        }
+    }
+
+    /// The names, types and lengths of the fields of the result.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Move to the next row, fetching another batch when the buffered rows run out.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<bool> {
+        if self.rows.is_empty() && !self.done {
+            let response = self.channel.lock().unwrap().call(&Request::Fetch {
+                id: self.id,
+                max_rows: FETCH_SIZE,
+            })?;
+            match response {
+                Response::Rows { rows, done } => {
+                    self.rows.extend(rows);
+                    self.done = done;
+                }
+                response => return Err(unexpected(response)),
+            }
+        }
+        self.current = self.rows.pop_front();
+        Ok(self.current.is_some())
+    }
+
+    pub fn get_int(&self, field_name: &str) -> Result<i32> {
+        self.get_val(field_name)?
+            .as_int()
+            .ok_or_else(|| ResultSetError::WrongType(field_name.to_string()).into())
+    }
+
+    pub fn get_string(&self, field_name: &str) -> Result<String> {
+        self.get_val(field_name)?
+            .as_string()
+            .map(str::to_string)
+            .ok_or_else(|| ResultSetError::WrongType(field_name.to_string()).into())
+    }
+
+    pub fn get_val(&self, field_name: &str) -> Result<Constant> {
+        let row = self.current.as_ref().ok_or(ResultSetError::NoCurrentRow)?;
+        let field_name = field_name.to_lowercase();
+        let idx = self
+            .schema
+            .fields()
+            .iter()
+            .position(|field| *field == field_name)
+            .ok_or(ResultSetError::FieldNotFound(field_name))?;
+        Ok(row[idx].clone())
+    }
+
+    /// Release the server-side scan if it has not been read to the end.
+    pub fn close(mut self) -> Result<()> {
+        self.current = None;
+        if !self.done {
+            self.done = true;
+            self.channel
+                .lock()
+                .unwrap()
+                .call_ok(&Request::CloseResultSet(self.id))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use anyhow::Result;
+use std::sync::{Arc, Mutex};
+
+use crate::server::protocol::{Request, Response};
+
+use super::{
+    connection::{unexpected, Channel},
+    result_set::ResultSet,
+};
+
+/// Executes SQL statements on a connection.
+#[derive(Debug)]
+pub struct Statement {
+    channel: Arc<Mutex<Channel>>,
+}
+
+impl Statement {
+    pub(crate) fn new(channel: Arc<Mutex<Channel>>) -> Self {
+        Self { channel }
+    }
+
+    /// Execute a select statement; its rows are fetched from the server as the result set is read.
+    pub fn execute_query(&self, sql: &str) -> Result<ResultSet> {
+        let response = self
+            .channel
+            .lock()
+            .unwrap()
+            .call(&Request::ExecuteQuery(sql.to_string()))?;
+        match response {
+            Response::ResultSet { id, schema } => {
+                Ok(ResultSet::new(Arc::clone(&self.channel), id, schema))
+            }
+            response => Err(unexpected(response)),
+        }
+    }
+
+    /// Execute an insert, delete, modify or create statement and return the number of affected records.
+    pub fn execute_update(&self, sql: &str) -> Result<usize> {
+        let response = self
+            .channel
+            .lock()
+            .unwrap()
+            .call(&Request::ExecuteUpdate(sql.to_string()))?;
+        match response {
+            Response::UpdateCount(count) => Ok(count as usize),
+            response => Err(unexpected(response)),
+        }
+    }
+}
@@ -1,106 +1,388 @@
 use anyhow::Result;
+use core::fmt;
 use std::{
     collections::HashMap,
     fs::{File, OpenOptions},
-    io::{Read, Seek, SeekFrom, Write},
+    os::unix::fs::{FileExt, MetadataExt, OpenOptionsExt},
     path::Path,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
 };
 
+use crate::record::temp_table::is_temp_table_file;
+
 use super::{block_id::BlockId, page::Page};
 
+const CHECKSUM_SIZE: usize = std::mem::size_of::<u32>();
+const LSN_SIZE: usize = std::mem::size_of::<i64>();
+/// The size of the header stored in front of each block when checksums are enabled.
+pub const BLOCK_HEADER_SIZE: usize = CHECKSUM_SIZE + LSN_SIZE;
+/// The file holding the double-write area.
+pub const DOUBLE_WRITE_FILE: &str = "simpledb.dblwr";
+// the file created to check whether the filesystem accepts direct I/O
+const DIRECT_IO_PROBE_FILE: &str = "simpledb.direct";
+
+#[derive(Debug)]
+pub enum FileManagerError {
+    /// The checksum stored in the block header does not match the block's contents,
+    /// typically because the block was only partially written.
+    Corrupted {
+        block: BlockId,
+        stored: u32,
+        computed: u32,
+    },
+    /// Direct I/O needs the block size to be a multiple of the alignment the filesystem requires for it,
+    /// which is usually the device's logical block size.
+    UnalignedBlockSize { block_size: usize, alignment: usize },
+    /// Blocks with checksum headers are not aligned, so they cannot be read and written with direct I/O.
+    DirectIoWithChecksums,
+}
+
+impl std::error::Error for FileManagerError {}
+impl fmt::Display for FileManagerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FileManagerError::Corrupted {
+                block,
+                stored,
+                computed,
+            } => write!(
+                f,
+                "block {} is corrupted: stored checksum {:#010x}, computed {:#010x}",
+                block, stored, computed
+            ),
+            FileManagerError::UnalignedBlockSize {
+                block_size,
+                alignment,
+            } => write!(
+                f,
+                "block size {} is not a multiple of the alignment {} required by direct I/O",
+                block_size, alignment
+            ),
+            FileManagerError::DirectIoWithChecksums => {
+                write!(f, "direct I/O cannot be used with checksums")
+            }
+        }
+    }
+}
+
+/// When the file manager syncs written blocks to disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Durability {
+    /// Every write is synced before it returns.
+    #[default]
+    Always,
+    /// Writes only mark their files dirty, and the files are synced by `sync` or `sync_all_dirty`,
+    /// which the log manager and the buffer manager call when the log is flushed and when a transaction commits.
+    OnCommit,
+    /// Files are never synced, which is only safe for tests and scratch databases.
+    Never,
+}
+
+/// The file manager reads and writes blocks with positional I/O, so its methods take `&self`,
+/// and blocks of the same or different files can be read and written in parallel.
 #[derive(Debug)]
 pub struct FileManager {
     db_dir: String,
     block_size: usize,
     is_new: bool,
-    open_files: HashMap<String, Arc<Mutex<File>>>,
-    total_blocks_read: usize,
-    total_blocks_write: usize,
+    checksums: bool,
+    double_write: bool,
+    durability: Durability,
+    direct_io: bool,
+    // the alignment of the pages created by the file manager, as direct I/O requires
+    alignment: usize,
+    open_files: Mutex<HashMap<String, Arc<File>>>,
+    // the files written since they were last synced; the lock is held while they are synced, so that a sync
+    // does not return while another one is still making the same writes durable
+    dirty_files: Mutex<HashMap<String, Arc<File>>>,
+    // appends compute the new block number from the file length, so they are serialized
+    append_lock: Mutex<()>,
+    // a batch keeps the double-write area until all of its blocks are written in place
+    double_write_lock: Mutex<()>,
+    total_blocks_read: AtomicUsize,
+    total_blocks_write: AtomicUsize,
 }
 
 impl FileManager {
     pub fn new(db_dir: &str, block_size: usize) -> Result<Self> {
         let path = Path::new(db_dir);
-        let is_new = !path.exists();
+        // an existing but empty directory holds no database yet
+        let is_new = !path.exists() || path.read_dir()?.next().is_none();
 
         if is_new {
             std::fs::create_dir_all(path)?;
         }
 
         // Remove any leftover temporary tables
-        std::fs::read_dir(path)?
-            .map(|entry| entry.unwrap().path())
-            .filter(|path| path.is_file() && path.starts_with("temp"))
-            .try_for_each(std::fs::remove_file)?;
+        for entry in std::fs::read_dir(path)? {
+            let path = entry?.path();
+            let is_temp = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(is_temp_table_file);
+            if path.is_file() && is_temp {
+                std::fs::remove_file(path)?;
+            }
+        }
 
         Ok(Self {
             db_dir: db_dir.to_string(),
             block_size,
             is_new,
-            open_files: HashMap::new(),
-            total_blocks_read: 0,
-            total_blocks_write: 0,
+            checksums: false,
+            double_write: false,
+            durability: Durability::default(),
+            direct_io: false,
+            alignment: 1,
+            open_files: Mutex::new(HashMap::new()),
+            dirty_files: Mutex::new(HashMap::new()),
+            append_lock: Mutex::new(()),
+            double_write_lock: Mutex::new(()),
+            total_blocks_read: AtomicUsize::new(0),
+            total_blocks_write: AtomicUsize::new(0),
         })
     }
 
-    pub fn read(&mut self, block: &BlockId, page: &mut Page) -> Result<()> {
+    /// Enable or disable block headers.
+    /// With headers, each block is stored on disk after a header holding a CRC32C checksum and the page's LSN,
+    /// and reading a block whose checksum does not match fails with `FileManagerError::Corrupted`.
+    /// The headers change the file format, so a database must always be opened with the same setting.
+    /// Blocks with headers are not aligned, so enabling checksums turns direct I/O off, as `direct_io` reports.
+    pub fn with_checksums(mut self, checksums: bool) -> Self {
+        self.checksums = checksums;
+        if checksums {
+            self.direct_io = false;
+            self.alignment = 1;
+        }
+        self
+    }
+
+    /// Enable or disable the double-write area.
+    /// Each batch of blocks is first written and synced to the double-write file, and only then written in place,
+    /// so that `restore_torn_blocks` can repair a block whose in-place write was interrupted by a crash.
+    /// This costs one extra write and sync of every batch of data pages; log writes, made with `write_log`, are not covered.
+    /// Torn blocks are detected by their checksums, so enabling the double-write area also enables checksums.
+    pub fn with_double_write(mut self, double_write: bool) -> Self {
+        self.double_write = double_write;
+        if double_write {
+            self = self.with_checksums(true);
+        }
+        self
+    }
+
+    /// Set when written blocks are synced to disk.
+    pub fn with_durability(mut self, durability: Durability) -> Self {
+        self.durability = durability;
+        self
+    }
+
+    /// Enable or disable direct I/O, which opens the files with `O_DIRECT` so that blocks bypass the OS page cache.
+    /// The block size must be a multiple of the alignment the filesystem requires for direct I/O, which is queried
+    /// with `statx`, and pages created by `new_page` are aligned to it.
+    /// If the filesystem does not support direct I/O, the file manager falls back to buffered I/O,
+    /// and `direct_io` returns false.
+    /// Blocks with checksum headers are not aligned, so enabling direct I/O fails when checksums are enabled.
+    pub fn with_direct_io(mut self, direct_io: bool) -> Result<Self> {
+        self.direct_io = false;
+        self.alignment = 1;
+        if !direct_io {
+            return Ok(self);
+        }
+        if self.checksums {
+            return Err(FileManagerError::DirectIoWithChecksums.into());
+        }
+        let Some(alignment) = Self::probe_direct_io(&self.db_dir)? else {
+            return Ok(self);
+        };
+        if !self.block_size.is_multiple_of(alignment) {
+            return Err(FileManagerError::UnalignedBlockSize {
+                block_size: self.block_size,
+                alignment,
+            }
+            .into());
+        }
+        self.direct_io = true;
+        self.alignment = alignment;
+        Ok(self)
+    }
+
+    /// Create a page of the block size, aligned for the file manager's I/O.
+    pub fn new_page(&self) -> Page {
+        Page::new_aligned(self.block_size, self.alignment)
+    }
+
+    pub fn read(&self, block: &BlockId, page: &mut Page) -> Result<()> {
         let file = self.get_file(block.filename())?;
-        let mut guard = file.lock().unwrap();
-        let pos = (block.block_number() * self.block_size) as u64;
-        guard.seek(SeekFrom::Start(pos))?;
+        let pos = (block.block_number() * self.disk_block_size()) as u64;
 
-        if guard.metadata()?.len() >= pos + page.contents().len() as u64 {
-            let mut temp_buf = vec![0u8; page.contents().len()];
-            guard.read_exact(&mut temp_buf)?;
-            page.contents().clear();
-            page.contents().write_bytes(&temp_buf);
+        if self.direct_io() {
+            self.read_direct(&file, page, pos)?;
+            self.total_blocks_read.fetch_add(1, Ordering::Relaxed);
+            return Ok(());
         }
 
-        self.total_blocks_read += 1;
+        // bytes past the end of the file read as zeros, so a reused page never keeps stale contents
+        let file_len = file.metadata()?.len();
+        let available = file_len
+            .saturating_sub(pos)
+            .min(self.disk_block_size() as u64) as usize;
+        let mut header = [0u8; BLOCK_HEADER_SIZE];
+        let header_len = if self.checksums {
+            available.min(BLOCK_HEADER_SIZE)
+        } else {
+            0
+        };
+        file.read_exact_at(&mut header[..header_len], pos)?;
+        let buf = page.contents_mut();
+        buf.fill(0);
+        file.read_exact_at(&mut buf[..available - header_len], pos + header_len as u64)?;
+        page.set_lsn(-1);
+
+        // a block past the end of the file has never been written, so there is nothing to verify
+        if self.checksums && available > 0 {
+            let stored = u32::from_be_bytes(header[..CHECKSUM_SIZE].try_into()?);
+            let computed = Self::checksum(&header[CHECKSUM_SIZE..], page.contents());
+            if stored != computed {
+                return Err(FileManagerError::Corrupted {
+                    block: block.clone(),
+                    stored,
+                    computed,
+                }
+                .into());
+            }
+            page.set_lsn(i64::from_be_bytes(header[CHECKSUM_SIZE..].try_into()?));
+        }
+
+        self.total_blocks_read.fetch_add(1, Ordering::Relaxed);
 
         Ok(())
     }
 
-    pub fn write(&mut self, block: &BlockId, page: &mut Page) -> Result<()> {
-        let file = self.get_file(block.filename())?;
-        let mut guard = file.lock().unwrap();
-        guard.seek(SeekFrom::Start(
-            (block.block_number() * self.block_size) as u64,
-        ))?;
-        guard.write_all(page.contents().as_bytes())?;
-        guard.sync_all()?;
+    pub fn write(&self, block: &BlockId, page: &Page) -> Result<()> {
+        self.write_batch(&[(block, page)])
+    }
+
+    /// Write a block of the log.
+    /// Log writes bypass the double-write area: the log manager never writes a block again once its records are flushed,
+    /// so a torn log write only loses records whose flush did not complete, and a log flush costs a single write and sync.
+    pub fn write_log(&self, block: &BlockId, page: &Page) -> Result<()> {
+        self.write_pages(&[(block, page)], false)
+    }
 
-        self.total_blocks_write += 1;
+    /// Write the pages to their blocks.
+    /// With the `Always` durability each file is synced once, otherwise the files are marked dirty.
+    /// If the double-write area is enabled, the whole batch is written to it first.
+    /// Writes to the double-write file are not counted in the statistics.
+    pub fn write_batch(&self, pages: &[(&BlockId, &Page)]) -> Result<()> {
+        self.write_pages(pages, self.double_write)
+    }
+
+    fn write_pages(&self, pages: &[(&BlockId, &Page)], double_write: bool) -> Result<()> {
+        let _guard = if double_write {
+            let guard = self.double_write_lock.lock().unwrap();
+            // the blocks of earlier batches must be on disk before their copies are replaced
+            self.sync_all_dirty()?;
+            self.write_double_write_area(pages)?;
+            Some(guard)
+        } else {
+            None
+        };
+
+        let mut files: Vec<(&str, Arc<File>)> = vec![];
+        for (block, page) in pages {
+            let file = self.get_file(block.filename())?;
+            self.write_block(&file, block, page)?;
+            if !files.iter().any(|(_, f)| Arc::ptr_eq(f, &file)) {
+                files.push((block.filename(), file));
+            }
+        }
+        if self.durability == Durability::Always {
+            for (_, file) in files {
+                self.sync_file(&file)?;
+            }
+        } else {
+            let mut dirty_files = self.dirty_files.lock().unwrap();
+            for (filename, file) in files {
+                dirty_files.insert(filename.to_string(), file);
+            }
+        }
+
+        self.total_blocks_write
+            .fetch_add(pages.len(), Ordering::Relaxed);
 
         Ok(())
     }
 
-    pub fn append(&mut self, filename: &str) -> Result<BlockId> {
+    /// Append an empty block to the file.
+    /// The empty page bypasses the double-write area, since the block held nothing that a torn write could lose.
+    pub fn append(&self, filename: &str) -> Result<BlockId> {
+        let _guard = self.append_lock.lock().unwrap();
         let new_block_number = self.length(filename)?;
         let block = BlockId::new(filename, new_block_number);
-        let bytes = vec![0u8; self.block_size];
+        let page = Page::new(self.block_size);
+        self.write_pages(&[(&block, &page)], false)?;
+        Ok(block)
+    }
 
-        let file = self.get_file(block.filename())?;
-        let mut guard = file.lock().unwrap();
-        guard.seek(SeekFrom::Start(
-            (block.block_number() * self.block_size) as u64,
-        ))?;
-        guard.write_all(&bytes)?;
-        guard.sync_all()?;
+    /// Repair the blocks whose in-place write was interrupted by a crash, from the copies in the double-write area.
+    /// Only blocks that fail their checksum are restored; the others were either written completely or not at all.
+    /// Return the number of restored blocks.
+    pub fn restore_torn_blocks(&self) -> Result<usize> {
+        if !self.double_write {
+            return Ok(0);
+        }
+        let _guard = self.double_write_lock.lock().unwrap();
+        let file = self.get_file(DOUBLE_WRITE_FILE)?;
+        let mut bytes = vec![0; file.metadata()?.len() as usize];
+        file.read_exact_at(&mut bytes, 0)?;
+
+        let mut restored = 0;
+        let mut page = Page::new(self.block_size);
+        for (block, copy) in self.parse_double_write_area(&bytes) {
+            let err = match self.read(&block, &mut page) {
+                Ok(()) => continue,
+                Err(err) => err,
+            };
+            if !matches!(
+                err.downcast_ref::<FileManagerError>(),
+                Some(FileManagerError::Corrupted { .. })
+            ) {
+                return Err(err);
+            }
+            let file = self.get_file(block.filename())?;
+            self.write_block(&file, &block, &copy)?;
+            self.sync_file(&file)?;
+            restored += 1;
+        }
+        Ok(restored)
+    }
 
-        self.total_blocks_write += 1;
+    /// Sync the specified file, if it has been written since it was last synced.
+    pub fn sync(&self, filename: &str) -> Result<()> {
+        let mut dirty_files = self.dirty_files.lock().unwrap();
+        if let Some(file) = dirty_files.remove(filename) {
+            self.sync_file(&file)?;
+        }
+        Ok(())
+    }
 
-        Ok(block)
+    /// Sync every file that has been written since it was last synced.
+    pub fn sync_all_dirty(&self) -> Result<()> {
+        let mut dirty_files = self.dirty_files.lock().unwrap();
+        for (_, file) in dirty_files.drain() {
+            self.sync_file(&file)?;
+        }
+        Ok(())
     }
 
-    pub fn length(&mut self, filename: &str) -> Result<usize> {
+    pub fn length(&self, filename: &str) -> Result<usize> {
         let file = self.get_file(filename)?;
-        let guard = file.lock().unwrap();
-        let len = guard.metadata()?.len();
+        let len = file.metadata()?.len();
 
-        //ceiling
-        Ok((len as usize + self.block_size - 1) / self.block_size)
+        Ok((len as usize).div_ceil(self.disk_block_size()))
     }
 
     pub fn is_new(&self) -> bool {
@@ -111,30 +393,237 @@ impl FileManager {
         self.block_size
     }
 
+    pub fn checksums(&self) -> bool {
+        self.checksums
+    }
+
+    pub fn durability(&self) -> Durability {
+        self.durability
+    }
+
+    /// Return true if the files are opened for direct I/O.
+    pub fn direct_io(&self) -> bool {
+        self.direct_io
+    }
+
     pub fn get_total_blocks_read(&self) -> usize {
-        self.total_blocks_read
+        self.total_blocks_read.load(Ordering::Relaxed)
     }
 
     pub fn get_total_blocks_write(&self) -> usize {
-        self.total_blocks_write
+        self.total_blocks_write.load(Ordering::Relaxed)
+    }
+
+    /// Return the size of a block on disk, including its header.
+    fn disk_block_size(&self) -> usize {
+        if self.checksums {
+            BLOCK_HEADER_SIZE + self.block_size
+        } else {
+            self.block_size
+        }
+    }
+
+    fn sync_file(&self, file: &File) -> Result<()> {
+        if self.durability != Durability::Never {
+            file.sync_all()?;
+        }
+        Ok(())
+    }
+
+    /// Write the page, preceded by its header if checksums are enabled, without syncing the file.
+    fn write_block(&self, file: &File, block: &BlockId, page: &Page) -> Result<()> {
+        let mut pos = (block.block_number() * self.disk_block_size()) as u64;
+        if self.checksums {
+            let lsn = page.lsn().to_be_bytes();
+            let checksum = Self::checksum(&lsn, page.contents());
+            let mut header = [0u8; BLOCK_HEADER_SIZE];
+            header[..CHECKSUM_SIZE].copy_from_slice(&checksum.to_be_bytes());
+            header[CHECKSUM_SIZE..].copy_from_slice(&lsn);
+            file.write_all_at(&header, pos)?;
+            pos += BLOCK_HEADER_SIZE as u64;
+        }
+        if self.direct_io() && !page.is_aligned(self.alignment) {
+            let mut aligned = self.new_page();
+            aligned.contents_mut().copy_from_slice(page.contents());
+            file.write_all_at(aligned.contents(), pos)?;
+        } else {
+            file.write_all_at(page.contents(), pos)?;
+        }
+        Ok(())
+    }
+
+    /// Read a block with direct I/O, which needs the whole block to be read into aligned memory.
+    /// Unaligned pages are read through an aligned copy.
+    fn read_direct(&self, file: &File, page: &mut Page, pos: u64) -> Result<()> {
+        if !page.is_aligned(self.alignment) {
+            let mut aligned = self.new_page();
+            self.read_direct(file, &mut aligned, pos)?;
+            page.contents_mut().copy_from_slice(aligned.contents());
+            page.set_lsn(-1);
+            return Ok(());
+        }
+        // the read stops short at the end of the file, and the rest of the page reads as zeros
+        let buf = page.contents_mut();
+        let n = file.read_at(buf, pos)?;
+        buf[n..].fill(0);
+        page.set_lsn(-1);
+        Ok(())
+    }
+
+    /// Check whether files in the directory can be opened with `O_DIRECT`, and return the alignment it requires,
+    /// or `None` if the filesystem does not support direct I/O.
+    /// Some filesystems, such as tmpfs on older kernels, reject `O_DIRECT` with `EINVAL`.
+    fn probe_direct_io(db_dir: &str) -> Result<Option<usize>> {
+        if cfg!(not(target_os = "linux")) {
+            return Ok(None);
+        }
+        let path = Path::new(db_dir).join(DIRECT_IO_PROBE_FILE);
+        let result = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .custom_flags(Self::direct_io_flags())
+            .open(&path);
+        let alignment = match result {
+            Ok(file) => Self::direct_io_alignment(&file),
+            Err(err) if err.raw_os_error() == Some(libc::EINVAL) => Ok(None),
+            Err(err) => Err(err.into()),
+        };
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        alignment
+    }
+
+    /// Return the alignment of file offsets, lengths and memory that direct I/O requires for the file,
+    /// or `None` if the file does not support direct I/O.
+    /// Kernels before 6.1 do not report it, in which case the filesystem's preferred I/O size is used,
+    /// which is a multiple of the device's logical block size.
+    #[cfg(target_os = "linux")]
+    fn direct_io_alignment(file: &File) -> Result<Option<usize>> {
+        use std::os::fd::AsRawFd;
+
+        // SAFETY: statx only writes into the zero-initialized buffer, which is a plain C struct
+        let mut stx: libc::statx = unsafe { std::mem::zeroed() };
+        let ret = unsafe {
+            libc::statx(
+                file.as_raw_fd(),
+                c"".as_ptr(),
+                libc::AT_EMPTY_PATH,
+                libc::STATX_DIOALIGN,
+                &mut stx,
+            )
+        };
+        if ret != 0 || stx.stx_mask & libc::STATX_DIOALIGN == 0 {
+            return Ok(Some(file.metadata()?.blksize() as usize));
+        }
+        if stx.stx_dio_offset_align == 0 {
+            return Ok(None);
+        }
+        Ok(Some(
+            stx.stx_dio_offset_align.max(stx.stx_dio_mem_align) as usize
+        ))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn direct_io_alignment(_: &File) -> Result<Option<usize>> {
+        Ok(None)
     }
 
-    fn get_file(&mut self, filename: &str) -> Result<Arc<Mutex<File>>> {
-        if let Some(file) = self.open_files.get(filename) {
+    #[cfg(target_os = "linux")]
+    fn direct_io_flags() -> i32 {
+        libc::O_DIRECT
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn direct_io_flags() -> i32 {
+        0
+    }
+
+    /// Replace the contents of the double-write area with the pages, and sync it.
+    /// Each entry holds a checksum of the rest of the entry, the block's filename and number, the page's LSN and
+    /// the page contents.
+    fn write_double_write_area(&self, pages: &[(&BlockId, &Page)]) -> Result<()> {
+        let mut bytes = vec![];
+        for (block, page) in pages {
+            let mut entry = vec![];
+            entry.extend_from_slice(&(block.filename().len() as u32).to_be_bytes());
+            entry.extend_from_slice(block.filename().as_bytes());
+            entry.extend_from_slice(&(block.block_number() as u64).to_be_bytes());
+            entry.extend_from_slice(&page.lsn().to_be_bytes());
+            entry.extend_from_slice(page.contents());
+            bytes.extend_from_slice(&crc32c::crc32c(&entry).to_be_bytes());
+            bytes.extend_from_slice(&entry);
+        }
+
+        let file = self.get_file(DOUBLE_WRITE_FILE)?;
+        file.write_all_at(&bytes, 0)?;
+        file.set_len(bytes.len() as u64)?;
+        self.sync_file(&file)?;
+        Ok(())
+    }
+
+    /// Return the blocks and pages stored in the double-write area.
+    /// Parsing stops at the first incomplete or damaged entry, which can only be left by a crash while the area
+    /// itself was being written, before any of the batch's blocks were written in place.
+    fn parse_double_write_area(&self, bytes: &[u8]) -> Vec<(BlockId, Page)> {
+        let mut entries = vec![];
+        let mut rest = bytes;
+        while let Some((entry, next)) = self.parse_double_write_entry(rest) {
+            entries.push(entry);
+            rest = next;
+        }
+        entries
+    }
+
+    fn parse_double_write_entry<'a>(&self, bytes: &'a [u8]) -> Option<((BlockId, Page), &'a [u8])> {
+        let (checksum, entry) = bytes.split_first_chunk::<CHECKSUM_SIZE>()?;
+        let (name_len, rest) = entry.split_first_chunk::<4>()?;
+        let name_len = u32::from_be_bytes(*name_len) as usize;
+        if rest.len() < name_len {
+            return None;
+        }
+        let (filename, rest) = rest.split_at(name_len);
+        let (block_number, rest) = rest.split_first_chunk::<8>()?;
+        let (lsn, rest) = rest.split_first_chunk::<LSN_SIZE>()?;
+        if rest.len() < self.block_size {
+            return None;
+        }
+        let (contents, next) = rest.split_at(self.block_size);
+        let entry_len = entry.len() - next.len();
+        if crc32c::crc32c(&entry[..entry_len]) != u32::from_be_bytes(*checksum) {
+            return None;
+        }
+
+        let block = BlockId::new(
+            std::str::from_utf8(filename).ok()?,
+            u64::from_be_bytes(*block_number) as usize,
+        );
+        let mut page = Page::from_bytes(contents.to_vec());
+        page.set_lsn(i64::from_be_bytes(*lsn));
+        Some(((block, page), next))
+    }
+
+    /// The checksum covers the LSN in the header as well as the page contents.
+    fn checksum(lsn: &[u8], contents: &[u8]) -> u32 {
+        crc32c::crc32c_append(crc32c::crc32c(lsn), contents)
+    }
+
+    fn get_file(&self, filename: &str) -> Result<Arc<File>> {
+        let mut open_files = self.open_files.lock().unwrap();
+        if let Some(file) = open_files.get(filename) {
             Ok(Arc::clone(file))
         } else {
             let path = Path::new(&self.db_dir).join(filename);
 
-            let file = Arc::new(Mutex::new(
-                OpenOptions::new()
-                    .read(true)
-                    .write(true)
-                    .create(true)
-                    .open(&path)?,
-            ));
+            let mut options = OpenOptions::new();
+            options.read(true).write(true).create(true).truncate(false);
+            if self.direct_io() {
+                options.custom_flags(Self::direct_io_flags());
+            }
+            let file = Arc::new(options.open(&path)?);
 
-            self.open_files
-                .insert(filename.to_string(), Arc::clone(&file));
+            open_files.insert(filename.to_string(), Arc::clone(&file));
 
             Ok(file)
         }
@@ -144,10 +633,15 @@ impl FileManager {
 #[cfg(test)]
 mod test {
 
+    use std::{sync::Arc, thread};
+
     use chrono::NaiveDate;
     use tempfile::tempdir;
 
-    use super::FileManager;
+    use super::{
+        Durability, FileManager, FileManagerError, BLOCK_HEADER_SIZE, DIRECT_IO_PROBE_FILE,
+        DOUBLE_WRITE_FILE,
+    };
     use crate::file::{block_id::BlockId, page::Page};
 
     #[test]
@@ -156,7 +650,7 @@ mod test {
         let db_dir = temp_dir.path().to_str().unwrap();
         let block_size = 512;
 
-        let mut file_manager = FileManager::new(db_dir, block_size).unwrap();
+        let file_manager = FileManager::new(db_dir, block_size).unwrap();
 
         let filename = "simple_short.tbl";
         let block = BlockId::new(filename, 0);
@@ -165,7 +659,7 @@ mod test {
         page.set_short(0, 10).unwrap();
 
         // write the page
-        file_manager.write(&block, &mut page).unwrap();
+        file_manager.write(&block, &page).unwrap();
 
         // read the page
         file_manager.read(&block, &mut page).unwrap();
@@ -180,7 +674,7 @@ mod test {
         let db_dir = temp_dir.path().to_str().unwrap();
         let block_size = 512;
 
-        let mut file_manager = FileManager::new(db_dir, block_size).unwrap();
+        let file_manager = FileManager::new(db_dir, block_size).unwrap();
 
         let filename = "simple_int.tbl";
         let block = BlockId::new(filename, 0);
@@ -189,7 +683,7 @@ mod test {
         page.set_int(0, 42).unwrap();
 
         // write the page
-        file_manager.write(&block, &mut page).unwrap();
+        file_manager.write(&block, &page).unwrap();
 
         // read the page
         file_manager.read(&block, &mut page).unwrap();
@@ -204,7 +698,7 @@ mod test {
         let db_dir = temp_dir.path().to_str().unwrap();
         let block_size = 512;
 
-        let mut file_manager = FileManager::new(db_dir, block_size).unwrap();
+        let file_manager = FileManager::new(db_dir, block_size).unwrap();
 
         let filename = "simple_long.tbl";
         let block = BlockId::new(filename, 0);
@@ -213,7 +707,7 @@ mod test {
         page.set_long(0, i64::MAX).unwrap();
 
         // write the page
-        file_manager.write(&block, &mut page).unwrap();
+        file_manager.write(&block, &page).unwrap();
 
         // read the page
         file_manager.read(&block, &mut page).unwrap();
@@ -228,7 +722,7 @@ mod test {
         let db_dir = temp_dir.path().to_str().unwrap();
         let block_size = 512;
 
-        let mut file_manager = FileManager::new(db_dir, block_size).unwrap();
+        let file_manager = FileManager::new(db_dir, block_size).unwrap();
 
         let filename = "simple_bytes.tbl";
         let block = BlockId::new(filename, 0);
@@ -238,7 +732,7 @@ mod test {
         page.set_bytes(0, &test_bytes).unwrap();
 
         // write the page
-        file_manager.write(&block, &mut page).unwrap();
+        file_manager.write(&block, &page).unwrap();
 
         // read the page
         file_manager.read(&block, &mut page).unwrap();
@@ -253,7 +747,7 @@ mod test {
         let db_dir = temp_dir.path().to_str().unwrap();
         let block_size = 512;
 
-        let mut file_manager = FileManager::new(db_dir, block_size).unwrap();
+        let file_manager = FileManager::new(db_dir, block_size).unwrap();
 
         let filename = "simple_string.tbl";
         let block = BlockId::new(filename, 0);
@@ -262,7 +756,7 @@ mod test {
         page.set_string(0, "Hello, SimpleDB!").unwrap();
 
         // write the page
-        file_manager.write(&block, &mut page).unwrap();
+        file_manager.write(&block, &page).unwrap();
 
         // read the page
         file_manager.read(&block, &mut page).unwrap();
@@ -277,7 +771,7 @@ mod test {
         let db_dir = temp_dir.path().to_str().unwrap();
         let block_size = 512;
 
-        let mut file_manager = FileManager::new(db_dir, block_size).unwrap();
+        let file_manager = FileManager::new(db_dir, block_size).unwrap();
 
         let filename = "simple.tbl";
 
@@ -296,7 +790,7 @@ mod test {
         let db_dir = temp_dir.path().to_str().unwrap();
         let block_size = 512;
 
-        let mut file_manager = FileManager::new(db_dir, block_size).unwrap();
+        let file_manager = FileManager::new(db_dir, block_size).unwrap();
 
         let filename = "simple_bool.tbl";
         let block = BlockId::new(filename, 0);
@@ -306,7 +800,7 @@ mod test {
         page.set_bool(1, false).unwrap();
 
         // write the page
-        file_manager.write(&block, &mut page).unwrap();
+        file_manager.write(&block, &page).unwrap();
 
         // read the page
         file_manager.read(&block, &mut page).unwrap();
@@ -322,7 +816,7 @@ mod test {
         let db_dir = temp_dir.path().to_str().unwrap();
         let block_size = 512;
 
-        let mut file_manager = FileManager::new(db_dir, block_size).unwrap();
+        let file_manager = FileManager::new(db_dir, block_size).unwrap();
 
         let filename = "simple_date.tbl";
         let block = BlockId::new(filename, 0);
@@ -332,7 +826,7 @@ mod test {
         page.set_date(0, test_date).unwrap();
 
         // write the page
-        file_manager.write(&block, &mut page).unwrap();
+        file_manager.write(&block, &page).unwrap();
 
         // read the page
         file_manager.read(&block, &mut page).unwrap();
@@ -347,7 +841,7 @@ mod test {
         let db_dir = temp_dir.path().to_str().unwrap();
         let block_size = 512;
 
-        let mut file_manager = FileManager::new(db_dir, block_size).unwrap();
+        let file_manager = FileManager::new(db_dir, block_size).unwrap();
 
         let filename = "stats_test.tbl";
         let block = BlockId::new(filename, 0);
@@ -358,7 +852,7 @@ mod test {
         assert_eq!(file_manager.get_total_blocks_read(), 0);
         assert_eq!(file_manager.get_total_blocks_write(), 0);
 
-        file_manager.write(&block, &mut page).unwrap();
+        file_manager.write(&block, &page).unwrap();
         assert_eq!(file_manager.get_total_blocks_write(), 1);
 
         file_manager.read(&block, &mut page).unwrap();
@@ -367,4 +861,339 @@ mod test {
         file_manager.append(filename).unwrap();
         assert_eq!(file_manager.get_total_blocks_write(), 2);
     }
+
+    #[test]
+    fn test_remove_temp_tables() {
+        let temp_dir = tempdir().unwrap();
+        let db_dir = temp_dir.path().to_str().unwrap();
+        let block_size = 512;
+
+        let file_manager = FileManager::new(db_dir, block_size).unwrap();
+        let user_files = [
+            "student.tbl",
+            "attempts.tbl",
+            "temperature.tbl",
+            "temp_readings.tbl",
+            "template.tbl",
+            "temp.tbl",
+            "temp1.tbl.bak",
+        ];
+        for filename in ["temp1.tbl", "temp23.tbl"].iter().chain(&user_files) {
+            file_manager.append(filename).unwrap();
+        }
+        drop(file_manager);
+
+        let file_manager = FileManager::new(db_dir, block_size).unwrap();
+        assert!(!file_manager.is_new());
+        assert!(!temp_dir.path().join("temp1.tbl").exists());
+        assert!(!temp_dir.path().join("temp23.tbl").exists());
+        for filename in user_files {
+            assert!(temp_dir.path().join(filename).exists(), "{}", filename);
+        }
+    }
+
+    #[test]
+    fn test_checksums() {
+        let temp_dir = tempdir().unwrap();
+        let db_dir = temp_dir.path().to_str().unwrap();
+        let block_size = 512;
+
+        let file_manager = FileManager::new(db_dir, block_size)
+            .unwrap()
+            .with_checksums(true);
+
+        let filename = "checksums.tbl";
+        file_manager.append(filename).unwrap();
+        let block = BlockId::new(filename, 1);
+        let mut page = Page::new(block_size);
+        page.set_int(0, 42).unwrap();
+        page.set_int(block_size - 4, 43).unwrap();
+        page.set_lsn(7);
+        file_manager.write(&block, &page).unwrap();
+        assert_eq!(file_manager.length(filename).unwrap(), 2);
+
+        // the contents and the LSN survive the round trip
+        let mut page = Page::new(block_size);
+        file_manager.read(&block, &mut page).unwrap();
+        assert_eq!(page.get_int(0).unwrap(), 42);
+        assert_eq!(page.get_int(block_size - 4).unwrap(), 43);
+        assert_eq!(page.lsn(), 7);
+        file_manager
+            .read(&BlockId::new(filename, 0), &mut page)
+            .unwrap();
+        assert_eq!(page.get_int(0).unwrap(), 0);
+        assert_eq!(page.lsn(), -1);
+
+        // simulate a torn write, by damaging the second half of the block on disk
+        let path = temp_dir.path().join(filename);
+        let mut bytes = std::fs::read(&path).unwrap();
+        let disk_block_size = BLOCK_HEADER_SIZE + block_size;
+        bytes[disk_block_size + BLOCK_HEADER_SIZE + 300] = 0xff;
+        std::fs::write(&path, &bytes).unwrap();
+
+        let file_manager = FileManager::new(db_dir, block_size)
+            .unwrap()
+            .with_checksums(true);
+        let err = file_manager.read(&block, &mut page).unwrap_err();
+        match err.downcast_ref::<FileManagerError>() {
+            Some(FileManagerError::Corrupted { block: b, .. }) => assert_eq!(b, &block),
+            _ => panic!("unexpected error: {}", err),
+        }
+        assert!(err
+            .to_string()
+            .starts_with("block [file checksums.tbl, block 1] is corrupted"));
+
+        // a block cut short by a crash is corrupted too
+        std::fs::write(&path, &bytes[..disk_block_size + 100]).unwrap();
+        assert!(file_manager.read(&block, &mut page).is_err());
+        file_manager
+            .read(&BlockId::new(filename, 0), &mut page)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_double_write() {
+        let temp_dir = tempdir().unwrap();
+        let db_dir = temp_dir.path().to_str().unwrap();
+        let block_size = 512;
+        let open = || {
+            FileManager::new(db_dir, block_size)
+                .unwrap()
+                .with_double_write(true)
+        };
+
+        let file_manager = open();
+        assert!(file_manager.checksums());
+        let filename = "double_write.tbl";
+        let blocks: Vec<_> = (0..3).map(|i| BlockId::new(filename, i)).collect();
+        let pages: Vec<_> = (0..3)
+            .map(|i| {
+                let mut page = Page::new(block_size);
+                page.set_int(0, i).unwrap();
+                page.set_int(block_size - 4, i * 10).unwrap();
+                page
+            })
+            .collect();
+        file_manager
+            .write_batch(&[(&blocks[0], &pages[0]), (&blocks[1], &pages[1])])
+            .unwrap();
+        assert_eq!(file_manager.get_total_blocks_write(), 2);
+        // the next batch replaces the contents of the double-write area
+        file_manager
+            .write_batch(&[(&blocks[1], &pages[1]), (&blocks[2], &pages[2])])
+            .unwrap();
+        drop(file_manager);
+
+        // simulate a crash that tore the in-place writes of blocks 1 and 2
+        let path = temp_dir.path().join(filename);
+        let bytes = std::fs::read(&path).unwrap();
+        let disk_block_size = BLOCK_HEADER_SIZE + block_size;
+        let mut torn = bytes[..2 * disk_block_size + 100].to_vec();
+        torn[disk_block_size + BLOCK_HEADER_SIZE + block_size - 1] ^= 0xff;
+        std::fs::write(&path, &torn).unwrap();
+
+        let file_manager = open();
+        let mut page = Page::new(block_size);
+        assert!(file_manager.read(&blocks[1], &mut page).is_err());
+        assert_eq!(file_manager.restore_torn_blocks().unwrap(), 2);
+        for (i, block) in blocks.iter().enumerate() {
+            file_manager.read(block, &mut page).unwrap();
+            assert_eq!(page.get_int(0).unwrap(), i as i32);
+            assert_eq!(page.get_int(block_size - 4).unwrap(), i as i32 * 10);
+        }
+        assert_eq!(std::fs::read(&path).unwrap(), bytes);
+
+        // a damaged entry, left by a crash while writing the double-write area, is ignored
+        let dblwr_path = temp_dir.path().join(DOUBLE_WRITE_FILE);
+        let mut dblwr = std::fs::read(&dblwr_path).unwrap();
+        let last = dblwr.len() - 1;
+        dblwr[last] ^= 0xff;
+        std::fs::write(&dblwr_path, &dblwr).unwrap();
+        std::fs::write(&path, &torn).unwrap();
+        let file_manager = open();
+        assert_eq!(file_manager.restore_torn_blocks().unwrap(), 1);
+        file_manager.read(&blocks[1], &mut page).unwrap();
+        assert_eq!(page.get_int(block_size - 4).unwrap(), 10);
+
+        // log writes bypass the double-write area
+        let dblwr = std::fs::read(&dblwr_path).unwrap();
+        let log_block = BlockId::new("simpledb.log", 0);
+        file_manager.write_log(&log_block, &pages[0]).unwrap();
+        assert_eq!(std::fs::read(&dblwr_path).unwrap(), dblwr);
+        file_manager.read(&log_block, &mut page).unwrap();
+        assert_eq!(page.get_int(0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_concurrent_io() {
+        let temp_dir = tempdir().unwrap();
+        let db_dir = temp_dir.path().to_str().unwrap();
+        let block_size = 512;
+        let file_manager = Arc::new(FileManager::new(db_dir, block_size).unwrap());
+
+        // each thread appends its own blocks and reads them back, without any external locking
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let file_manager = Arc::clone(&file_manager);
+                thread::spawn(move || {
+                    let mut page = Page::new(block_size);
+                    for i in 0..10 {
+                        let block = file_manager.append("concurrent.tbl").unwrap();
+                        page.set_int(0, t * 100 + i).unwrap();
+                        file_manager.write(&block, &page).unwrap();
+                        file_manager.read(&block, &mut page).unwrap();
+                        assert_eq!(page.get_int(0).unwrap(), t * 100 + i);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        // the appends never handed out the same block twice
+        assert_eq!(file_manager.length("concurrent.tbl").unwrap(), 40);
+        let mut values = vec![];
+        let mut page = Page::new(block_size);
+        for i in 0..40 {
+            file_manager
+                .read(&BlockId::new("concurrent.tbl", i), &mut page)
+                .unwrap();
+            values.push(page.get_int(0).unwrap());
+        }
+        values.sort();
+        let expected: Vec<_> = (0..4)
+            .flat_map(|t| (0..10).map(move |i| t * 100 + i))
+            .collect();
+        assert_eq!(values, expected);
+        assert_eq!(file_manager.get_total_blocks_write(), 80);
+        assert_eq!(file_manager.get_total_blocks_read(), 80);
+    }
+
+    #[test]
+    fn test_durability() {
+        let temp_dir = tempdir().unwrap();
+        let db_dir = temp_dir.path().to_str().unwrap();
+        let block_size = 512;
+        let is_dirty = |file_manager: &FileManager, filename: &str| {
+            file_manager
+                .dirty_files
+                .lock()
+                .unwrap()
+                .contains_key(filename)
+        };
+
+        let file_manager = FileManager::new(db_dir, block_size)
+            .unwrap()
+            .with_durability(Durability::OnCommit);
+        let mut page = Page::new(block_size);
+        page.set_int(0, 42).unwrap();
+        file_manager
+            .write(&BlockId::new("a.tbl", 0), &page)
+            .unwrap();
+        file_manager.append("b.tbl").unwrap();
+        assert!(is_dirty(&file_manager, "a.tbl"));
+        assert!(is_dirty(&file_manager, "b.tbl"));
+
+        file_manager.sync("a.tbl").unwrap();
+        assert!(!is_dirty(&file_manager, "a.tbl"));
+        assert!(is_dirty(&file_manager, "b.tbl"));
+        file_manager.sync_all_dirty().unwrap();
+        assert!(!is_dirty(&file_manager, "b.tbl"));
+
+        // unsynced writes are still visible to reads
+        for durability in [Durability::OnCommit, Durability::Never] {
+            let file_manager = FileManager::new(db_dir, block_size)
+                .unwrap()
+                .with_durability(durability);
+            page.set_int(0, 43).unwrap();
+            file_manager
+                .write(&BlockId::new("a.tbl", 1), &page)
+                .unwrap();
+            file_manager
+                .read(&BlockId::new("a.tbl", 0), &mut page)
+                .unwrap();
+            assert_eq!(page.get_int(0).unwrap(), 42);
+            file_manager
+                .read(&BlockId::new("a.tbl", 1), &mut page)
+                .unwrap();
+            assert_eq!(page.get_int(0).unwrap(), 43);
+        }
+
+        let file_manager = FileManager::new(db_dir, block_size).unwrap();
+        assert_eq!(file_manager.durability(), Durability::Always);
+        file_manager
+            .write(&BlockId::new("a.tbl", 2), &page)
+            .unwrap();
+        assert!(!is_dirty(&file_manager, "a.tbl"));
+    }
+
+    #[test]
+    fn test_direct_io() {
+        let temp_dir = tempdir().unwrap();
+        let db_dir = temp_dir.path().to_str().unwrap();
+
+        // the block size is only validated when the filesystem supports direct I/O
+        let result = FileManager::new(db_dir, 100).unwrap().with_direct_io(true);
+        match FileManager::probe_direct_io(db_dir).unwrap() {
+            Some(alignment) => {
+                assert!(alignment.is_power_of_two() && alignment > 100);
+                assert!(matches!(
+                    result.unwrap_err().downcast_ref::<FileManagerError>(),
+                    Some(FileManagerError::UnalignedBlockSize {
+                        block_size: 100,
+                        ..
+                    })
+                ));
+            }
+            None => assert!(!result.unwrap().direct_io()),
+        }
+
+        // whether or not the filesystem supports direct I/O, blocks are read and written the same way
+        let block_size = 4096;
+        let file_manager = FileManager::new(db_dir, block_size)
+            .unwrap()
+            .with_direct_io(true)
+            .unwrap();
+        assert!(!temp_dir.path().join(DIRECT_IO_PROBE_FILE).exists());
+        let filename = "direct.tbl";
+        let block = file_manager.append(filename).unwrap();
+
+        let mut page = file_manager.new_page();
+        page.set_int(0, 42).unwrap();
+        file_manager.write(&block, &page).unwrap();
+        // a page that is not aligned for direct I/O goes through an aligned copy
+        let mut unaligned = Page::new(block_size);
+        unaligned.set_int(0, 43).unwrap();
+        file_manager
+            .write(&BlockId::new(filename, 1), &unaligned)
+            .unwrap();
+
+        for (i, expected) in [(0, 42), (1, 43), (2, 0)] {
+            let block = BlockId::new(filename, i);
+            file_manager.read(&block, &mut page).unwrap();
+            assert_eq!(page.get_int(0).unwrap(), expected);
+            file_manager.read(&block, &mut unaligned).unwrap();
+            assert_eq!(unaligned.get_int(0).unwrap(), expected);
+        }
+        assert_eq!(file_manager.length(filename).unwrap(), 2);
+
+        // checksum headers leave the blocks unaligned, so direct I/O cannot be used with them
+        let err = FileManager::new(db_dir, block_size)
+            .unwrap()
+            .with_checksums(true)
+            .with_direct_io(true)
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<FileManagerError>(),
+            Some(FileManagerError::DirectIoWithChecksums)
+        ));
+        let file_manager = FileManager::new(db_dir, block_size)
+            .unwrap()
+            .with_direct_io(true)
+            .unwrap()
+            .with_double_write(true);
+        assert!(!file_manager.direct_io());
+        assert!(file_manager.new_page().is_aligned(1));
+    }
 }
@@ -1,11 +1,30 @@
-use anyhow::{bail, Result};
-use bytebuffer::ByteBuffer;
 use chrono::{Datelike, NaiveDate};
 use core::fmt;
 
-#[derive(Debug)]
-enum PageError {
-    BufferSizeExceeded,
+use crate::Lsn;
+
+/// The errors returned by the accessors of a page.
+/// Each error records where in the page it happened, so that a corrupted block can be diagnosed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PageError {
+    /// A value of len bytes at the offset does not fit in a page of capacity bytes.
+    OutOfBounds {
+        offset: usize,
+        len: usize,
+        capacity: usize,
+    },
+    /// The length prefix at the offset is larger than the rest of the page.
+    LengthPrefixTooLarge {
+        offset: usize,
+        len: usize,
+        capacity: usize,
+    },
+    /// The string at the offset is not valid UTF-8.
+    InvalidUtf8 { offset: usize },
+    /// The day number at the offset is not a valid date.
+    InvalidDate { offset: usize, days: i32 },
+    /// A string has more characters than its VARCHAR field allows.
+    StringTooLong { chars: usize, max_chars: usize },
 }
 
 impl std::error::Error for PageError {}
@@ -13,146 +32,368 @@ impl std::error::Error for PageError {}
 impl fmt::Display for PageError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            PageError::BufferSizeExceeded => write!(f, "buffer size exceeded"),
+            PageError::OutOfBounds {
+                offset,
+                len,
+                capacity,
+            } => write!(
+                f,
+                "{} bytes at offset {} do not fit in a page of {} bytes",
+                len, offset, capacity
+            ),
+            PageError::LengthPrefixTooLarge {
+                offset,
+                len,
+                capacity,
+            } => write!(
+                f,
+                "length prefix {} at offset {} is too large for a page of {} bytes",
+                len, offset, capacity
+            ),
+            PageError::InvalidUtf8 { offset } => {
+                write!(f, "string at offset {} is not valid UTF-8", offset)
+            }
+            PageError::InvalidDate { offset, days } => {
+                write!(
+                    f,
+                    "day number {} at offset {} is not a valid date",
+                    days, offset
+                )
+            }
+            PageError::StringTooLong { chars, max_chars } => write!(
+                f,
+                "string of {} characters does not fit in varchar({})",
+                chars, max_chars
+            ),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+type Result<T> = std::result::Result<T, PageError>;
+
+const LEN_SIZE: usize = std::mem::size_of::<i32>();
+// the longest UTF-8 encoding of a character
+const MAX_BYTES_PER_CHAR: usize = 4;
+
+/// The contents of a disk block, held in memory.
+/// Values are stored big-endian at explicit offsets, the same layout as the Java ByteBuffer used by the original
+/// SimpleDB. Byte arrays are stored as a 4-byte length followed by the bytes, and strings as a 4-byte length
+/// followed by their UTF-8 bytes.
+/// A VARCHAR(n) field reserves `max_length(n)` bytes, enough for any string of up to n characters;
+/// `set_varchar` checks the number of characters and zero-pads the unused bytes of the field.
+/// Reading a value does not modify the page, so the getters take `&self`, and byte arrays and strings are returned
+/// as views into the page.
+/// The page also carries the LSN of the latest log record that modified it, which the file manager stores in the
+/// block header when checksums are enabled.
+#[derive(Debug)]
 pub struct Page {
-    buf: ByteBuffer,
+    // the contents are buf[start..start + len], where start puts the contents at an address that is a multiple of
+    // align, as direct I/O requires
+    buf: Box<[u8]>,
+    start: usize,
+    len: usize,
+    align: usize,
+    lsn: Lsn,
 }
 
 impl Page {
     pub fn new(block_size: usize) -> Self {
+        Self::new_aligned(block_size, 1)
+    }
+
+    /// Create a page whose contents start at an address that is a multiple of align, which must be a power of two.
+    pub fn new_aligned(block_size: usize, align: usize) -> Self {
+        let buf = vec![0; block_size + align - 1].into_boxed_slice();
+        let addr = buf.as_ptr() as usize;
         Self {
-            buf: ByteBuffer::from_vec(vec![0; block_size]),
+            start: addr.next_multiple_of(align) - addr,
+            buf,
+            len: block_size,
+            align,
+            lsn: -1,
         }
     }
 
     pub fn from_bytes(bytes: Vec<u8>) -> Page {
         Self {
-            buf: ByteBuffer::from_vec(bytes),
+            start: 0,
+            len: bytes.len(),
+            buf: bytes.into_boxed_slice(),
+            align: 1,
+            lsn: -1,
         }
     }
 
-    pub fn get_short(&mut self, offset: usize) -> Result<i16> {
-        self.buf.set_rpos(offset);
-        Ok(self.buf.read_i16()?)
+    /// Return true if the contents start at an address that is a multiple of align.
+    pub fn is_aligned(&self, align: usize) -> bool {
+        (self.contents().as_ptr() as usize).is_multiple_of(align)
     }
 
-    pub fn set_short(&mut self, offset: usize, n: i16) -> Result<()> {
-        if offset + std::mem::size_of::<i16>() > self.buf.len() {
-            bail!(PageError::BufferSizeExceeded)
-        }
+    /// Return the LSN of the page, or -1 if it is not known.
+    pub fn lsn(&self) -> Lsn {
+        self.lsn
+    }
 
-        self.buf.set_wpos(offset);
-        self.buf.write_i16(n);
-        Ok(())
+    pub fn set_lsn(&mut self, lsn: Lsn) {
+        self.lsn = lsn;
     }
 
-    pub fn get_int(&mut self, offset: usize) -> Result<i32> {
-        self.buf.set_rpos(offset);
-        Ok(self.buf.read_i32()?)
+    pub fn get_short(&self, offset: usize) -> Result<i16> {
+        Ok(i16::from_be_bytes(self.read_array(offset)?))
     }
 
-    pub fn set_int(&mut self, offset: usize, n: i32) -> Result<()> {
-        if offset + std::mem::size_of::<i32>() > self.buf.len() {
-            bail!(PageError::BufferSizeExceeded)
-        }
+    pub fn set_short(&mut self, offset: usize, n: i16) -> Result<()> {
+        self.write(offset, &n.to_be_bytes())
+    }
 
-        self.buf.set_wpos(offset);
-        self.buf.write_i32(n);
-        Ok(())
+    pub fn get_int(&self, offset: usize) -> Result<i32> {
+        Ok(i32::from_be_bytes(self.read_array(offset)?))
     }
 
-    pub fn get_long(&mut self, offset: usize) -> Result<i64> {
-        self.buf.set_rpos(offset);
-        Ok(self.buf.read_i64()?)
+    pub fn set_int(&mut self, offset: usize, n: i32) -> Result<()> {
+        self.write(offset, &n.to_be_bytes())
     }
 
-    pub fn set_long(&mut self, offset: usize, n: i64) -> Result<()> {
-        if offset + std::mem::size_of::<i64>() > self.buf.len() {
-            bail!(PageError::BufferSizeExceeded)
-        }
+    pub fn get_long(&self, offset: usize) -> Result<i64> {
+        Ok(i64::from_be_bytes(self.read_array(offset)?))
+    }
 
-        self.buf.set_wpos(offset);
-        self.buf.write_i64(n);
-        Ok(())
+    pub fn set_long(&mut self, offset: usize, n: i64) -> Result<()> {
+        self.write(offset, &n.to_be_bytes())
     }
 
-    pub fn get_bytes(&mut self, offset: usize) -> Result<Vec<u8>> {
-        self.buf.set_rpos(offset);
-        let len = self.buf.read_i32()? as usize;
-        Ok(self.buf.read_bytes(len)?)
+    /// Return a view of the byte array stored at the specified offset.
+    pub fn get_bytes(&self, offset: usize) -> Result<&[u8]> {
+        let len = u32::from_be_bytes(self.read_array(offset)?) as usize;
+        self.read(offset + LEN_SIZE, len)
+            .map_err(|_| PageError::LengthPrefixTooLarge {
+                offset,
+                len,
+                capacity: self.len,
+            })
     }
 
     pub fn set_bytes(&mut self, offset: usize, bytes: &[u8]) -> Result<()> {
-        if offset + std::mem::size_of::<i32>() + bytes.len() > self.buf.len() {
-            bail!(PageError::BufferSizeExceeded)
-        }
-
-        self.buf.set_wpos(offset);
-        self.buf.write_i32(bytes.len() as i32);
-        self.buf.write_bytes(bytes);
-        Ok(())
+        // check the whole value first, so that nothing is written if it does not fit
+        self.check_bounds(offset, LEN_SIZE.saturating_add(bytes.len()))?;
+        let len = bytes.len() as u32;
+        self.write(offset, &len.to_be_bytes())?;
+        self.write(offset + LEN_SIZE, bytes)
     }
 
-    pub fn get_string(&mut self, offset: usize) -> Result<String> {
-        self.buf.set_rpos(offset);
-        Ok(self.buf.read_string()?)
+    /// Return a view of the string stored at the specified offset.
+    /// It is an error if the stored length runs past the end of the page, or if the bytes are not valid UTF-8.
+    pub fn get_string(&self, offset: usize) -> Result<&str> {
+        std::str::from_utf8(self.get_bytes(offset)?).map_err(|_| PageError::InvalidUtf8 { offset })
     }
 
     pub fn set_string(&mut self, offset: usize, s: &str) -> Result<()> {
-        if offset + s.len() > self.buf.len() {
-            bail!(PageError::BufferSizeExceeded)
-        }
+        self.set_bytes(offset, s.as_bytes())
+    }
 
-        self.buf.set_wpos(offset);
-        self.buf.write_string(s);
+    /// Store a string in a VARCHAR(max_chars) field at the specified offset, zero-padding the rest of the field.
+    /// Nothing is written if the string has more than max_chars characters or the field runs past the end of the page.
+    pub fn set_varchar(&mut self, offset: usize, s: &str, max_chars: usize) -> Result<()> {
+        Self::check_varchar(s, max_chars)?;
+        let field_len = Self::max_length(max_chars);
+        self.check_bounds(offset, field_len)?;
+        self.set_string(offset, s)?;
+        self.contents_mut()[offset + LEN_SIZE + s.len()..offset + field_len].fill(0);
         Ok(())
     }
 
-    pub fn get_bool(&mut self, offset: usize) -> Result<bool> {
-        self.buf.set_rpos(offset);
-        Ok(self.buf.read_u8().map(|n| n != 0)?)
+    /// Check that a string fits in a VARCHAR(max_chars) field.
+    pub fn check_varchar(s: &str, max_chars: usize) -> Result<()> {
+        let chars = s.chars().count();
+        if chars > max_chars {
+            return Err(PageError::StringTooLong { chars, max_chars });
+        }
+        Ok(())
     }
 
-    pub fn set_bool(&mut self, offset: usize, b: bool) -> Result<()> {
-        if offset + std::mem::size_of::<u8>() > self.buf.len() {
-            bail!(PageError::BufferSizeExceeded)
-        }
+    pub fn get_bool(&self, offset: usize) -> Result<bool> {
+        Ok(self.read_array::<1>(offset)?[0] != 0)
+    }
 
-        self.buf.set_wpos(offset);
-        self.buf.write_u8(b as u8);
-        Ok(())
+    pub fn set_bool(&mut self, offset: usize, b: bool) -> Result<()> {
+        self.write(offset, &[b as u8])
     }
 
-    pub fn get_date(&mut self, offset: usize) -> Result<NaiveDate> {
-        self.buf.set_rpos(offset);
-        Ok(self
-            .buf
-            .read_i32()
-            .map(|days| NaiveDate::from_num_days_from_ce_opt(days).unwrap())?)
+    pub fn get_date(&self, offset: usize) -> Result<NaiveDate> {
+        let days = self.get_int(offset)?;
+        NaiveDate::from_num_days_from_ce_opt(days).ok_or(PageError::InvalidDate { offset, days })
     }
 
     pub fn set_date(&mut self, offset: usize, date: NaiveDate) -> Result<()> {
-        if offset + std::mem::size_of::<i32>() > self.buf.len() {
-            bail!(PageError::BufferSizeExceeded)
+        self.set_int(offset, date.num_days_from_ce())
+    }
+
+    /// Return the number of bytes needed to store a string of up to strlen characters:
+    /// the length prefix, plus the longest UTF-8 encoding of each character.
+    pub fn max_length(strlen: usize) -> usize {
+        LEN_SIZE.saturating_add(strlen.saturating_mul(MAX_BYTES_PER_CHAR))
+    }
+
+    // package private methods, needed by FileManager
+
+    pub(crate) fn contents(&self) -> &[u8] {
+        &self.buf[self.start..self.start + self.len]
+    }
+
+    pub(crate) fn contents_mut(&mut self) -> &mut [u8] {
+        &mut self.buf[self.start..self.start + self.len]
+    }
+
+    fn check_bounds(&self, offset: usize, len: usize) -> Result<()> {
+        match offset.checked_add(len) {
+            Some(end) if end <= self.len => Ok(()),
+            _ => Err(PageError::OutOfBounds {
+                offset,
+                len,
+                capacity: self.len,
+            }),
         }
+    }
+
+    fn read(&self, offset: usize, len: usize) -> Result<&[u8]> {
+        self.check_bounds(offset, len)?;
+        Ok(&self.contents()[offset..offset + len])
+    }
+
+    fn read_array<const N: usize>(&self, offset: usize) -> Result<[u8; N]> {
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(self.read(offset, N)?);
+        Ok(bytes)
+    }
 
-        self.buf.set_wpos(offset);
-        self.buf.write_i32(date.num_days_from_ce());
+    fn write(&mut self, offset: usize, bytes: &[u8]) -> Result<()> {
+        self.check_bounds(offset, bytes.len())?;
+        self.contents_mut()[offset..offset + bytes.len()].copy_from_slice(bytes);
         Ok(())
     }
+}
 
-    pub fn max_length(strlen: usize) -> usize {
-        std::mem::size_of::<i32>() + (strlen * std::mem::size_of::<char>())
+// a derived clone would copy the buffer to a new address, losing the alignment of the contents
+impl Clone for Page {
+    fn clone(&self) -> Self {
+        let mut page = Self::new_aligned(self.len, self.align);
+        page.contents_mut().copy_from_slice(self.contents());
+        page.lsn = self.lsn;
+        page
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
 
-    // a package private method, needed by FileManager
-    pub(crate) fn contents(&mut self) -> &mut ByteBuffer {
-        self.buf.set_rpos(0);
-        &mut self.buf
+    use super::{Page, PageError};
+
+    #[test]
+    fn test_page() {
+        let mut page = Page::new(16);
+        page.set_int(0, 0x01020304).unwrap();
+        page.set_short(4, -2).unwrap();
+        assert_eq!(&page.contents()[..6], [1, 2, 3, 4, 0xff, 0xfe]);
+        assert_eq!(page.get_int(0).unwrap(), 0x01020304);
+        assert_eq!(page.get_short(4).unwrap(), -2);
+
+        page.set_string(6, "abcdef").unwrap();
+        assert_eq!(page.get_string(6).unwrap(), "abcdef");
+        assert_eq!(page.get_bytes(6).unwrap(), b"abcdef");
+
+        // values that do not fit leave the page unchanged
+        let out_of_bounds = |offset, len| PageError::OutOfBounds {
+            offset,
+            len,
+            capacity: 16,
+        };
+        assert_eq!(page.set_long(12, 1), Err(out_of_bounds(12, 8)));
+        assert_eq!(page.set_string(8, "abcdef"), Err(out_of_bounds(8, 10)));
+        assert_eq!(page.get_string(6).unwrap(), "abcdef");
+        assert_eq!(page.get_long(12), Err(out_of_bounds(12, 8)));
+        assert_eq!(page.get_int(usize::MAX), Err(out_of_bounds(usize::MAX, 4)));
+
+        // a corrupt length is an error rather than a panic
+        page.set_int(0, -1).unwrap();
+        assert_eq!(
+            page.get_bytes(0),
+            Err(PageError::LengthPrefixTooLarge {
+                offset: 0,
+                len: u32::MAX as usize,
+                capacity: 16
+            })
+        );
+    }
+
+    #[test]
+    fn test_page_dates() {
+        let mut page = Page::new(8);
+        let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        page.set_date(4, date).unwrap();
+        assert_eq!(page.get_date(4), Ok(date));
+
+        page.set_int(4, i32::MAX).unwrap();
+        assert_eq!(
+            page.get_date(4),
+            Err(PageError::InvalidDate {
+                offset: 4,
+                days: i32::MAX
+            })
+        );
+    }
+
+    #[test]
+    fn test_page_strings() {
+        let mut page = Page::new(Page::max_length(3) + 8);
+        let end = Page::max_length(3);
+
+        // multi-byte characters count once towards the varchar length
+        page.set_varchar(0, "äöü", 3).unwrap();
+        assert_eq!(page.get_string(0).unwrap(), "äöü");
+        assert_eq!(page.get_int(0).unwrap(), 6);
+
+        // a shorter value zero-pads the rest of the field, leaving the next field alone
+        page.set_int(end, 7).unwrap();
+        page.set_varchar(0, "a", 3).unwrap();
+        assert_eq!(page.get_string(0).unwrap(), "a");
+        assert!(page.contents()[5..end].iter().all(|&b| b == 0));
+        assert_eq!(page.get_int(end).unwrap(), 7);
+
+        let err = page.set_varchar(0, "abcd", 3).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "string of 4 characters does not fit in varchar(3)"
+        );
+        assert!(page.set_varchar(end, "ab", 3).is_err());
+        assert_eq!(page.get_string(0).unwrap(), "a");
+
+        // invalid UTF-8 and lengths past the end of the page are reported, not panicked on
+        page.set_bytes(0, &[0xff, 0xfe]).unwrap();
+        assert_eq!(
+            page.get_string(0),
+            Err(PageError::InvalidUtf8 { offset: 0 })
+        );
+        page.set_int(0, 100).unwrap();
+        let err = page.get_string(0).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "length prefix 100 at offset 0 is too large for a page of 24 bytes"
+        );
+    }
+
+    #[test]
+    fn test_aligned_page() {
+        for align in [1, 512, 4096] {
+            let mut page = Page::new_aligned(4096, align);
+            assert!(page.is_aligned(align));
+            assert_eq!(page.contents().len(), 4096);
+            page.set_int(4092, 42).unwrap();
+            assert!(page.set_int(4093, 42).is_err());
+
+            let copy = page.clone();
+            assert!(copy.is_aligned(align));
+            assert_eq!(copy.get_int(4092).unwrap(), 42);
+        }
     }
 }
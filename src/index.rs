@@ -0,0 +1,7 @@
+pub mod btree_dir;
+pub mod btree_index;
+pub mod btree_leaf;
+pub mod btree_page;
+pub mod hash_index;
+#[allow(clippy::module_inception)]
+pub mod index;
@@ -0,0 +1,139 @@
+use anyhow::{Ok, Result};
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    file::block_id::BlockId, query::constant::Constant, record::layout::Layout,
+    tx::transaction::Transaction,
+};
+
+use super::btree_page::BTreePage;
+
+/// A directory entry has two components: the number of the child block, and the dataval of the first record in that block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    data_val: Constant,
+    block_number: usize,
+}
+
+impl DirEntry {
+    pub fn new(data_val: Constant, block_number: usize) -> Self {
+        Self {
+            data_val,
+            block_number,
+        }
+    }
+
+    pub fn data_val(&self) -> &Constant {
+        &self.data_val
+    }
+
+    pub fn block_number(&self) -> usize {
+        self.block_number
+    }
+}
+
+/// A B-tree directory block.
+#[derive(Debug)]
+pub struct BTreeDir {
+    tx: Arc<Mutex<Transaction>>,
+    layout: Layout,
+    contents: BTreePage,
+    filename: String,
+}
+
+impl BTreeDir {
+    /// Create an object to hold the contents of the specified directory block.
+    pub fn new(tx: Arc<Mutex<Transaction>>, block: BlockId, layout: Layout) -> Result<Self> {
+        let filename = block.filename().to_string();
+        let contents = BTreePage::new(Arc::clone(&tx), block, layout.clone())?;
+        Ok(Self {
+            tx,
+            layout,
+            contents,
+            filename,
+        })
+    }
+
+    /// Close the directory page.
+    pub fn close(&self) -> Result<()> {
+        self.contents.close()
+    }
+
+    /// Return the block number of the B-tree leaf block that contains the specified search key.
+    /// Without a search key, return the leftmost leaf block.
+    pub fn search(&mut self, search_key: Option<&Constant>) -> Result<usize> {
+        let mut child_block = self.find_child_block(search_key)?;
+        while self.contents.flag()? > 0 {
+            self.contents.close()?;
+            self.contents = BTreePage::new(Arc::clone(&self.tx), child_block, self.layout.clone())?;
+            child_block = self.find_child_block(search_key)?;
+        }
+        Ok(child_block.block_number())
+    }
+
+    /// Create a new root block for the B-tree.
+    /// The new root will have two children: the old root, and the specified block.
+    /// Since the root must always be in block 0 of the file, the contents of the old root will get transferred to a new block.
+    pub fn make_new_root(&self, entry: &DirEntry) -> Result<()> {
+        let first_val = self.contents.data_val(0)?;
+        let level = self.contents.flag()?;
+        let new_block = self.contents.split(0, level)?; // ie, transfer all the records
+        let old_root = DirEntry::new(first_val, new_block.block_number());
+        self.insert_entry(&old_root)?;
+        self.insert_entry(entry)?;
+        self.contents.set_flag(level + 1)
+    }
+
+    /// Insert a new directory entry into the B-tree block.
+    /// If the block is at level 0, then the entry is inserted there. Otherwise, the entry is inserted into the
+    /// appropriate child node, and the return value is examined. A non-empty return value indicates that the child
+    /// node split, and so the returned entry is inserted into this block.
+    /// If this block splits, then the method similarly returns the entry information of the new block to its caller.
+    pub fn insert(&self, entry: &DirEntry) -> Result<Option<DirEntry>> {
+        if self.contents.flag()? == 0 {
+            return self.insert_entry(entry);
+        }
+        let child_block = self.find_child_block(Some(entry.data_val()))?;
+        let child = BTreeDir::new(Arc::clone(&self.tx), child_block, self.layout.clone())?;
+        let my_entry = child.insert(entry)?;
+        child.close()?;
+        match my_entry {
+            Some(my_entry) => self.insert_entry(&my_entry),
+            None => Ok(None),
+        }
+    }
+
+    fn insert_entry(&self, entry: &DirEntry) -> Result<Option<DirEntry>> {
+        let new_slot = 1 + self.contents.find_slot_before(Some(entry.data_val()))?;
+        self.contents
+            .insert_dir(new_slot, entry.data_val(), entry.block_number())?;
+        if !self.contents.is_full()? {
+            return Ok(None);
+        }
+        // else page is full, so split it
+        let level = self.contents.flag()?;
+        let split_pos = self.contents.num_recs()? / 2;
+        let split_val = self.contents.data_val(split_pos)?;
+        let new_block = self.contents.split(split_pos, level)?;
+        Ok(Some(DirEntry::new(split_val, new_block.block_number())))
+    }
+
+    fn find_child_block(&self, search_key: Option<&Constant>) -> Result<BlockId> {
+        let slot = match search_key {
+            // the first entry holds the smallest possible key, so it leads to the leftmost child
+            None => 0,
+            Some(search_key) => {
+                let slot = self.contents.find_slot_before(Some(search_key))?;
+                if slot + 1 < self.contents.num_recs()?
+                    && self.contents.data_val(slot + 1)? == *search_key
+                {
+                    slot + 1
+                } else {
+                    slot
+                }
+            }
+        };
+        let block_number = self.contents.child_num(slot)?;
+        Ok(BlockId::new(&self.filename, block_number))
+    }
+}
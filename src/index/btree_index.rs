@@ -0,0 +1,353 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    file::block_id::BlockId,
+    query::constant::Constant,
+    record::{
+        layout::Layout,
+        rid::RecordId,
+        schema::{FieldType, Schema},
+    },
+    tx::transaction::Transaction,
+};
+
+use super::{
+    btree_dir::BTreeDir,
+    btree_leaf::BTreeLeaf,
+    btree_page::BTreePage,
+    index::{index_table_name, Index},
+};
+
+#[derive(Debug)]
+enum BTreeIndexError {
+    NotPositioned,
+}
+
+impl std::error::Error for BTreeIndexError {}
+impl fmt::Display for BTreeIndexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BTreeIndexError::NotPositioned => write!(f, "b-tree index is not positioned on a leaf"),
+        }
+    }
+}
+
+/// A B-tree implementation of the Index interface.
+/// The leaves are stored in the file `<index>#leaf.tbl` and the directory in `<index>#dir.tbl`, whose block 0 is the root.
+/// Besides equality searches, the index supports range searches over the leaves.
+#[derive(Debug)]
+pub struct BTreeIndex {
+    tx: Arc<Mutex<Transaction>>,
+    dir_layout: Layout,
+    leaf_layout: Layout,
+    leaf_table: String,
+    leaf: Option<BTreeLeaf>,
+    root_block: BlockId,
+}
+
+impl BTreeIndex {
+    /// Open a B-tree index for the specified index.
+    /// The method determines the appropriate files for the leaf and directory records, creating them if they did not exist.
+    pub fn new(tx: Arc<Mutex<Transaction>>, index_name: &str, leaf_layout: Layout) -> Result<Self> {
+        // deal with the leaves
+        let leaf_table = index_table_name(index_name, "leaf");
+        let leaf_file = format!("{}.tbl", leaf_table);
+        if tx.lock().unwrap().size(&leaf_file)? == 0 {
+            let block = tx.lock().unwrap().append(&leaf_file)?;
+            let node = BTreePage::new(Arc::clone(&tx), block, leaf_layout.clone())?;
+            node.format(-1)?;
+            node.close()?;
+        }
+
+        // deal with the directory
+        let leaf_schema = leaf_layout.schema();
+        let mut dir_schema = Schema::new();
+        dir_schema.add("block", leaf_schema);
+        dir_schema.add("dataval", leaf_schema);
+        let dir_layout = Layout::new(dir_schema);
+        let dir_file = format!("{}.tbl", index_table_name(index_name, "dir"));
+        let root_block = BlockId::new(&dir_file, 0);
+        if tx.lock().unwrap().size(&dir_file)? == 0 {
+            // create new root block
+            tx.lock().unwrap().append(&dir_file)?;
+            let node = BTreePage::new(Arc::clone(&tx), root_block.clone(), dir_layout.clone())?;
+            node.format(0)?;
+            // insert initial directory entry
+            let min_val = match leaf_schema.field_type("dataval") {
+                Some(FieldType::Varchar) => Constant::String(String::new()),
+                _ => Constant::Int(i32::MIN),
+            };
+            node.insert_dir(0, &min_val, 0)?;
+            node.close()?;
+        }
+
+        Ok(Self {
+            tx,
+            dir_layout,
+            leaf_layout,
+            leaf_table,
+            leaf: None,
+            root_block,
+        })
+    }
+
+    /// Estimate the number of block accesses required to find all index records having a particular search key.
+    pub fn search_cost(num_blocks: usize, records_per_block: usize) -> usize {
+        if num_blocks <= 1 || records_per_block <= 1 {
+            return 1;
+        }
+        1 + ((num_blocks as f64).ln() / (records_per_block as f64).ln()) as usize
+    }
+
+    fn current_leaf(&mut self) -> Result<&mut BTreeLeaf> {
+        self.leaf
+            .as_mut()
+            .ok_or_else(|| BTreeIndexError::NotPositioned.into())
+    }
+}
+
+impl Index for BTreeIndex {
+    /// Traverse the directory to find the leaf block corresponding to the specified search key.
+    /// The method then opens a page for that leaf block, and positions the page before the first record (if any)
+    /// having that search key. The leaf page is kept open, for use by the methods next and get_data_rid.
+    fn before_first(&mut self, search_key: &Constant) -> Result<()> {
+        self.before_first_range(Some(search_key), Some(search_key))
+    }
+
+    /// Position the index before the first index record whose dataval is at least `lo`,
+    /// so that `next` visits the index records whose datavals lie between `lo` and `hi`, inclusive.
+    /// A missing bound leaves that end of the range open.
+    fn before_first_range(&mut self, lo: Option<&Constant>, hi: Option<&Constant>) -> Result<()> {
+        self.close()?;
+        let mut root = BTreeDir::new(
+            Arc::clone(&self.tx),
+            self.root_block.clone(),
+            self.dir_layout.clone(),
+        )?;
+        let block_number = root.search(lo)?;
+        root.close()?;
+        let leaf_block = BlockId::new(&format!("{}.tbl", self.leaf_table), block_number);
+        self.leaf = Some(BTreeLeaf::with_range(
+            Arc::clone(&self.tx),
+            leaf_block,
+            self.leaf_layout.clone(),
+            lo.cloned(),
+            hi.cloned(),
+        )?);
+        Ok(())
+    }
+
+    /// Move to the next leaf record having the previously-specified search key.
+    /// Return false if there are no more such leaf records.
+    fn next(&mut self) -> Result<bool> {
+        self.current_leaf()?.next()
+    }
+
+    /// Return the data record id value from the current leaf record.
+    fn get_data_rid(&self) -> Result<RecordId> {
+        self.leaf
+            .as_ref()
+            .ok_or(BTreeIndexError::NotPositioned)?
+            .data_rid()
+    }
+
+    /// Insert the specified record into the index.
+    /// The method first traverses the directory to find the appropriate leaf page; then it inserts the record into
+    /// the leaf. If the insertion causes the leaf to split, then the method calls insert on the root, passing it the
+    /// directory entry of the new leaf page. If the root node splits, then make_new_root is called.
+    fn insert(&mut self, data_val: &Constant, data_rid: &RecordId) -> Result<()> {
+        self.before_first(data_val)?;
+        let entry = self.current_leaf()?.insert(data_rid)?;
+        self.close()?;
+        let Some(entry) = entry else {
+            return Ok(());
+        };
+
+        let root = BTreeDir::new(
+            Arc::clone(&self.tx),
+            self.root_block.clone(),
+            self.dir_layout.clone(),
+        )?;
+        if let Some(entry) = root.insert(&entry)? {
+            root.make_new_root(&entry)?;
+        }
+        root.close()
+    }
+
+    /// Delete the specified index record.
+    /// The method first traverses the directory to find the leaf page containing that record; then it deletes the
+    /// record from the page.
+    fn delete(&mut self, data_val: &Constant, data_rid: &RecordId) -> Result<()> {
+        self.before_first(data_val)?;
+        self.current_leaf()?.delete(data_rid)?;
+        self.close()
+    }
+
+    /// Close the index by closing its open leaf page, if necessary.
+    fn close(&mut self) -> Result<()> {
+        if let Some(leaf) = self.leaf.take() {
+            leaf.close()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+
+    use crate::{
+        index::index::Index,
+        query::constant::Constant,
+        record::{layout::Layout, rid::RecordId, schema::Schema},
+        server::simpledb::SimpleDB,
+    };
+
+    use super::BTreeIndex;
+
+    fn leaf_layout() -> Layout {
+        let mut schema = Schema::new();
+        schema.add_int_field("block");
+        schema.add_int_field("id");
+        schema.add_int_field("dataval");
+        Layout::new(schema)
+    }
+
+    fn collect(index: &mut BTreeIndex) -> Vec<RecordId> {
+        let mut rids = vec![];
+        while index.next().unwrap() {
+            rids.push(index.get_data_rid().unwrap());
+        }
+        rids
+    }
+
+    #[test]
+    fn test_btree_index() {
+        let temp_dir = tempdir().unwrap();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+        let tx = db.new_tx().unwrap();
+
+        // 800-byte pages hold 49 leaf records and 65 directory entries,
+        // so 4000 keys split the leaves and the directory
+        let mut index = BTreeIndex::new(Arc::clone(&tx), "idx", leaf_layout()).unwrap();
+        assert!(index.next().is_err());
+        let keys: Vec<i32> = (0..4000).map(|i| (i * 7919) % 1000).collect();
+        for (i, key) in keys.iter().enumerate() {
+            index
+                .insert(&Constant::Int(*key), &RecordId::new(i, *key))
+                .unwrap();
+        }
+        assert!(tx.lock().unwrap().size("idx#dir.tbl").unwrap() > 1);
+
+        for key in [0, 1, 500, 999] {
+            index.before_first(&Constant::Int(key)).unwrap();
+            let rids = collect(&mut index);
+            assert_eq!(rids.len(), 4);
+            assert!(rids.iter().all(|rid| rid.slot() == key));
+        }
+        index.before_first(&Constant::Int(1000)).unwrap();
+        assert!(collect(&mut index).is_empty());
+
+        // ranges cross leaf boundaries
+        index
+            .before_first_range(Some(&Constant::Int(100)), Some(&Constant::Int(199)))
+            .unwrap();
+        let mut slots: Vec<i32> = collect(&mut index).iter().map(|rid| rid.slot()).collect();
+        slots.sort();
+        assert_eq!(slots, (100..200).flat_map(|k| [k; 4]).collect::<Vec<_>>());
+        index
+            .before_first_range(None, Some(&Constant::Int(9)))
+            .unwrap();
+        assert_eq!(collect(&mut index).len(), 40);
+        index
+            .before_first_range(Some(&Constant::Int(990)), None)
+            .unwrap();
+        assert_eq!(collect(&mut index).len(), 40);
+        index.before_first_range(None, None).unwrap();
+        assert_eq!(collect(&mut index).len(), 4000);
+
+        let rid = RecordId::new(keys.iter().position(|&k| k == 500).unwrap(), 500);
+        index.delete(&Constant::Int(500), &rid).unwrap();
+        index.before_first(&Constant::Int(500)).unwrap();
+        assert_eq!(collect(&mut index).len(), 3);
+        index.close().unwrap();
+
+        tx.lock().unwrap().commit().unwrap();
+    }
+
+    #[test]
+    fn test_btree_files_do_not_clash_with_tables() {
+        let temp_dir = tempdir().unwrap();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+        let planner = db.planner();
+        let tx = db.new_tx().unwrap();
+
+        for sql in [
+            "create table idxleaf(X int)",
+            "insert into idxleaf(X) values (1)",
+            "create table idxdir(X int)",
+            "insert into idxdir(X) values (2)",
+            "create table t(A int)",
+            "create index idx on t(A) using btree",
+            "insert into t(A) values (7)",
+        ] {
+            planner.execute_update(sql, Arc::clone(&tx)).unwrap();
+        }
+        tx.lock().unwrap().commit().unwrap();
+
+        let tx = db.new_tx().unwrap();
+        for (table_name, val) in [("idxleaf", 1), ("idxdir", 2)] {
+            let plan = planner
+                .create_query_plan(&format!("select X from {}", table_name), Arc::clone(&tx))
+                .unwrap();
+            let mut scan = plan.open().unwrap();
+            let mut result = vec![];
+            while scan.next().unwrap() {
+                result.push(scan.get_int("x").unwrap());
+            }
+            scan.close().unwrap();
+            assert_eq!(result, [val], "{}", table_name);
+        }
+        tx.lock().unwrap().commit().unwrap();
+    }
+
+    #[test]
+    fn test_btree_index_overflow() {
+        let temp_dir = tempdir().unwrap();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+        let tx = db.new_tx().unwrap();
+
+        // many records with the same key go to overflow blocks, and smaller and larger keys still split around them
+        let mut index = BTreeIndex::new(Arc::clone(&tx), "dup", leaf_layout()).unwrap();
+        for i in 0..100 {
+            index
+                .insert(&Constant::Int(5), &RecordId::new(i, 5))
+                .unwrap();
+        }
+        for i in 0..30 {
+            for key in [1, 9] {
+                index
+                    .insert(&Constant::Int(key), &RecordId::new(i, key))
+                    .unwrap();
+            }
+        }
+
+        for (key, count) in [(1, 30), (5, 100), (9, 30), (7, 0)] {
+            index.before_first(&Constant::Int(key)).unwrap();
+            let rids = collect(&mut index);
+            assert_eq!(rids.len(), count);
+            assert!(rids.iter().all(|rid| rid.slot() == key));
+        }
+        index
+            .before_first_range(Some(&Constant::Int(2)), Some(&Constant::Int(9)))
+            .unwrap();
+        assert_eq!(collect(&mut index).len(), 130);
+        index.close().unwrap();
+
+        tx.lock().unwrap().commit().unwrap();
+    }
+}
@@ -0,0 +1,235 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    file::block_id::BlockId,
+    query::constant::Constant,
+    record::{layout::Layout, rid::RecordId},
+    tx::transaction::Transaction,
+};
+
+use super::{
+    btree_dir::DirEntry,
+    btree_page::{BTreePage, NO_BLOCK},
+};
+
+#[derive(Debug)]
+enum BTreeLeafError {
+    NoSearchKey,
+}
+
+impl std::error::Error for BTreeLeafError {}
+impl fmt::Display for BTreeLeafError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BTreeLeafError::NoSearchKey => write!(f, "insert needs a leaf opened on a search key"),
+        }
+    }
+}
+
+/// An object that holds the contents of a B-tree leaf block.
+/// The leaf is positioned before the first record whose dataval is at least `lo`, and `next` visits the records
+/// whose datavals lie between `lo` and `hi`, following the overflow chain and then the next leaves as needed.
+/// A missing bound leaves that end of the range open.
+#[derive(Debug)]
+pub struct BTreeLeaf {
+    tx: Arc<Mutex<Transaction>>,
+    layout: Layout,
+    lo: Option<Constant>,
+    hi: Option<Constant>,
+    contents: BTreePage,
+    current_slot: i32,
+    filename: String,
+    // the leaf that follows the current page, or its overflow chain
+    next_leaf: i32,
+}
+
+impl BTreeLeaf {
+    /// Open a leaf positioned before the first record having the specified search key.
+    pub fn new(
+        tx: Arc<Mutex<Transaction>>,
+        block: BlockId,
+        layout: Layout,
+        search_key: &Constant,
+    ) -> Result<Self> {
+        Self::with_range(
+            tx,
+            block,
+            layout,
+            Some(search_key.clone()),
+            Some(search_key.clone()),
+        )
+    }
+
+    /// Open a leaf positioned before the first record of the range.
+    pub fn with_range(
+        tx: Arc<Mutex<Transaction>>,
+        block: BlockId,
+        layout: Layout,
+        lo: Option<Constant>,
+        hi: Option<Constant>,
+    ) -> Result<Self> {
+        let filename = block.filename().to_string();
+        let contents = BTreePage::new(Arc::clone(&tx), block, layout.clone())?;
+        let current_slot = contents.find_slot_before(lo.as_ref())?;
+        let next_leaf = contents.next_block()?;
+        Ok(Self {
+            tx,
+            layout,
+            lo,
+            hi,
+            contents,
+            current_slot,
+            filename,
+            next_leaf,
+        })
+    }
+
+    /// Close the leaf page.
+    pub fn close(&self) -> Result<()> {
+        self.contents.close()
+    }
+
+    /// Move to the next leaf record in the range.
+    /// Return false if there are no more such records.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<bool> {
+        loop {
+            self.current_slot += 1;
+            if self.current_slot < self.contents.num_recs()? {
+                let data_val = self.contents.data_val(self.current_slot)?;
+                if self.above_range(&data_val) {
+                    // the rest of this page is past the range
+                    self.current_slot = self.contents.num_recs()?;
+                } else if !self.below_range(&data_val) {
+                    return Ok(true);
+                }
+            } else if !self.try_overflow()? && !self.try_next_leaf()? {
+                return Ok(false);
+            }
+        }
+    }
+
+    /// Return the data record id of the current leaf record.
+    pub fn data_rid(&self) -> Result<RecordId> {
+        self.contents.data_rid(self.current_slot)
+    }
+
+    /// Delete the leaf record having the specified data record id.
+    pub fn delete(&mut self, data_rid: &RecordId) -> Result<()> {
+        while self.next()? {
+            if self.data_rid()? == *data_rid {
+                return self.contents.delete(self.current_slot);
+            }
+        }
+        Ok(())
+    }
+
+    /// Insert a new leaf record having the specified data record id and the leaf's search key.
+    /// If the record does not fit in the page, then the page splits and the method returns the directory entry for
+    /// the new page; otherwise, the method returns None. If all of the records in the page have the same dataval,
+    /// then the block does not split; instead, all but one of the records are placed into an overflow block.
+    pub fn insert(&mut self, data_rid: &RecordId) -> Result<Option<DirEntry>> {
+        let search_key = self.lo.clone().ok_or(BTreeLeafError::NoSearchKey)?;
+        if self.contents.flag()? >= 0 && self.contents.data_val(0)? > search_key {
+            let first_val = self.contents.data_val(0)?;
+            let new_block = self.contents.split(0, self.contents.flag()?)?;
+            self.link(&new_block)?;
+            self.current_slot = 0;
+            self.contents.set_flag(-1)?;
+            self.contents
+                .insert_leaf(self.current_slot, &search_key, data_rid)?;
+            return Ok(Some(DirEntry::new(first_val, new_block.block_number())));
+        }
+
+        self.current_slot += 1;
+        self.contents
+            .insert_leaf(self.current_slot, &search_key, data_rid)?;
+        if !self.contents.is_full()? {
+            return Ok(None);
+        }
+
+        // else page is full, so split it
+        let first_key = self.contents.data_val(0)?;
+        let last_key = self.contents.data_val(self.contents.num_recs()? - 1)?;
+        if last_key == first_key {
+            // create an overflow block to hold all but the first record
+            let new_block = self.contents.split(1, self.contents.flag()?)?;
+            self.contents.set_flag(new_block.block_number() as i32)?;
+            return Ok(None);
+        }
+
+        let mut split_pos = self.contents.num_recs()? / 2;
+        let mut split_key = self.contents.data_val(split_pos)?;
+        if split_key == first_key {
+            // move right, looking for the next key
+            while self.contents.data_val(split_pos)? == split_key {
+                split_pos += 1;
+            }
+            split_key = self.contents.data_val(split_pos)?;
+        } else {
+            // move left, looking for first entry having that key
+            while self.contents.data_val(split_pos - 1)? == split_key {
+                split_pos -= 1;
+            }
+        }
+        let new_block = self.contents.split(split_pos, -1)?;
+        self.link(&new_block)?;
+        Ok(Some(DirEntry::new(split_key, new_block.block_number())))
+    }
+
+    /// Put a block that was split off this leaf right after it in the chain of leaves.
+    fn link(&self, new_block: &BlockId) -> Result<()> {
+        let new_page =
+            BTreePage::new(Arc::clone(&self.tx), new_block.clone(), self.layout.clone())?;
+        new_page.set_next_block(self.contents.next_block()?)?;
+        new_page.close()?;
+        self.contents
+            .set_next_block(new_block.block_number() as i32)
+    }
+
+    /// Move to the overflow block of the current page, if its records lie in the range.
+    /// The records of an overflow block all have the same dataval as the first record of the page.
+    fn try_overflow(&mut self) -> Result<bool> {
+        let flag = self.contents.flag()?;
+        if flag < 0 || self.contents.num_recs()? == 0 {
+            return Ok(false);
+        }
+        let first_key = self.contents.data_val(0)?;
+        if self.below_range(&first_key) || self.above_range(&first_key) {
+            return Ok(false);
+        }
+        self.move_to(flag as usize)?;
+        Ok(true)
+    }
+
+    /// Move to the leaf following the current one, unless its records are past the range.
+    fn try_next_leaf(&mut self) -> Result<bool> {
+        if self.next_leaf == NO_BLOCK {
+            return Ok(false);
+        }
+        self.move_to(self.next_leaf as usize)?;
+        self.next_leaf = self.contents.next_block()?;
+        if self.contents.num_recs()? > 0 && self.above_range(&self.contents.data_val(0)?) {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    fn move_to(&mut self, block_number: usize) -> Result<()> {
+        self.contents.close()?;
+        let block = BlockId::new(&self.filename, block_number);
+        self.contents = BTreePage::new(Arc::clone(&self.tx), block, self.layout.clone())?;
+        self.current_slot = -1;
+        Ok(())
+    }
+
+    fn below_range(&self, data_val: &Constant) -> bool {
+        self.lo.as_ref().is_some_and(|lo| data_val < lo)
+    }
+
+    fn above_range(&self, data_val: &Constant) -> bool {
+        self.hi.as_ref().is_some_and(|hi| data_val > hi)
+    }
+}
@@ -0,0 +1,320 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    file::{block_id::BlockId, page::Page},
+    query::constant::Constant,
+    record::{layout::Layout, rid::RecordId, schema::FieldType},
+    tx::transaction::Transaction,
+};
+
+const INT_SIZE: usize = std::mem::size_of::<i32>();
+const FLAG_OFFSET: usize = 0;
+const NUM_RECS_OFFSET: usize = INT_SIZE;
+const NEXT_OFFSET: usize = 2 * INT_SIZE;
+const HEADER_SIZE: usize = 3 * INT_SIZE;
+
+/// The value of the next-leaf pointer of the last leaf.
+pub const NO_BLOCK: i32 = -1;
+
+#[derive(Debug)]
+enum BTreePageError {
+    UnknownField(String),
+}
+
+impl std::error::Error for BTreePageError {}
+impl fmt::Display for BTreePageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BTreePageError::UnknownField(field_name) => write!(f, "unknown field: {}", field_name),
+        }
+    }
+}
+
+/// B-tree directory and leaf pages have many commonalities: in particular, their records are stored in sorted order,
+/// and pages split when full. A page starts with a header holding a flag, the number of records
+/// and the number of the next leaf block, followed by the records.
+/// The flag of a directory page is its level; the flag of a leaf page is the block number of its overflow block, or -1.
+#[derive(Debug)]
+pub struct BTreePage {
+    tx: Arc<Mutex<Transaction>>,
+    current_block: BlockId,
+    layout: Layout,
+}
+
+impl BTreePage {
+    /// Open a page for the specified block, and pin the block for the lifetime of the page.
+    pub fn new(
+        tx: Arc<Mutex<Transaction>>,
+        current_block: BlockId,
+        layout: Layout,
+    ) -> Result<Self> {
+        tx.lock().unwrap().pin(&current_block)?;
+        Ok(Self {
+            tx,
+            current_block,
+            layout,
+        })
+    }
+
+    /// Calculate the position where the first record having the specified search key should be,
+    /// then return the position before it.
+    /// Without a search key, return the position before the first record.
+    pub fn find_slot_before(&self, search_key: Option<&Constant>) -> Result<i32> {
+        let Some(search_key) = search_key else {
+            return Ok(-1);
+        };
+        let mut slot = 0;
+        while slot < self.num_recs()? && self.data_val(slot)? < *search_key {
+            slot += 1;
+        }
+        Ok(slot - 1)
+    }
+
+    /// Return whether a page of the block size has room for enough records of the layout to be split when full.
+    pub fn fits(layout: &Layout, block_size: usize) -> bool {
+        HEADER_SIZE + 3 * layout.slot_size() <= block_size
+    }
+
+    /// Unpin the page's block.
+    pub fn close(&self) -> Result<()> {
+        self.tx.lock().unwrap().unpin(&self.current_block)
+    }
+
+    /// Return true if the block does not have room for another record.
+    pub fn is_full(&self) -> Result<bool> {
+        Ok(self.slot_pos(self.num_recs()? + 1) >= self.tx.lock().unwrap().block_size())
+    }
+
+    /// Split the page at the specified position.
+    /// A new page is created, and the records of the page starting at the split position are transferred to the new page.
+    pub fn split(&self, split_pos: i32, flag: i32) -> Result<BlockId> {
+        let new_block = self.append_new(flag)?;
+        let new_page =
+            BTreePage::new(Arc::clone(&self.tx), new_block.clone(), self.layout.clone())?;
+        self.transfer_recs(split_pos, &new_page)?;
+        new_page.set_flag(flag)?;
+        new_page.close()?;
+        Ok(new_block)
+    }
+
+    /// Return the dataval of the record at the specified slot.
+    pub fn data_val(&self, slot: i32) -> Result<Constant> {
+        self.get_val(slot, "dataval")
+    }
+
+    /// Return the value of the page's flag field.
+    pub fn flag(&self) -> Result<i32> {
+        self.tx
+            .lock()
+            .unwrap()
+            .get_int(&self.current_block, FLAG_OFFSET)
+    }
+
+    pub fn set_flag(&self, val: i32) -> Result<()> {
+        self.tx
+            .lock()
+            .unwrap()
+            .set_int(&self.current_block, FLAG_OFFSET, val, true)
+    }
+
+    /// Return the block number of the next leaf, or `NO_BLOCK` for the last leaf.
+    pub fn next_block(&self) -> Result<i32> {
+        self.tx
+            .lock()
+            .unwrap()
+            .get_int(&self.current_block, NEXT_OFFSET)
+    }
+
+    pub fn set_next_block(&self, block_number: i32) -> Result<()> {
+        self.tx
+            .lock()
+            .unwrap()
+            .set_int(&self.current_block, NEXT_OFFSET, block_number, true)
+    }
+
+    /// Append a new block to the end of the file, and format it with the specified flag.
+    pub fn append_new(&self, flag: i32) -> Result<BlockId> {
+        let block = self
+            .tx
+            .lock()
+            .unwrap()
+            .append(self.current_block.filename())?;
+        let page = BTreePage::new(Arc::clone(&self.tx), block.clone(), self.layout.clone())?;
+        page.format(flag)?;
+        page.close()?;
+        Ok(block)
+    }
+
+    /// Initialize the page with the specified flag, no records and no next leaf.
+    /// These values are not logged, because the old values are meaningless.
+    pub fn format(&self, flag: i32) -> Result<()> {
+        let mut tx = self.tx.lock().unwrap();
+        tx.set_int(&self.current_block, FLAG_OFFSET, flag, false)?;
+        tx.set_int(&self.current_block, NUM_RECS_OFFSET, 0, false)?;
+        tx.set_int(&self.current_block, NEXT_OFFSET, NO_BLOCK, false)?;
+
+        let record_size = self.layout.slot_size();
+        let schema = self.layout.schema();
+        let mut pos = HEADER_SIZE;
+        while pos + record_size <= tx.block_size() {
+            for field_name in schema.fields() {
+                let field_pos = pos + self.field_offset(field_name)?;
+                if schema.field_type(field_name) == Some(FieldType::Integer) {
+                    tx.set_int(&self.current_block, field_pos, 0, false)?;
+                } else {
+                    tx.set_string(&self.current_block, field_pos, "", false)?;
+                }
+            }
+            pos += record_size;
+        }
+        Ok(())
+    }
+
+    // Methods called only by BTreeDir
+
+    /// Return the block number stored in the index record at the specified slot.
+    pub fn child_num(&self, slot: i32) -> Result<usize> {
+        Ok(self.get_int(slot, "block")? as usize)
+    }
+
+    /// Insert a directory entry at the specified slot.
+    pub fn insert_dir(&self, slot: i32, val: &Constant, block_number: usize) -> Result<()> {
+        self.insert(slot)?;
+        self.set_val(slot, "dataval", val)?;
+        self.set_int(slot, "block", block_number as i32)
+    }
+
+    // Methods called only by BTreeLeaf
+
+    /// Return the data record id value stored in the specified leaf index record.
+    pub fn data_rid(&self, slot: i32) -> Result<RecordId> {
+        Ok(RecordId::new(
+            self.get_int(slot, "block")? as usize,
+            self.get_int(slot, "id")?,
+        ))
+    }
+
+    /// Insert a leaf index record at the specified slot.
+    pub fn insert_leaf(&self, slot: i32, val: &Constant, rid: &RecordId) -> Result<()> {
+        self.insert(slot)?;
+        self.set_val(slot, "dataval", val)?;
+        self.set_int(slot, "block", rid.block_number() as i32)?;
+        self.set_int(slot, "id", rid.slot())
+    }
+
+    /// Delete the index record at the specified slot, shifting the following records left.
+    pub fn delete(&self, slot: i32) -> Result<()> {
+        let num_recs = self.num_recs()?;
+        for i in slot + 1..num_recs {
+            self.copy_record(i, i - 1)?;
+        }
+        self.set_num_recs(num_recs - 1)
+    }
+
+    /// Return the number of index records in this page.
+    pub fn num_recs(&self) -> Result<i32> {
+        self.tx
+            .lock()
+            .unwrap()
+            .get_int(&self.current_block, NUM_RECS_OFFSET)
+    }
+
+    pub fn block(&self) -> &BlockId {
+        &self.current_block
+    }
+
+    // Private methods
+
+    fn get_int(&self, slot: i32, field_name: &str) -> Result<i32> {
+        let pos = self.field_pos(slot, field_name)?;
+        self.tx.lock().unwrap().get_int(&self.current_block, pos)
+    }
+
+    fn get_string(&self, slot: i32, field_name: &str) -> Result<String> {
+        let pos = self.field_pos(slot, field_name)?;
+        self.tx.lock().unwrap().get_string(&self.current_block, pos)
+    }
+
+    fn get_val(&self, slot: i32, field_name: &str) -> Result<Constant> {
+        match self.layout.schema().field_type(field_name) {
+            Some(FieldType::Varchar) => Ok(Constant::String(self.get_string(slot, field_name)?)),
+            _ => Ok(Constant::Int(self.get_int(slot, field_name)?)),
+        }
+    }
+
+    fn set_int(&self, slot: i32, field_name: &str, val: i32) -> Result<()> {
+        let pos = self.field_pos(slot, field_name)?;
+        self.tx
+            .lock()
+            .unwrap()
+            .set_int(&self.current_block, pos, val, true)
+    }
+
+    fn set_string(&self, slot: i32, field_name: &str, val: &str) -> Result<()> {
+        let pos = self.field_pos(slot, field_name)?;
+        Page::check_varchar(val, self.layout.schema().length(field_name).unwrap_or(0))?;
+        self.tx
+            .lock()
+            .unwrap()
+            .set_string(&self.current_block, pos, val, true)
+    }
+
+    fn set_val(&self, slot: i32, field_name: &str, val: &Constant) -> Result<()> {
+        match val {
+            Constant::Int(n) => self.set_int(slot, field_name, *n),
+            Constant::String(s) => self.set_string(slot, field_name, s),
+        }
+    }
+
+    fn set_num_recs(&self, n: i32) -> Result<()> {
+        self.tx
+            .lock()
+            .unwrap()
+            .set_int(&self.current_block, NUM_RECS_OFFSET, n, true)
+    }
+
+    /// Make room for a record at the specified slot by shifting the following records right.
+    fn insert(&self, slot: i32) -> Result<()> {
+        let num_recs = self.num_recs()?;
+        for i in (slot + 1..=num_recs).rev() {
+            self.copy_record(i - 1, i)?;
+        }
+        self.set_num_recs(num_recs + 1)
+    }
+
+    fn copy_record(&self, from: i32, to: i32) -> Result<()> {
+        for field_name in self.layout.schema().fields() {
+            self.set_val(to, field_name, &self.get_val(from, field_name)?)?;
+        }
+        Ok(())
+    }
+
+    fn transfer_recs(&self, slot: i32, dest: &BTreePage) -> Result<()> {
+        let mut dest_slot = 0;
+        while slot < self.num_recs()? {
+            dest.insert(dest_slot)?;
+            for field_name in self.layout.schema().fields() {
+                dest.set_val(dest_slot, field_name, &self.get_val(slot, field_name)?)?;
+            }
+            self.delete(slot)?;
+            dest_slot += 1;
+        }
+        Ok(())
+    }
+
+    fn field_pos(&self, slot: i32, field_name: &str) -> Result<usize> {
+        Ok(self.slot_pos(slot) + self.field_offset(field_name)?)
+    }
+
+    fn field_offset(&self, field_name: &str) -> Result<usize> {
+        self.layout
+            .offset(field_name)
+            .ok_or_else(|| BTreePageError::UnknownField(field_name.to_string()).into())
+    }
+
+    fn slot_pos(&self, slot: i32) -> usize {
+        HEADER_SIZE + slot as usize * self.layout.slot_size()
+    }
+}
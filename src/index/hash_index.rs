@@ -0,0 +1,249 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    query::{
+        constant::Constant,
+        scan::{Scan, UpdateScan},
+    },
+    record::{layout::Layout, rid::RecordId, table_scan::TableScan},
+    tx::transaction::Transaction,
+};
+
+use super::index::{index_table_name, Index};
+
+pub const NUM_BUCKETS: usize = 100;
+
+#[derive(Debug)]
+enum HashIndexError {
+    NotPositioned,
+}
+
+impl std::error::Error for HashIndexError {}
+impl fmt::Display for HashIndexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HashIndexError::NotPositioned => write!(f, "hash index is not positioned on a bucket"),
+        }
+    }
+}
+
+/// A static hash implementation of the Index interface.
+/// A fixed number of buckets is allocated, and each bucket is implemented as a file of index records
+/// named `<index>#<bucket>.tbl`.
+#[derive(Debug)]
+pub struct HashIndex {
+    tx: Arc<Mutex<Transaction>>,
+    index_name: String,
+    layout: Layout,
+    search_key: Option<Constant>,
+    scan: Option<TableScan>,
+}
+
+impl HashIndex {
+    pub fn new(tx: Arc<Mutex<Transaction>>, index_name: &str, layout: Layout) -> Self {
+        Self {
+            tx,
+            index_name: index_name.to_string(),
+            layout,
+            search_key: None,
+            scan: None,
+        }
+    }
+
+    /// Return the cost of searching an index file having the specified number of blocks.
+    /// The method assumes that all buckets are about the same size, and so the cost is simply the size of the bucket.
+    pub fn search_cost(num_blocks: usize, _records_per_block: usize) -> usize {
+        num_blocks / NUM_BUCKETS
+    }
+
+    fn current_scan(&mut self) -> Result<&mut TableScan> {
+        self.scan
+            .as_mut()
+            .ok_or_else(|| HashIndexError::NotPositioned.into())
+    }
+}
+
+/// Hash a search key to its bucket.
+/// The hash is spelled out rather than taken from `std::hash`, since the buckets are stored on disk
+/// and must not change between builds.
+fn bucket(search_key: &Constant) -> usize {
+    let hash = match search_key {
+        Constant::Int(n) => *n as u32,
+        Constant::String(s) => s
+            .bytes()
+            .fold(0u32, |hash, b| hash.wrapping_mul(31).wrapping_add(b as u32)),
+    };
+    hash as usize % NUM_BUCKETS
+}
+
+impl Index for HashIndex {
+    /// Position the index before the first index record having the specified search key.
+    /// The method hashes the search key to determine the bucket, and then opens a table scan on the file
+    /// corresponding to the bucket. The table scan for the previous bucket (if any) is closed.
+    fn before_first(&mut self, search_key: &Constant) -> Result<()> {
+        self.close()?;
+        let table_name = index_table_name(&self.index_name, bucket(search_key));
+        self.scan = Some(TableScan::new(
+            Arc::clone(&self.tx),
+            &table_name,
+            self.layout.clone(),
+        )?);
+        self.search_key = Some(search_key.clone());
+        Ok(())
+    }
+
+    /// Move to the next record having the search key.
+    /// The method loops through the table scan for the bucket, looking for a matching record.
+    fn next(&mut self) -> Result<bool> {
+        let search_key = self.search_key.clone();
+        let scan = self.current_scan()?;
+        while scan.next()? {
+            if Some(scan.get_val("dataval")?) == search_key {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Retrieve the data record id from the current record in the table scan for the bucket.
+    fn get_data_rid(&self) -> Result<RecordId> {
+        let scan = self.scan.as_ref().ok_or(HashIndexError::NotPositioned)?;
+        let block_number = scan.get_int("block")?;
+        let id = scan.get_int("id")?;
+        Ok(RecordId::new(block_number as usize, id))
+    }
+
+    /// Insert a new record into the table scan for the bucket.
+    fn insert(&mut self, data_val: &Constant, data_rid: &RecordId) -> Result<()> {
+        self.before_first(data_val)?;
+        let scan = self.current_scan()?;
+        scan.insert()?;
+        scan.set_int("block", data_rid.block_number() as i32)?;
+        scan.set_int("id", data_rid.slot())?;
+        scan.set_val("dataval", data_val)
+    }
+
+    /// Delete the specified record from the table scan for the bucket.
+    /// The method starts at the beginning of the scan, and loops through the records until the specified record is found.
+    fn delete(&mut self, data_val: &Constant, data_rid: &RecordId) -> Result<()> {
+        self.before_first(data_val)?;
+        while self.next()? {
+            if self.get_data_rid()? == *data_rid {
+                return self.current_scan()?.delete();
+            }
+        }
+        Ok(())
+    }
+
+    /// Close the index by closing the current table scan.
+    fn close(&mut self) -> Result<()> {
+        if let Some(mut scan) = self.scan.take() {
+            scan.close()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+
+    use crate::{
+        index::index::Index,
+        query::constant::Constant,
+        record::{layout::Layout, rid::RecordId, schema::Schema},
+        server::simpledb::SimpleDB,
+    };
+
+    use super::{bucket, HashIndex};
+
+    #[test]
+    fn test_hash_index() {
+        let temp_dir = tempdir().unwrap();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+        let tx = db.new_tx().unwrap();
+
+        let mut schema = Schema::new();
+        schema.add_int_field("block");
+        schema.add_int_field("id");
+        schema.add_int_field("dataval");
+        let mut index = HashIndex::new(Arc::clone(&tx), "idx", Layout::new(schema));
+        assert!(index.next().is_err());
+
+        // keys 1 and 101 share a bucket
+        for (i, key) in [1, 101, 1, 2].into_iter().enumerate() {
+            index
+                .insert(&Constant::Int(key), &RecordId::new(i, i as i32))
+                .unwrap();
+        }
+
+        let mut rids = vec![];
+        index.before_first(&Constant::Int(1)).unwrap();
+        while index.next().unwrap() {
+            rids.push(index.get_data_rid().unwrap());
+        }
+        assert_eq!(rids, [RecordId::new(0, 0), RecordId::new(2, 2)]);
+
+        index
+            .delete(&Constant::Int(1), &RecordId::new(0, 0))
+            .unwrap();
+        index.before_first(&Constant::Int(1)).unwrap();
+        assert!(index.next().unwrap());
+        assert_eq!(index.get_data_rid().unwrap(), RecordId::new(2, 2));
+        assert!(!index.next().unwrap());
+
+        index.before_first(&Constant::Int(3)).unwrap();
+        assert!(!index.next().unwrap());
+        assert!(index
+            .before_first_range(Some(&Constant::Int(0)), None)
+            .is_err());
+        index.close().unwrap();
+
+        tx.lock().unwrap().commit().unwrap();
+    }
+
+    #[test]
+    fn test_bucket_does_not_clash_with_table() {
+        let temp_dir = tempdir().unwrap();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+        let planner = db.planner();
+        let tx = db.new_tx().unwrap();
+
+        // the key 7 hashes to bucket 7 of the index idx, whose name is also a valid table name
+        for sql in [
+            "create table idx7(X int)",
+            "insert into idx7(X) values (1)",
+            "create table t(A int)",
+            "create index idx on t(A)",
+            "insert into t(A) values (7)",
+        ] {
+            planner.execute_update(sql, Arc::clone(&tx)).unwrap();
+        }
+        tx.lock().unwrap().commit().unwrap();
+
+        let tx = db.new_tx().unwrap();
+        let plan = planner
+            .create_query_plan("select X from idx7", Arc::clone(&tx))
+            .unwrap();
+        let mut scan = plan.open().unwrap();
+        let mut result = vec![];
+        while scan.next().unwrap() {
+            result.push(scan.get_int("x").unwrap());
+        }
+        scan.close().unwrap();
+        assert_eq!(result, [1]);
+        tx.lock().unwrap().commit().unwrap();
+    }
+
+    #[test]
+    fn test_bucket_is_stable() {
+        assert_eq!(bucket(&Constant::Int(42)), 42);
+        assert_eq!(bucket(&Constant::Int(-1)), 95);
+        // "abc" hashes to 96354, as Java's String.hashCode does
+        assert_eq!(bucket(&Constant::String("abc".to_string())), 54);
+    }
+}
@@ -0,0 +1,87 @@
+use anyhow::Result;
+use core::fmt;
+
+use crate::{query::constant::Constant, record::rid::RecordId};
+
+#[derive(Debug)]
+enum IndexError {
+    RangeNotSupported,
+}
+
+impl std::error::Error for IndexError {}
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IndexError::RangeNotSupported => write!(f, "index does not support range searches"),
+        }
+    }
+}
+
+/// The data structure that implements an index.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IndexType {
+    #[default]
+    Hash,
+    BTree,
+}
+
+impl fmt::Display for IndexType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl IndexType {
+    /// Return the name of the index type, as used in SQL and in the catalog.
+    pub fn name(&self) -> &'static str {
+        match self {
+            IndexType::Hash => "hash",
+            IndexType::BTree => "btree",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "hash" => Some(IndexType::Hash),
+            "btree" => Some(IndexType::BTree),
+            _ => None,
+        }
+    }
+}
+
+/// Return the name of the table that stores a part of an index, such as one of its buckets.
+/// The index name and the part are joined by `#`, which the lexer does not accept in identifiers,
+/// so that the files of an index never clash with those of a user table.
+pub fn index_table_name(index_name: &str, part: impl fmt::Display) -> String {
+    format!("{}#{}", index_name, part)
+}
+
+/// The interface that contains methods to traverse an index.
+pub trait Index {
+    /// Position the index before the first record having the specified search key.
+    fn before_first(&mut self, search_key: &Constant) -> Result<()>;
+
+    /// Position the index before the first record whose dataval lies between `lo` and `hi`, inclusive.
+    /// A missing bound leaves that end of the range open.
+    /// Only ordered indexes support range searches; the others return an error.
+    fn before_first_range(&mut self, _lo: Option<&Constant>, _hi: Option<&Constant>) -> Result<()> {
+        Err(IndexError::RangeNotSupported.into())
+    }
+
+    /// Move the index to the next record having the search key specified in `before_first`,
+    /// or lying in the range specified in `before_first_range`.
+    /// Return false if there are no more such index records.
+    fn next(&mut self) -> Result<bool>;
+
+    /// Return the data record id stored in the current index record.
+    fn get_data_rid(&self) -> Result<RecordId>;
+
+    /// Insert an index record having the specified dataval and data record id values.
+    fn insert(&mut self, data_val: &Constant, data_rid: &RecordId) -> Result<()>;
+
+    /// Delete the index record having the specified dataval and data record id values.
+    fn delete(&mut self, data_val: &Constant, data_rid: &RecordId) -> Result<()>;
+
+    /// Close the index.
+    fn close(&mut self) -> Result<()>;
+}
@@ -2,6 +2,14 @@
 pub type Lsn = i64;
 
 pub mod buffer;
+pub mod client;
 pub mod file;
+pub mod index;
 pub mod log;
+pub mod metadata;
+pub mod parse;
+pub mod plan;
+pub mod query;
+pub mod record;
+pub mod server;
 pub mod tx;
\ No newline at end of file
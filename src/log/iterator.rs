@@ -1,5 +1,5 @@
 use anyhow::Result;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 
 use crate::file::block_id::BlockId;
 use crate::file::manager::FileManager;
@@ -7,7 +7,7 @@ use crate::file::page::Page;
 
 #[derive(Debug)]
 pub struct LogIterator {
-    file_manager: Arc<Mutex<FileManager>>,
+    file_manager: Arc<FileManager>,
     block: BlockId,
     page: Page,
     current_pos: usize,
@@ -15,12 +15,11 @@ pub struct LogIterator {
 }
 
 impl LogIterator {
-    pub fn new(file_manager: Arc<Mutex<FileManager>>, block: &BlockId) -> Result<Self> {
-        let block_size = file_manager.lock().unwrap().block_size();
+    pub fn new(file_manager: Arc<FileManager>, block: &BlockId) -> Result<Self> {
         let mut iterator = Self {
             file_manager: Arc::clone(&file_manager),
             block: block.clone(),
-            page: Page::from_bytes(vec![0; block_size]),
+            page: file_manager.new_page(),
             current_pos: 0,
             boundary: 0,
         };
@@ -30,15 +29,11 @@ impl LogIterator {
     }
 
     pub fn has_next(&self) -> bool {
-        self.current_pos < self.file_manager.lock().unwrap().block_size()
-            || self.block.block_number() > 0
+        self.current_pos < self.file_manager.block_size() || self.block.block_number() > 0
     }
 
     fn move_to_block(&mut self, block: &BlockId) -> Result<()> {
-        self.file_manager
-            .lock()
-            .unwrap()
-            .read(block, &mut self.page)?;
+        self.file_manager.read(block, &mut self.page)?;
         self.boundary = self.page.get_int(0)? as usize;
         self.current_pos = self.boundary;
         Ok(())
@@ -49,13 +44,14 @@ impl Iterator for LogIterator {
     type Item = Vec<u8>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current_pos == self.file_manager.lock().unwrap().block_size() {
+        if self.current_pos == self.file_manager.block_size() {
             self.block = BlockId::new(self.block.filename(), self.block.block_number() - 1);
             self.move_to_block(&self.block.clone()).ok();
         }
 
         self.page
             .get_bytes(self.current_pos)
+            .map(<[u8]>::to_vec)
             .inspect(|rec| self.current_pos += std::mem::size_of::<i32>() + rec.len())
             .ok()
     }
@@ -1,58 +1,83 @@
 use anyhow::{Ok, Result};
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 
-use crate::{file::{block_id::BlockId, manager::FileManager, page::Page}, Lsn};
+use crate::{
+    file::{
+        block_id::BlockId,
+        manager::{FileManager, FileManagerError},
+        page::Page,
+    },
+    Lsn,
+};
 
 use super::iterator::LogIterator;
 
 #[derive(Debug)]
 pub struct LogManager {
-    file_manager: Arc<Mutex<FileManager>>,
+    file_manager: Arc<FileManager>,
     logfile: String,
     logpage: Page,
     current_block: BlockId,
     // log sequence number
     latest_lsn: Lsn,
     last_saved_lsn: Lsn,
+    // whether the current block holds flushed records, in which case it is not written again
+    block_flushed: bool,
 }
 
 /// The log manager is responsible for writing log records to the log file from right to left.
 /// A log sequence number (or LSN)identifies identifies a log record.
 /// The log manager keeps track of the next available LSN and the LSN of the most recent log record written to disk.
+/// Once the records of a block are flushed, the block is never written again and later records go to the next block,
+/// so that a write torn by a crash cannot lose records that were already flushed. Each flush thus ends a block.
 impl LogManager {
-    pub fn new(file_manager: Arc<Mutex<FileManager>>, log_file: &str) -> Result<Self> {
-        let block_size = file_manager.lock().unwrap().block_size();
-
+    /// Open the log file, creating it if it does not exist.
+    /// If the last block of the log fails its checksum, its write was torn by a crash before its flush completed,
+    /// so none of its records were flushed: that block is the end of the log, and it is started afresh.
+    pub fn new(file_manager: Arc<FileManager>, log_file: &str) -> Result<Self> {
         let mut log_manager = LogManager {
             file_manager: Arc::clone(&file_manager),
             logfile: log_file.to_string(),
-            logpage: Page::from_bytes(vec![0; block_size]),
+            logpage: file_manager.new_page(),
             current_block: BlockId::new(log_file, 0),
             latest_lsn: 0,
             last_saved_lsn: 0,
+            block_flushed: false,
         };
 
         // If the log file does not yet exist, create it with an empty first block
-        let log_size = file_manager.lock().unwrap().length(log_file)?;
+        let log_size = file_manager.length(log_file)?;
         log_manager.current_block = if log_size == 0 {
             log_manager.append_new_block()?
         } else {
             let block = BlockId::new(log_file, log_size - 1);
-            file_manager
-                .lock()
-                .unwrap()
-                .read(&block, &mut log_manager.logpage)?;
+            match file_manager.read(&block, &mut log_manager.logpage) {
+                Err(err)
+                    if matches!(
+                        err.downcast_ref::<FileManagerError>(),
+                        Some(FileManagerError::Corrupted { .. })
+                    ) =>
+                {
+                    log_manager.start_block(&block)?;
+                }
+                result => result?,
+            }
             block
         };
+        log_manager.block_flushed =
+            log_manager.logpage.get_int(0)? < file_manager.block_size() as i32;
 
         Ok(log_manager)
     }
 
-    /// Ensures that the log record with specified LSN (and all previous log records) is written to disk
+    /// Ensures that the log record with specified LSN (and all previous log records) is written to disk.
+    /// The log file is then synced, in case the file manager does not sync every write, since a full log page may
+    /// have been written without being synced.
     pub fn flush(&mut self, lsn: Lsn) -> Result<()> {
         if lsn >= self.last_saved_lsn {
             self.do_flush()?;
         }
+        self.file_manager.sync(&self.logfile)?;
 
         Ok(())
     }
@@ -68,19 +93,19 @@ impl LogManager {
     /// The first 4 bytes of the page is the ofsset of the most recently added record,
     /// so that the iterator will know where the records begin.
     pub fn append(&mut self, logrec: &[u8]) -> Result<i64> {
-        let mut boundary = self.logpage.get_int(0)?;
         let int_bytes = std::mem::size_of::<i32>() as i32;
         let recsize = logrec.len() as i32;
         let bytes_needed = recsize + int_bytes;
-        // the log record doesn't fit, so write the current page to disk,
-        // clear the page and append the now-empty page to the log file
-        if boundary - bytes_needed < int_bytes {
-            // so move to the next block
+        // the log record doesn't fit, so write the current page to disk
+        if self.logpage.get_int(0)? - bytes_needed < int_bytes {
             self.do_flush()?;
-            self.current_block = self.append_new_block()?;
-            boundary = self.logpage.get_int(0)?;
+        }
+        // the records of the current block are flushed, so move to the next block
+        if self.block_flushed {
+            self.move_to_next_block()?;
         }
 
+        let boundary = self.logpage.get_int(0)?;
         let recpos = boundary - bytes_needed;
         self.logpage.set_bytes(recpos as usize, logrec)?;
         self.logpage.set_int(0, recpos)?; // the new boundary
@@ -89,24 +114,41 @@ impl LogManager {
     }
 
     fn do_flush(&mut self) -> Result<()> {
+        if self.latest_lsn == self.last_saved_lsn {
+            return Ok(());
+        }
         self.file_manager
-            .lock()
-            .unwrap()
-            .write(&self.current_block, &mut self.logpage)?;
+            .write_log(&self.current_block, &self.logpage)?;
 
         self.last_saved_lsn = self.latest_lsn;
+        self.block_flushed = true;
+
+        Ok(())
+    }
 
+    /// Clear the page for the block after the current one.
+    /// The new block is only written to the log file when its records are flushed.
+    fn move_to_next_block(&mut self) -> Result<()> {
+        self.current_block = BlockId::new(&self.logfile, self.current_block.block_number() + 1);
+        self.logpage
+            .set_int(0, self.file_manager.block_size() as i32)?;
+        self.block_flushed = false;
         Ok(())
     }
 
     /// writes
     fn append_new_block(&mut self) -> Result<BlockId> {
-        let mut guard = self.file_manager.lock().unwrap();
-        let block = guard.append(&self.logfile)?;
-        self.logpage.set_int(0, guard.block_size() as i32)?;
-        guard.write(&block, &mut self.logpage)?;
+        let block = self.file_manager.append(&self.logfile)?;
+        self.start_block(&block)?;
         Ok(block)
     }
+
+    /// Write an empty log page to the block.
+    fn start_block(&mut self, block: &BlockId) -> Result<()> {
+        self.logpage
+            .set_int(0, self.file_manager.block_size() as i32)?;
+        self.file_manager.write_log(block, &self.logpage)
+    }
 }
 
 #[cfg(test)]
@@ -115,7 +157,11 @@ mod tests {
     use std::sync::{Arc, Mutex};
     use tempfile::tempdir;
 
-    use crate::{file::manager::FileManager, file::page::Page, log::manager::LogManager};
+    use crate::{
+        file::manager::{FileManager, BLOCK_HEADER_SIZE},
+        file::page::Page,
+        log::manager::LogManager,
+    };
 
     #[test]
     fn logtest() {
@@ -128,9 +174,8 @@ mod tests {
             .to_string();
 
         let block_size = 512;
-        let file_manager = Arc::new(Mutex::new(
-            FileManager::new(temp_dir.path().to_str().unwrap(), block_size).unwrap(),
-        ));
+        let file_manager =
+            Arc::new(FileManager::new(temp_dir.path().to_str().unwrap(), block_size).unwrap());
         let log_manager = Arc::new(Mutex::new(
             LogManager::new(Arc::clone(&file_manager), &test_log_file).unwrap(),
         ));
@@ -141,13 +186,100 @@ mod tests {
         assert_log_records(Arc::clone(&log_manager), 50, 1);
     }
 
+    #[test]
+    fn test_torn_log_tail() {
+        let temp_dir = tempdir().unwrap();
+        let db_dir = temp_dir.path().to_str().unwrap();
+        let block_size = 512;
+        let open = || {
+            let file_manager = Arc::new(
+                FileManager::new(db_dir, block_size)
+                    .unwrap()
+                    .with_checksums(true),
+            );
+            let log_manager = LogManager::new(Arc::clone(&file_manager), "logtest").unwrap();
+            (file_manager, Arc::new(Mutex::new(log_manager)))
+        };
+
+        // append records until they fill the first block, and a few more into the second one
+        let (file_manager, log_manager) = open();
+        let mut kept = 0;
+        for i in 1.. {
+            create_records(Arc::clone(&log_manager), i, i);
+            if log_manager.lock().unwrap().current_block.block_number() > 0 {
+                kept = i - 1;
+                break;
+            }
+        }
+        create_records(Arc::clone(&log_manager), kept + 2, kept + 4);
+        log_manager.lock().unwrap().flush(kept as i64 + 4).unwrap();
+        drop(log_manager);
+        drop(file_manager);
+
+        // a crash tears the last write of the log's last block
+        let path = temp_dir.path().join("logtest");
+        let len = std::fs::metadata(&path).unwrap().len();
+        let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(len - (block_size / 2) as u64).unwrap();
+        drop(file);
+
+        let (file_manager, log_manager) = open();
+        assert_eq!(file_manager.length("logtest").unwrap(), 2);
+        assert_log_records(Arc::clone(&log_manager), kept, 1);
+        // the log can be appended to again
+        create_records(Arc::clone(&log_manager), 1, 5);
+    }
+
+    #[test]
+    fn test_torn_log_rewrite() {
+        let temp_dir = tempdir().unwrap();
+        let db_dir = temp_dir.path().to_str().unwrap();
+        let block_size = 512;
+        let open = || {
+            let file_manager = Arc::new(
+                FileManager::new(db_dir, block_size)
+                    .unwrap()
+                    .with_checksums(true),
+            );
+            let log_manager = LogManager::new(Arc::clone(&file_manager), "logtest").unwrap();
+            Arc::new(Mutex::new(log_manager))
+        };
+        let path = temp_dir.path().join("logtest");
+
+        // two flushes, the second of which leaves room in the block
+        let log_manager = open();
+        create_records(Arc::clone(&log_manager), 1, 3);
+        log_manager.lock().unwrap().flush(3).unwrap();
+        let before = std::fs::read(&path).unwrap();
+        create_records(Arc::clone(&log_manager), 4, 6);
+        log_manager.lock().unwrap().flush(6).unwrap();
+        let after = std::fs::read(&path).unwrap();
+        drop(log_manager);
+
+        // a crash tears the second flush: half of its block is written, and the rest is as before
+        let disk_block_size = BLOCK_HEADER_SIZE + block_size;
+        let start = after.len() - disk_block_size;
+        let mut torn = after[..start + disk_block_size / 2].to_vec();
+        torn.extend_from_slice(
+            before
+                .get(torn.len()..start + disk_block_size)
+                .unwrap_or_default(),
+        );
+        std::fs::write(&path, &torn).unwrap();
+
+        // the records of the first flush survive
+        let log_manager = open();
+        assert_log_records(Arc::clone(&log_manager), 3, 1);
+        create_records(Arc::clone(&log_manager), 1, 5);
+    }
+
     fn assert_log_records(log_manager: Arc<Mutex<LogManager>>, start: i32, end: i32) {
         let mut iter = log_manager.lock().unwrap().iterator().unwrap();
         let mut current = start;
 
         while iter.has_next() {
             if let Some(rec) = iter.next() {
-                let mut p = Page::from_bytes(rec);
+                let p = Page::from_bytes(rec);
                 let s = p.get_string(0).unwrap();
                 let npos = Page::max_length(s.len());
                 let val = p.get_int(npos).unwrap();
@@ -177,6 +309,6 @@ mod tests {
         let mut p = Page::from_bytes(bytes);
         p.set_string(0, s).unwrap();
         p.set_int(npos, n).unwrap();
-        p.contents().as_bytes().to_vec()
+        p.contents().to_vec()
     }
 }
@@ -1,3 +1,259 @@
-fn main() {
-    println!("Hello, world!");
+use anyhow::Result;
+use std::{
+    env,
+    io::{self, BufRead, Write},
+    sync::{Arc, Mutex},
+};
+
+use simpledb::{
+    query::constant::Constant,
+    record::schema::{FieldType, Schema},
+    server::simpledb::SimpleDB,
+    tx::transaction::Transaction,
+};
+
+const DEFAULT_DB_DIR: &str = "simpledb_data";
+
+const HELP: &str = "\
+Enter SQL statements terminated by ';'.
+  commit              commit the current transaction
+  rollback            roll back the current transaction
+  .tables             list the tables
+  .schema <table>     show the fields of a table
+  .help               show this message
+  .exit               roll back uncommitted work and quit";
+
+/// An interactive shell that runs SQL statements against a database directory.
+/// All statements run in the current transaction until `commit` or `rollback`;
+/// a failed statement rolls the transaction back.
+struct Shell {
+    db: SimpleDB,
+    tx: Arc<Mutex<Transaction>>,
+}
+
+impl Shell {
+    fn new(db_dir: &str) -> Result<Self> {
+        let db = SimpleDB::open(db_dir)?;
+        let tx = db.new_tx()?;
+        Ok(Self { db, tx })
+    }
+
+    /// Execute one command and return its output.
+    fn execute(&mut self, cmd: &str) -> Result<String> {
+        let mut words = cmd.split_whitespace();
+        match words.next().unwrap_or_default().to_lowercase().as_str() {
+            "commit" => {
+                self.tx.lock().unwrap().commit()?;
+                self.tx = self.db.new_tx()?;
+                Ok("transaction committed".to_string())
+            }
+            "rollback" => {
+                self.rollback()?;
+                Ok("transaction rolled back".to_string())
+            }
+            ".tables" => {
+                let mut table_names = self
+                    .db
+                    .metadata_manager()
+                    .table_names(Arc::clone(&self.tx))?;
+                table_names.sort();
+                Ok(table_names.join("\n"))
+            }
+            ".schema" => match words.next() {
+                Some(table_name) => self.schema(&table_name.to_lowercase()),
+                None => Ok("usage: .schema <table>".to_string()),
+            },
+            ".help" => Ok(HELP.to_string()),
+            "select" => self.query(cmd),
+            _ => {
+                let count = self
+                    .db
+                    .planner()
+                    .execute_update(cmd, Arc::clone(&self.tx))?;
+                Ok(format!("{} record(s) affected", count))
+            }
+        }
+    }
+
+    fn query(&self, sql: &str) -> Result<String> {
+        let plan = self
+            .db
+            .planner()
+            .create_query_plan(sql, Arc::clone(&self.tx))?;
+        let fields = plan.schema().fields().to_vec();
+
+        let mut rows = vec![];
+        let mut scan = plan.open()?;
+        while scan.next()? {
+            let row = fields
+                .iter()
+                .map(|field_name| {
+                    Ok(match scan.get_val(field_name)? {
+                        Constant::Int(n) => n.to_string(),
+                        Constant::String(s) => s,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            rows.push(row);
+        }
+        scan.close()?;
+
+        Ok(format_table(&fields, &rows))
+    }
+
+    fn schema(&self, table_name: &str) -> Result<String> {
+        let layout = self
+            .db
+            .metadata_manager()
+            .get_layout(table_name, Arc::clone(&self.tx))?;
+        Ok(format!(
+            "create table {} ({})",
+            table_name,
+            field_defs(layout.schema()).join(", ")
+        ))
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        self.tx.lock().unwrap().rollback()?;
+        self.tx = self.db.new_tx()?;
+        Ok(())
+    }
+}
+
+fn field_defs(schema: &Schema) -> Vec<String> {
+    schema
+        .fields()
+        .iter()
+        .map(|field_name| match schema.field_type(field_name) {
+            Some(FieldType::Varchar) => format!(
+                "{} varchar({})",
+                field_name,
+                schema.length(field_name).unwrap_or_default()
+            ),
+            _ => format!("{} int", field_name),
+        })
+        .collect()
+}
+
+/// Lay out the rows as a table whose columns are as wide as their widest value.
+fn format_table(fields: &[String], rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field_name)| {
+            rows.iter()
+                .map(|row| row[i].len())
+                .fold(field_name.len(), usize::max)
+        })
+        .collect();
+
+    let format_row = |row: &[String]| {
+        row.iter()
+            .zip(&widths)
+            .map(|(val, width)| format!("{:<width$}", val, width = width))
+            .collect::<Vec<_>>()
+            .join(" | ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![format_row(fields)];
+    lines.push(
+        widths
+            .iter()
+            .map(|width| "-".repeat(*width))
+            .collect::<Vec<_>>()
+            .join("-+-"),
+    );
+    lines.extend(rows.iter().map(|row| format_row(row)));
+    lines.push(format!("({} row(s))", rows.len()));
+    lines.join("\n")
+}
+
+/// Run a command in the shell and print its output.
+/// A failed command rolls back the current transaction.
+fn run(shell: &mut Shell, cmd: &str) -> Result<()> {
+    match shell.execute(cmd) {
+        Ok(output) => println!("{}", output),
+        Err(err) => {
+            println!("error: {}", err);
+            shell.rollback()?;
+            println!("transaction rolled back");
+        }
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let db_dir = env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_DB_DIR.to_string());
+    let mut shell = Shell::new(&db_dir)?;
+    println!("connected to {}; type .help for help", db_dir);
+
+    let mut lines = io::stdin().lock().lines();
+    let mut statement = String::new();
+    loop {
+        print!(
+            "{}",
+            if statement.is_empty() {
+                "sql> "
+            } else {
+                "...> "
+            }
+        );
+        io::stdout().flush()?;
+
+        let Some(line) = lines.next().transpose()? else {
+            break;
+        };
+        let line = line.trim();
+
+        if statement.is_empty() {
+            // meta commands, commit and rollback fit on one line and need no terminating ';'
+            let cmd = line.trim_end_matches(';').trim();
+            match cmd.to_lowercase().as_str() {
+                "" => continue,
+                ".exit" | ".quit" => break,
+                "commit" | "rollback" => {
+                    run(&mut shell, cmd)?;
+                    continue;
+                }
+                _ if cmd.starts_with('.') => {
+                    run(&mut shell, cmd)?;
+                    continue;
+                }
+                _ => {}
+            }
+        } else {
+            statement.push(' ');
+        }
+
+        statement.push_str(line);
+        if statement.ends_with(';') {
+            run(&mut shell, statement.trim_end_matches(';'))?;
+            statement.clear();
+        }
+    }
+
+    // uncommitted work is discarded on exit
+    shell.rollback()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_table;
+
+    #[test]
+    fn test_format_table() {
+        let fields = vec!["a".to_string(), "name".to_string()];
+        let rows = vec![
+            vec!["1".to_string(), "joe".to_string()],
+            vec!["100".to_string(), "amy".to_string()],
+        ];
+        assert_eq!(
+            format_table(&fields, &rows),
+            "a   | name\n----+-----\n1   | joe\n100 | amy\n(2 row(s))"
+        );
+    }
 }
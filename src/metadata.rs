@@ -0,0 +1,5 @@
+pub mod index_manager;
+pub mod manager;
+pub mod stat_manager;
+pub mod table_manager;
+pub mod view_manager;
@@ -0,0 +1,305 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    index::{
+        btree_index::BTreeIndex,
+        btree_page::BTreePage,
+        hash_index::HashIndex,
+        index::{Index, IndexType},
+    },
+    query::scan::{Scan, UpdateScan},
+    record::{
+        layout::Layout,
+        schema::{FieldType, Schema},
+        table_scan::TableScan,
+    },
+    tx::transaction::Transaction,
+};
+
+use super::{
+    stat_manager::{StatInfo, StatManager},
+    table_manager::{TableManager, MAX_NAME},
+};
+
+pub const INDEX_CATALOG: &str = "idxcat";
+
+#[derive(Debug)]
+enum IndexManagerError {
+    UnknownIndexType(String),
+    IndexExists(String),
+    FieldIndexed(String, String),
+    KeyTooLarge {
+        field_name: String,
+        slot_size: usize,
+        block_size: usize,
+    },
+}
+
+impl std::error::Error for IndexManagerError {}
+impl fmt::Display for IndexManagerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IndexManagerError::UnknownIndexType(name) => write!(f, "unknown index type: {}", name),
+            IndexManagerError::IndexExists(name) => write!(f, "index already exists: {}", name),
+            IndexManagerError::FieldIndexed(table_name, field_name) => write!(
+                f,
+                "field {} of table {} already has an index",
+                field_name, table_name
+            ),
+            IndexManagerError::KeyTooLarge {
+                field_name,
+                slot_size,
+                block_size,
+            } => write!(
+                f,
+                "index records of field {} need {} bytes, too many for a block of {} bytes",
+                field_name, slot_size, block_size
+            ),
+        }
+    }
+}
+
+/// The information about an index.
+/// This information is used by the query planner in order to estimate the costs of using the index,
+/// and to obtain the layout of the index records.
+#[derive(Debug, Clone)]
+pub struct IndexInfo {
+    index_name: String,
+    field_name: String,
+    index_type: IndexType,
+    tx: Arc<Mutex<Transaction>>,
+    index_layout: Layout,
+    stat_info: StatInfo,
+}
+
+impl IndexInfo {
+    pub fn new(
+        index_name: &str,
+        field_name: &str,
+        index_type: IndexType,
+        table_schema: &Schema,
+        tx: Arc<Mutex<Transaction>>,
+        stat_info: StatInfo,
+    ) -> Self {
+        let index_layout = Self::create_index_layout(field_name, table_schema);
+        Self {
+            index_name: index_name.to_string(),
+            field_name: field_name.to_string(),
+            index_type,
+            tx,
+            index_layout,
+            stat_info,
+        }
+    }
+
+    pub fn index_name(&self) -> &str {
+        &self.index_name
+    }
+
+    pub fn field_name(&self) -> &str {
+        &self.field_name
+    }
+
+    pub fn index_type(&self) -> IndexType {
+        self.index_type
+    }
+
+    pub fn index_layout(&self) -> &Layout {
+        &self.index_layout
+    }
+
+    /// Open the index described by this object.
+    pub fn open(&self) -> Result<Box<dyn Index>> {
+        match self.index_type {
+            IndexType::Hash => Ok(Box::new(HashIndex::new(
+                Arc::clone(&self.tx),
+                &self.index_name,
+                self.index_layout.clone(),
+            ))),
+            IndexType::BTree => Ok(Box::new(BTreeIndex::new(
+                Arc::clone(&self.tx),
+                &self.index_name,
+                self.index_layout.clone(),
+            )?)),
+        }
+    }
+
+    /// Estimate the number of block accesses required to find all index records having a particular search key.
+    /// The method uses the table's metadata to estimate the size of the index file and the number of index records per block.
+    /// It then passes this information to the traversal cost method of the appropriate index type, which provides the estimate.
+    pub fn blocks_accessed(&self) -> usize {
+        // an index created before wide keys were rejected may not fit a record in a block
+        let records_per_block =
+            (self.tx.lock().unwrap().block_size() / self.index_layout.slot_size()).max(1);
+        let num_blocks = self.stat_info.records_output() / records_per_block;
+        match self.index_type {
+            IndexType::Hash => HashIndex::search_cost(num_blocks, records_per_block),
+            IndexType::BTree => BTreeIndex::search_cost(num_blocks, records_per_block),
+        }
+    }
+
+    /// Return the estimated number of records having a search key.
+    /// This value is the same as doing a select query; that is, it is the number of records in the table
+    /// divided by the number of distinct values of the indexed field.
+    pub fn records_output(&self) -> usize {
+        self.stat_info.records_output() / self.stat_info.distinct_values(&self.field_name)
+    }
+
+    /// Return the distinct values for a specified field in the underlying table, or 1 for the indexed field.
+    pub fn distinct_values(&self, field_name: &str) -> usize {
+        if self.field_name == field_name {
+            1
+        } else {
+            self.stat_info.distinct_values(field_name)
+        }
+    }
+
+    /// Return the layout of the index records.
+    /// The schema consists of the dataRID (which is represented as two integers, the block number and the record ID)
+    /// and the dataval (which is the indexed field). Schema information about the indexed field is obtained
+    /// via the table's schema.
+    fn create_index_layout(field_name: &str, table_schema: &Schema) -> Layout {
+        let mut schema = Schema::new();
+        schema.add_int_field("block");
+        schema.add_int_field("id");
+        match table_schema.field_type(field_name) {
+            Some(FieldType::Varchar) => {
+                schema.add_string_field("dataval", table_schema.length(field_name).unwrap_or(0))
+            }
+            _ => schema.add_int_field("dataval"),
+        }
+        Layout::new(schema)
+    }
+}
+
+/// The index manager stores the index metadata in the catalog table `idxcat`,
+/// one record per index holding the name of the index, the name of the table being indexed and the name of the indexed field.
+#[derive(Debug, Clone)]
+pub struct IndexManager {
+    layout: Layout,
+    table_manager: TableManager,
+    stat_manager: Arc<Mutex<StatManager>>,
+}
+
+impl IndexManager {
+    /// Create the index manager.
+    /// This constructor is called during system startup. If the database is new, then the `idxcat` table is created.
+    pub fn new(
+        is_new: bool,
+        table_manager: TableManager,
+        stat_manager: Arc<Mutex<StatManager>>,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Self> {
+        if is_new {
+            let mut schema = Schema::new();
+            schema.add_string_field("indexname", MAX_NAME);
+            schema.add_string_field("tablename", MAX_NAME);
+            schema.add_string_field("fieldname", MAX_NAME);
+            schema.add_string_field("indextype", MAX_NAME);
+            table_manager.create_table(INDEX_CATALOG, &schema, Arc::clone(&tx))?;
+        }
+
+        let layout = table_manager.get_layout(INDEX_CATALOG, tx)?;
+        Ok(Self {
+            layout,
+            table_manager,
+            stat_manager,
+        })
+    }
+
+    /// Create an index of the specified type for the specified field.
+    /// A unique ID is assigned to this index, and its information is stored in the `idxcat` table.
+    /// The index name must be new, since it names the index's files, and a field can only have one index.
+    /// A block must have room for the index records, which rules out indexes on the widest varchar fields.
+    pub fn create_index(
+        &self,
+        index_name: &str,
+        table_name: &str,
+        field_name: &str,
+        index_type: IndexType,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        let table_layout = self.table_manager.get_layout(table_name, Arc::clone(&tx))?;
+        let index_layout = IndexInfo::create_index_layout(field_name, table_layout.schema());
+        let block_size = tx.lock().unwrap().block_size();
+        let fits = match index_type {
+            IndexType::Hash => index_layout.slot_size() <= block_size,
+            IndexType::BTree => BTreePage::fits(&index_layout, block_size),
+        };
+        if !fits {
+            return Err(IndexManagerError::KeyTooLarge {
+                field_name: field_name.to_string(),
+                slot_size: index_layout.slot_size(),
+                block_size,
+            }
+            .into());
+        }
+
+        let mut index_catalog = TableScan::new(tx, INDEX_CATALOG, self.layout.clone())?;
+        while index_catalog.next()? {
+            if index_catalog.get_string("indexname")? == index_name {
+                index_catalog.close()?;
+                return Err(IndexManagerError::IndexExists(index_name.to_string()).into());
+            }
+            if index_catalog.get_string("tablename")? == table_name
+                && index_catalog.get_string("fieldname")? == field_name
+            {
+                index_catalog.close()?;
+                return Err(IndexManagerError::FieldIndexed(
+                    table_name.to_string(),
+                    field_name.to_string(),
+                )
+                .into());
+            }
+        }
+        index_catalog.before_first()?;
+        index_catalog.insert()?;
+        index_catalog.set_string("indexname", index_name)?;
+        index_catalog.set_string("tablename", table_name)?;
+        index_catalog.set_string("fieldname", field_name)?;
+        index_catalog.set_string("indextype", index_type.name())?;
+        index_catalog.close()
+    }
+
+    /// Return a map containing the index info for all indexes on the specified table, keyed by the indexed field.
+    pub fn get_index_info(
+        &self,
+        table_name: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<HashMap<String, IndexInfo>> {
+        let mut result = HashMap::new();
+        let mut index_catalog =
+            TableScan::new(Arc::clone(&tx), INDEX_CATALOG, self.layout.clone())?;
+        while index_catalog.next()? {
+            if index_catalog.get_string("tablename")? == table_name {
+                let index_name = index_catalog.get_string("indexname")?;
+                let field_name = index_catalog.get_string("fieldname")?;
+                let index_type = index_catalog.get_string("indextype")?;
+                let index_type = IndexType::from_name(&index_type)
+                    .ok_or(IndexManagerError::UnknownIndexType(index_type))?;
+                let table_layout = self.table_manager.get_layout(table_name, Arc::clone(&tx))?;
+                let stat_info = self.stat_manager.lock().unwrap().get_stat_info(
+                    table_name,
+                    &table_layout,
+                    Arc::clone(&tx),
+                )?;
+                let index_info = IndexInfo::new(
+                    &index_name,
+                    &field_name,
+                    index_type,
+                    table_layout.schema(),
+                    Arc::clone(&tx),
+                    stat_info,
+                );
+                result.insert(field_name, index_info);
+            }
+        }
+        index_catalog.close()?;
+        Ok(result)
+    }
+}
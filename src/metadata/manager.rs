@@ -0,0 +1,362 @@
+use anyhow::{Ok, Result};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    index::index::IndexType,
+    record::{layout::Layout, schema::Schema},
+    tx::transaction::Transaction,
+};
+
+use super::{
+    index_manager::{IndexInfo, IndexManager},
+    stat_manager::{StatInfo, StatManager},
+    table_manager::TableManager,
+    view_manager::ViewManager,
+};
+
+/// A single entry point for the table, view, index and statistics managers.
+/// The catalog tables are created when the metadata manager is built for a new database.
+#[derive(Debug)]
+pub struct MetadataManager {
+    table_manager: TableManager,
+    view_manager: ViewManager,
+    stat_manager: Arc<Mutex<StatManager>>,
+    index_manager: IndexManager,
+}
+
+impl MetadataManager {
+    pub fn new(is_new: bool, tx: Arc<Mutex<Transaction>>) -> Result<Self> {
+        let table_manager = TableManager::new(is_new, Arc::clone(&tx))?;
+        let view_manager = ViewManager::new(is_new, table_manager.clone(), Arc::clone(&tx))?;
+        let stat_manager = Arc::new(Mutex::new(StatManager::new(
+            table_manager.clone(),
+            Arc::clone(&tx),
+        )?));
+        let index_manager =
+            IndexManager::new(is_new, table_manager.clone(), Arc::clone(&stat_manager), tx)?;
+
+        Ok(Self {
+            table_manager,
+            view_manager,
+            stat_manager,
+            index_manager,
+        })
+    }
+
+    /// Create a table, whose name must not belong to an existing table or view.
+    pub fn create_table(
+        &self,
+        table_name: &str,
+        schema: &Schema,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        self.view_manager
+            .check_name_free(table_name, Arc::clone(&tx))?;
+        self.table_manager.create_table(table_name, schema, tx)
+    }
+
+    pub fn get_layout(&self, table_name: &str, tx: Arc<Mutex<Transaction>>) -> Result<Layout> {
+        self.table_manager.get_layout(table_name, tx)
+    }
+
+    pub fn table_names(&self, tx: Arc<Mutex<Transaction>>) -> Result<Vec<String>> {
+        self.table_manager.table_names(tx)
+    }
+
+    pub fn create_view(
+        &self,
+        view_name: &str,
+        view_def: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        self.view_manager.create_view(view_name, view_def, tx)
+    }
+
+    pub fn get_view_def(
+        &self,
+        view_name: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Option<String>> {
+        self.view_manager.get_view_def(view_name, tx)
+    }
+
+    pub fn create_index(
+        &self,
+        index_name: &str,
+        table_name: &str,
+        field_name: &str,
+        index_type: IndexType,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        self.index_manager
+            .create_index(index_name, table_name, field_name, index_type, tx)
+    }
+
+    pub fn get_index_info(
+        &self,
+        table_name: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<HashMap<String, IndexInfo>> {
+        self.index_manager.get_index_info(table_name, tx)
+    }
+
+    pub fn get_stat_info(
+        &self,
+        table_name: &str,
+        layout: &Layout,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<StatInfo> {
+        self.stat_manager
+            .lock()
+            .unwrap()
+            .get_stat_info(table_name, layout, tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+
+    use crate::{
+        index::index::IndexType,
+        query::scan::{Scan, UpdateScan},
+        record::{
+            schema::{FieldType, Schema},
+            table_scan::TableScan,
+        },
+        server::simpledb::SimpleDB,
+    };
+
+    use super::IndexInfo;
+
+    #[test]
+    fn test_metadata_manager() {
+        let temp_dir = tempdir().unwrap();
+        let db_dir = temp_dir.path().to_str().unwrap();
+        let db = SimpleDB::new(db_dir, 800, 8).unwrap();
+        assert!(db.file_manager().is_new());
+        let metadata_manager = db.metadata_manager();
+        let tx = db.new_tx().unwrap();
+
+        let mut schema = Schema::new();
+        schema.add_int_field("A");
+        schema.add_string_field("B", 9);
+
+        // Part 1: Table Metadata
+        metadata_manager
+            .create_table("MyTable", &schema, Arc::clone(&tx))
+            .unwrap();
+        let layout = metadata_manager
+            .get_layout("MyTable", Arc::clone(&tx))
+            .unwrap();
+        assert_eq!(layout.slot_size(), 48);
+        assert_eq!(layout.schema().fields(), ["A", "B"]);
+        assert_eq!(layout.schema().field_type("A"), Some(FieldType::Integer));
+        assert_eq!(layout.schema().field_type("B"), Some(FieldType::Varchar));
+        assert_eq!(layout.schema().length("B"), Some(9));
+        assert_eq!(layout.offset("A"), Some(4));
+        assert_eq!(layout.offset("B"), Some(8));
+        assert!(metadata_manager
+            .get_layout("NoTable", Arc::clone(&tx))
+            .is_err());
+
+        // Part 2: Statistics Metadata
+        let mut scan = TableScan::new(Arc::clone(&tx), "MyTable", layout.clone()).unwrap();
+        for i in 0..50 {
+            scan.insert().unwrap();
+            scan.set_int("A", i).unwrap();
+            scan.set_string("B", &format!("rec{}", i)).unwrap();
+        }
+        scan.close().unwrap();
+        let stat_info = metadata_manager
+            .get_stat_info("MyTable", &layout, Arc::clone(&tx))
+            .unwrap();
+        assert_eq!(stat_info.blocks_accessed(), 4);
+        assert_eq!(stat_info.records_output(), 50);
+        assert_eq!(stat_info.distinct_values("A"), 17);
+
+        // Part 3: View Metadata
+        let view_def = "select B from MyTable where A = 1";
+        metadata_manager
+            .create_view("viewA", view_def, Arc::clone(&tx))
+            .unwrap();
+        assert_eq!(
+            metadata_manager
+                .get_view_def("viewA", Arc::clone(&tx))
+                .unwrap()
+                .as_deref(),
+            Some(view_def)
+        );
+        assert!(metadata_manager
+            .get_view_def("viewB", Arc::clone(&tx))
+            .unwrap()
+            .is_none());
+
+        // Part 4: Index Metadata
+        metadata_manager
+            .create_index("indexA", "MyTable", "A", IndexType::Hash, Arc::clone(&tx))
+            .unwrap();
+        metadata_manager
+            .create_index("indexB", "MyTable", "B", IndexType::BTree, Arc::clone(&tx))
+            .unwrap();
+        let index_info = metadata_manager
+            .get_index_info("MyTable", Arc::clone(&tx))
+            .unwrap();
+        assert_eq!(index_info.len(), 2);
+        assert_eq!(index_info["A"].index_name(), "indexA");
+        assert_eq!(index_info["A"].records_output(), 2);
+        assert_eq!(index_info["A"].distinct_values("A"), 1);
+        assert_eq!(index_info["A"].blocks_accessed(), 0);
+        assert_eq!(index_info["B"].index_type(), IndexType::BTree);
+        assert_eq!(index_info["B"].blocks_accessed(), 1);
+        assert_eq!(index_info["B"].distinct_values("A"), 17);
+        assert_eq!(
+            index_info["B"]
+                .index_layout()
+                .schema()
+                .field_type("dataval"),
+            Some(FieldType::Varchar)
+        );
+        tx.lock().unwrap().commit().unwrap();
+
+        // reopening the existing database rediscovers every table
+        drop(db);
+        let db = SimpleDB::new(db_dir, 800, 8).unwrap();
+        assert!(!db.file_manager().is_new());
+        let metadata_manager = db.metadata_manager();
+        let tx = db.new_tx().unwrap();
+        assert_eq!(
+            metadata_manager
+                .get_layout("MyTable", Arc::clone(&tx))
+                .unwrap(),
+            layout
+        );
+        assert_eq!(
+            metadata_manager.table_names(Arc::clone(&tx)).unwrap(),
+            ["tblcat", "fldcat", "viewcat", "idxcat", "MyTable"]
+        );
+        tx.lock().unwrap().commit().unwrap();
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        let temp_dir = tempdir().unwrap();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+        let metadata_manager = db.metadata_manager();
+        let tx = db.new_tx().unwrap();
+
+        let mut schema = Schema::new();
+        schema.add_int_field("A");
+        schema.add_string_field("B", 9);
+        metadata_manager
+            .create_table("T", &schema, Arc::clone(&tx))
+            .unwrap();
+        let mut other_schema = Schema::new();
+        other_schema.add_int_field("C");
+        assert!(metadata_manager
+            .create_table("T", &other_schema, Arc::clone(&tx))
+            .is_err());
+        let layout = metadata_manager.get_layout("T", Arc::clone(&tx)).unwrap();
+        assert_eq!(layout.schema().fields(), ["A", "B"]);
+
+        // tables and views share their names
+        metadata_manager
+            .create_view("V", "select A from T", Arc::clone(&tx))
+            .unwrap();
+        assert!(metadata_manager
+            .create_view("V", "select B from T", Arc::clone(&tx))
+            .is_err());
+        assert!(metadata_manager
+            .create_view("T", "select B from T", Arc::clone(&tx))
+            .is_err());
+        assert!(metadata_manager
+            .create_table("V", &schema, Arc::clone(&tx))
+            .is_err());
+        assert_eq!(
+            metadata_manager
+                .get_view_def("V", Arc::clone(&tx))
+                .unwrap()
+                .as_deref(),
+            Some("select A from T")
+        );
+
+        metadata_manager
+            .create_index("I", "T", "A", IndexType::Hash, Arc::clone(&tx))
+            .unwrap();
+        assert!(metadata_manager
+            .create_index("I", "T", "B", IndexType::Hash, Arc::clone(&tx))
+            .is_err());
+        assert!(metadata_manager
+            .create_index("J", "T", "A", IndexType::BTree, Arc::clone(&tx))
+            .is_err());
+        let index_info = metadata_manager
+            .get_index_info("T", Arc::clone(&tx))
+            .unwrap();
+        assert_eq!(index_info.len(), 1);
+        assert_eq!(index_info["A"].index_type(), IndexType::Hash);
+
+        tx.lock().unwrap().commit().unwrap();
+    }
+
+    #[test]
+    fn test_wide_index_key() {
+        let temp_dir = tempdir().unwrap();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+        let metadata_manager = db.metadata_manager();
+        let tx = db.new_tx().unwrap();
+
+        // the table's records fit in a block, but an index record adds the record id to the field
+        let mut schema = Schema::new();
+        schema.add_string_field("Wide", 197);
+        metadata_manager
+            .create_table("W", &schema, Arc::clone(&tx))
+            .unwrap();
+        let mut schema = Schema::new();
+        schema.add_string_field("Medium", 70);
+        schema.add_string_field("Narrow", 40);
+        metadata_manager
+            .create_table("T", &schema, Arc::clone(&tx))
+            .unwrap();
+
+        // an index record of the wide field does not fit in a block
+        for index_type in [IndexType::Hash, IndexType::BTree] {
+            assert!(metadata_manager
+                .create_index("wide", "W", "Wide", index_type, Arc::clone(&tx))
+                .is_err());
+        }
+        // a B-tree page must also hold enough records to be split
+        assert!(metadata_manager
+            .create_index("medium", "T", "Medium", IndexType::BTree, Arc::clone(&tx))
+            .is_err());
+        metadata_manager
+            .create_index("medium", "T", "Medium", IndexType::Hash, Arc::clone(&tx))
+            .unwrap();
+        metadata_manager
+            .create_index("narrow", "T", "Narrow", IndexType::BTree, Arc::clone(&tx))
+            .unwrap();
+
+        // planning with an index whose records do not fit in a block does not divide by zero
+        let layout = metadata_manager.get_layout("W", Arc::clone(&tx)).unwrap();
+        let stat_info = metadata_manager
+            .get_stat_info("W", &layout, Arc::clone(&tx))
+            .unwrap();
+        for index_type in [IndexType::Hash, IndexType::BTree] {
+            let index_info = IndexInfo::new(
+                "wide",
+                "Wide",
+                index_type,
+                layout.schema(),
+                Arc::clone(&tx),
+                stat_info,
+            );
+            index_info.blocks_accessed();
+        }
+
+        tx.lock().unwrap().commit().unwrap();
+    }
+}
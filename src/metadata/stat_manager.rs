@@ -0,0 +1,119 @@
+use anyhow::{Ok, Result};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    query::scan::{Scan, UpdateScan},
+    record::{layout::Layout, table_scan::TableScan},
+    tx::transaction::Transaction,
+};
+
+use super::table_manager::TableManager;
+
+/// The number of calls to `get_stat_info` after which all the statistics are recalculated.
+const REFRESH_THRESHOLD: usize = 100;
+
+/// Holds three pieces of statistical information about a table:
+/// the number of blocks, the number of records, and the number of distinct values for each field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatInfo {
+    num_blocks: usize,
+    num_records: usize,
+}
+
+impl StatInfo {
+    pub fn new(num_blocks: usize, num_records: usize) -> Self {
+        Self {
+            num_blocks,
+            num_records,
+        }
+    }
+
+    pub fn blocks_accessed(&self) -> usize {
+        self.num_blocks
+    }
+
+    pub fn records_output(&self) -> usize {
+        self.num_records
+    }
+
+    /// Return the estimated number of distinct values for the specified field.
+    /// This estimate is a complete guess, because doing something reasonable is beyond the scope of this system.
+    pub fn distinct_values(&self, _field_name: &str) -> usize {
+        1 + (self.num_records / 3)
+    }
+}
+
+/// The statistics manager is responsible for keeping statistical information about each table.
+/// The manager does not store this information in the database.
+/// Instead, it calculates this information on system startup, and periodically refreshes it.
+#[derive(Debug)]
+pub struct StatManager {
+    table_manager: TableManager,
+    table_stats: HashMap<String, StatInfo>,
+    num_calls: usize,
+}
+
+impl StatManager {
+    pub fn new(table_manager: TableManager, tx: Arc<Mutex<Transaction>>) -> Result<Self> {
+        let mut stat_manager = Self {
+            table_manager,
+            table_stats: HashMap::new(),
+            num_calls: 0,
+        };
+        stat_manager.refresh_statistics(tx)?;
+        Ok(stat_manager)
+    }
+
+    /// Return the statistical information about the specified table.
+    pub fn get_stat_info(
+        &mut self,
+        table_name: &str,
+        layout: &Layout,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<StatInfo> {
+        self.num_calls += 1;
+        if self.num_calls > REFRESH_THRESHOLD {
+            self.refresh_statistics(Arc::clone(&tx))?;
+        }
+
+        if let Some(stat_info) = self.table_stats.get(table_name) {
+            return Ok(*stat_info);
+        }
+
+        let stat_info = Self::calc_table_stats(table_name, layout, tx)?;
+        self.table_stats.insert(table_name.to_string(), stat_info);
+        Ok(stat_info)
+    }
+
+    fn refresh_statistics(&mut self, tx: Arc<Mutex<Transaction>>) -> Result<()> {
+        self.table_stats.clear();
+        self.num_calls = 0;
+        for table_name in self.table_manager.table_names(Arc::clone(&tx))? {
+            let layout = self
+                .table_manager
+                .get_layout(&table_name, Arc::clone(&tx))?;
+            let stat_info = Self::calc_table_stats(&table_name, &layout, Arc::clone(&tx))?;
+            self.table_stats.insert(table_name, stat_info);
+        }
+        Ok(())
+    }
+
+    fn calc_table_stats(
+        table_name: &str,
+        layout: &Layout,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<StatInfo> {
+        let mut num_records = 0;
+        let mut num_blocks = 0;
+        let mut scan = TableScan::new(tx, table_name, layout.clone())?;
+        while scan.next()? {
+            num_records += 1;
+            num_blocks = scan.get_rid()?.block_number() + 1;
+        }
+        scan.close()?;
+        Ok(StatInfo::new(num_blocks, num_records))
+    }
+}
@@ -0,0 +1,210 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    query::scan::{Scan, UpdateScan},
+    record::{
+        layout::Layout,
+        schema::{FieldType, Schema},
+        table_scan::TableScan,
+        temp_table::is_temp_table_file,
+    },
+    tx::transaction::Transaction,
+};
+
+/// The max characters a table or field name can have.
+pub const MAX_NAME: usize = 16;
+
+pub const TABLE_CATALOG: &str = "tblcat";
+pub const FIELD_CATALOG: &str = "fldcat";
+
+#[derive(Debug)]
+enum TableManagerError {
+    TableNotFound(String),
+    ReservedTableName(String),
+    TableExists(String),
+}
+
+impl std::error::Error for TableManagerError {}
+impl fmt::Display for TableManagerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TableManagerError::TableNotFound(table_name) => {
+                write!(f, "table not found: {}", table_name)
+            }
+            TableManagerError::TableExists(table_name) => {
+                write!(f, "table already exists: {}", table_name)
+            }
+            TableManagerError::ReservedTableName(table_name) => {
+                write!(
+                    f,
+                    "table name is reserved for temporary tables: {}",
+                    table_name
+                )
+            }
+        }
+    }
+}
+
+/// The table manager has methods to create a table, save the metadata in the catalog, and obtain the metadata of a previously-created table.
+/// The catalog consists of two tables:
+/// - `tblcat` stores the name and slot size of each table;
+/// - `fldcat` stores the name, type, length and offset of each field of each table.
+#[derive(Debug, Clone)]
+pub struct TableManager {
+    table_catalog_layout: Layout,
+    field_catalog_layout: Layout,
+}
+
+impl TableManager {
+    /// Create a new catalog manager for the database system.
+    /// If the database is new, the two catalog tables are created.
+    pub fn new(is_new: bool, tx: Arc<Mutex<Transaction>>) -> Result<Self> {
+        let mut table_catalog_schema = Schema::new();
+        table_catalog_schema.add_string_field("tblname", MAX_NAME);
+        table_catalog_schema.add_int_field("slotsize");
+        let table_catalog_layout = Layout::new(table_catalog_schema);
+
+        let mut field_catalog_schema = Schema::new();
+        field_catalog_schema.add_string_field("tblname", MAX_NAME);
+        field_catalog_schema.add_string_field("fldname", MAX_NAME);
+        field_catalog_schema.add_int_field("type");
+        field_catalog_schema.add_int_field("length");
+        field_catalog_schema.add_int_field("offset");
+        let field_catalog_layout = Layout::new(field_catalog_schema);
+
+        let table_manager = Self {
+            table_catalog_layout,
+            field_catalog_layout,
+        };
+
+        if is_new {
+            table_manager.create_table(
+                TABLE_CATALOG,
+                table_manager.table_catalog_layout.schema(),
+                Arc::clone(&tx),
+            )?;
+            table_manager.create_table(
+                FIELD_CATALOG,
+                table_manager.field_catalog_layout.schema(),
+                Arc::clone(&tx),
+            )?;
+        }
+
+        Ok(table_manager)
+    }
+
+    /// Create a new table having the specified name and schema.
+    /// The table's layout is inserted as one record into `tblcat` and one record per field into `fldcat`.
+    /// Names of the form `tempN` are reserved, because the files of temporary tables are removed on startup,
+    /// and a table that already exists cannot be created again.
+    pub fn create_table(
+        &self,
+        table_name: &str,
+        schema: &Schema,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        if is_temp_table_file(&format!("{}.tbl", table_name)) {
+            return Err(TableManagerError::ReservedTableName(table_name.to_string()).into());
+        }
+        if self.has_table(table_name, Arc::clone(&tx))? {
+            return Err(TableManagerError::TableExists(table_name.to_string()).into());
+        }
+        let layout = Layout::new(schema.clone());
+
+        let mut table_catalog = TableScan::new(
+            Arc::clone(&tx),
+            TABLE_CATALOG,
+            self.table_catalog_layout.clone(),
+        )?;
+        table_catalog.insert()?;
+        table_catalog.set_string("tblname", table_name)?;
+        table_catalog.set_int("slotsize", layout.slot_size() as i32)?;
+        table_catalog.close()?;
+
+        let mut field_catalog = TableScan::new(
+            Arc::clone(&tx),
+            FIELD_CATALOG,
+            self.field_catalog_layout.clone(),
+        )?;
+        for field_name in schema.fields() {
+            field_catalog.insert()?;
+            field_catalog.set_string("tblname", table_name)?;
+            field_catalog.set_string("fldname", field_name)?;
+            field_catalog.set_int(
+                "type",
+                schema.field_type(field_name).unwrap_or(FieldType::Integer) as i32,
+            )?;
+            field_catalog.set_int("length", schema.length(field_name).unwrap_or(0) as i32)?;
+            field_catalog.set_int("offset", layout.offset(field_name).unwrap_or(0) as i32)?;
+        }
+        field_catalog.close()?;
+
+        Ok(())
+    }
+
+    /// Retrieve the layout of the specified table from the catalog.
+    pub fn get_layout(&self, table_name: &str, tx: Arc<Mutex<Transaction>>) -> Result<Layout> {
+        let mut slot_size = None;
+        let mut table_catalog = TableScan::new(
+            Arc::clone(&tx),
+            TABLE_CATALOG,
+            self.table_catalog_layout.clone(),
+        )?;
+        while table_catalog.next()? {
+            if table_catalog.get_string("tblname")? == table_name {
+                slot_size = Some(table_catalog.get_int("slotsize")? as usize);
+                break;
+            }
+        }
+        table_catalog.close()?;
+
+        let slot_size =
+            slot_size.ok_or_else(|| TableManagerError::TableNotFound(table_name.to_string()))?;
+
+        let mut schema = Schema::new();
+        let mut offsets = HashMap::new();
+        let mut field_catalog = TableScan::new(
+            Arc::clone(&tx),
+            FIELD_CATALOG,
+            self.field_catalog_layout.clone(),
+        )?;
+        while field_catalog.next()? {
+            if field_catalog.get_string("tblname")? == table_name {
+                let field_name = field_catalog.get_string("fldname")?;
+                let field_type = FieldType::try_from(field_catalog.get_int("type")?)?;
+                let length = field_catalog.get_int("length")? as usize;
+                let offset = field_catalog.get_int("offset")? as usize;
+                offsets.insert(field_name.clone(), offset);
+                schema.add_field(&field_name, field_type, length);
+            }
+        }
+        field_catalog.close()?;
+
+        Ok(Layout::from_metadata(schema, offsets, slot_size))
+    }
+
+    /// Return whether the catalog has a table with the specified name.
+    pub fn has_table(&self, table_name: &str, tx: Arc<Mutex<Transaction>>) -> Result<bool> {
+        Ok(self.table_names(tx)?.iter().any(|name| name == table_name))
+    }
+
+    /// Return the names of all the tables in the catalog, including the catalog tables themselves.
+    pub fn table_names(&self, tx: Arc<Mutex<Transaction>>) -> Result<Vec<String>> {
+        let mut table_names = vec![];
+        let mut table_catalog = TableScan::new(
+            Arc::clone(&tx),
+            TABLE_CATALOG,
+            self.table_catalog_layout.clone(),
+        )?;
+        while table_catalog.next()? {
+            table_names.push(table_catalog.get_string("tblname")?);
+        }
+        table_catalog.close()?;
+        Ok(table_names)
+    }
+}
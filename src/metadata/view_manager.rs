@@ -0,0 +1,105 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    query::scan::{Scan, UpdateScan},
+    record::{layout::Layout, schema::Schema, table_scan::TableScan},
+    tx::transaction::Transaction,
+};
+
+use super::table_manager::{TableManager, MAX_NAME};
+
+/// The max characters a view definition can have.
+pub const MAX_VIEWDEF: usize = 100;
+
+pub const VIEW_CATALOG: &str = "viewcat";
+
+#[derive(Debug)]
+enum ViewManagerError {
+    NameInUse(String),
+}
+
+impl std::error::Error for ViewManagerError {}
+impl fmt::Display for ViewManagerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ViewManagerError::NameInUse(name) => {
+                write!(f, "a table or view already exists: {}", name)
+            }
+        }
+    }
+}
+
+/// The view manager stores view definitions in the catalog table `viewcat`, one record per view.
+#[derive(Debug, Clone)]
+pub struct ViewManager {
+    table_manager: TableManager,
+}
+
+impl ViewManager {
+    pub fn new(
+        is_new: bool,
+        table_manager: TableManager,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Self> {
+        if is_new {
+            let mut schema = Schema::new();
+            schema.add_string_field("viewname", MAX_NAME);
+            schema.add_string_field("viewdef", MAX_VIEWDEF);
+            table_manager.create_table(VIEW_CATALOG, &schema, tx)?;
+        }
+
+        Ok(Self { table_manager })
+    }
+
+    /// Store the definition of a new view.
+    /// A query cannot tell a view from a table, so the name must not belong to an existing view or table.
+    pub fn create_view(
+        &self,
+        view_name: &str,
+        view_def: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<()> {
+        self.check_name_free(view_name, Arc::clone(&tx))?;
+        let layout = self.view_catalog_layout(Arc::clone(&tx))?;
+        let mut view_catalog = TableScan::new(tx, VIEW_CATALOG, layout)?;
+        view_catalog.insert()?;
+        view_catalog.set_string("viewname", view_name)?;
+        view_catalog.set_string("viewdef", view_def)?;
+        view_catalog.close()
+    }
+
+    /// Return the definition of the specified view, or None if there is no such view.
+    pub fn get_view_def(
+        &self,
+        view_name: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Option<String>> {
+        let mut result = None;
+        let layout = self.view_catalog_layout(Arc::clone(&tx))?;
+        let mut view_catalog = TableScan::new(tx, VIEW_CATALOG, layout)?;
+        while view_catalog.next()? {
+            if view_catalog.get_string("viewname")? == view_name {
+                result = Some(view_catalog.get_string("viewdef")?);
+                break;
+            }
+        }
+        view_catalog.close()?;
+        Ok(result)
+    }
+
+    /// Return an error if a view or a table has the specified name.
+    pub fn check_name_free(&self, name: &str, tx: Arc<Mutex<Transaction>>) -> Result<()> {
+        if self.get_view_def(name, Arc::clone(&tx))?.is_some()
+            || self.table_manager.has_table(name, tx)?
+        {
+            return Err(ViewManagerError::NameInUse(name.to_string()).into());
+        }
+        Ok(())
+    }
+
+    fn view_catalog_layout(&self, tx: Arc<Mutex<Transaction>>) -> Result<Layout> {
+        self.table_manager.get_layout(VIEW_CATALOG, tx)
+    }
+}
@@ -0,0 +1,42 @@
+use crate::index::index::IndexType;
+
+/// Data for the SQL create index statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateIndexData {
+    index_name: String,
+    table_name: String,
+    field_name: String,
+    index_type: IndexType,
+}
+
+impl CreateIndexData {
+    pub fn new(
+        index_name: &str,
+        table_name: &str,
+        field_name: &str,
+        index_type: IndexType,
+    ) -> Self {
+        Self {
+            index_name: index_name.to_string(),
+            table_name: table_name.to_string(),
+            field_name: field_name.to_string(),
+            index_type,
+        }
+    }
+
+    pub fn index_name(&self) -> &str {
+        &self.index_name
+    }
+
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    pub fn field_name(&self) -> &str {
+        &self.field_name
+    }
+
+    pub fn index_type(&self) -> IndexType {
+        self.index_type
+    }
+}
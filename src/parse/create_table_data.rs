@@ -0,0 +1,25 @@
+use crate::record::schema::Schema;
+
+/// Data for the SQL create table statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateTableData {
+    table_name: String,
+    schema: Schema,
+}
+
+impl CreateTableData {
+    pub fn new(table_name: &str, schema: Schema) -> Self {
+        Self {
+            table_name: table_name.to_string(),
+            schema,
+        }
+    }
+
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    pub fn new_schema(&self) -> &Schema {
+        &self.schema
+    }
+}
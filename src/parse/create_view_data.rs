@@ -0,0 +1,26 @@
+use super::query_data::QueryData;
+
+/// Data for the SQL create view statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateViewData {
+    view_name: String,
+    query_data: QueryData,
+}
+
+impl CreateViewData {
+    pub fn new(view_name: &str, query_data: QueryData) -> Self {
+        Self {
+            view_name: view_name.to_string(),
+            query_data,
+        }
+    }
+
+    pub fn view_name(&self) -> &str {
+        &self.view_name
+    }
+
+    /// Return the SQL text of the view's query, as stored in the catalog.
+    pub fn view_def(&self) -> String {
+        self.query_data.to_string()
+    }
+}
@@ -0,0 +1,25 @@
+use crate::query::predicate::Predicate;
+
+/// Data for the SQL delete statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeleteData {
+    table_name: String,
+    pred: Predicate,
+}
+
+impl DeleteData {
+    pub fn new(table_name: &str, pred: Predicate) -> Self {
+        Self {
+            table_name: table_name.to_string(),
+            pred,
+        }
+    }
+
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    pub fn pred(&self) -> &Predicate {
+        &self.pred
+    }
+}
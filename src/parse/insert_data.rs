@@ -0,0 +1,31 @@
+use crate::query::constant::Constant;
+
+/// Data for the SQL insert statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InsertData {
+    table_name: String,
+    fields: Vec<String>,
+    vals: Vec<Constant>,
+}
+
+impl InsertData {
+    pub fn new(table_name: &str, fields: Vec<String>, vals: Vec<Constant>) -> Self {
+        Self {
+            table_name: table_name.to_string(),
+            fields,
+            vals,
+        }
+    }
+
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    pub fn vals(&self) -> &[Constant] {
+        &self.vals
+    }
+}
@@ -0,0 +1,277 @@
+use anyhow::Result;
+use core::fmt;
+
+const KEYWORDS: &[&str] = &[
+    "select", "from", "where", "and", "insert", "into", "values", "delete", "update", "set",
+    "create", "table", "int", "varchar", "view", "as", "index", "on", "using", "between", "group",
+    "order", "by",
+];
+
+/// A syntax error, reporting the byte position in the statement at which the offending token starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadSyntaxError {
+    position: usize,
+    token: String,
+}
+
+impl std::error::Error for BadSyntaxError {}
+impl fmt::Display for BadSyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "bad syntax at position {}: unexpected {}",
+            self.position, self.token
+        )
+    }
+}
+
+impl BadSyntaxError {
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Delim(char),
+    IntConstant(i32),
+    StringConstant(String),
+    Keyword(String),
+    Id(String),
+    Eof,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Delim(c) => write!(f, "'{}'", c),
+            Token::IntConstant(n) => write!(f, "{}", n),
+            Token::StringConstant(s) => write!(f, "'{}'", s),
+            Token::Keyword(w) => write!(f, "keyword {}", w),
+            Token::Id(id) => write!(f, "identifier {}", id),
+            Token::Eof => write!(f, "end of statement"),
+        }
+    }
+}
+
+/// The lexical analyzer.
+/// Keywords and identifiers are case-insensitive and are converted to lower case;
+/// string constants are enclosed in single quotes and keep their case.
+#[derive(Debug)]
+pub struct Lexer {
+    tokens: Vec<(Token, usize)>,
+    current: usize,
+}
+
+impl Lexer {
+    pub fn new(s: &str) -> Result<Self> {
+        Ok(Self {
+            tokens: Self::tokenize(s)?,
+            current: 0,
+        })
+    }
+
+    /// Return true if the current token is the specified delimiter character.
+    pub fn match_delim(&self, d: char) -> bool {
+        self.token() == &Token::Delim(d)
+    }
+
+    pub fn match_int_constant(&self) -> bool {
+        matches!(self.token(), Token::IntConstant(_))
+    }
+
+    pub fn match_string_constant(&self) -> bool {
+        matches!(self.token(), Token::StringConstant(_))
+    }
+
+    pub fn match_keyword(&self, w: &str) -> bool {
+        matches!(self.token(), Token::Keyword(k) if k == w)
+    }
+
+    pub fn match_id(&self) -> bool {
+        matches!(self.token(), Token::Id(_))
+    }
+
+    /// Return true if all the tokens have been consumed.
+    pub fn match_eof(&self) -> bool {
+        self.token() == &Token::Eof
+    }
+
+    /// Throw an error if the current token is not the specified delimiter. Otherwise, move to the next token.
+    pub fn eat_delim(&mut self, d: char) -> Result<()> {
+        if !self.match_delim(d) {
+            return Err(self.bad_syntax().into());
+        }
+        self.next_token();
+        Ok(())
+    }
+
+    pub fn eat_int_constant(&mut self) -> Result<i32> {
+        match self.token() {
+            Token::IntConstant(n) => {
+                let n = *n;
+                self.next_token();
+                Ok(n)
+            }
+            _ => Err(self.bad_syntax().into()),
+        }
+    }
+
+    pub fn eat_string_constant(&mut self) -> Result<String> {
+        match self.token() {
+            Token::StringConstant(s) => {
+                let s = s.clone();
+                self.next_token();
+                Ok(s)
+            }
+            _ => Err(self.bad_syntax().into()),
+        }
+    }
+
+    pub fn eat_keyword(&mut self, w: &str) -> Result<()> {
+        if !self.match_keyword(w) {
+            return Err(self.bad_syntax().into());
+        }
+        self.next_token();
+        Ok(())
+    }
+
+    pub fn eat_id(&mut self) -> Result<String> {
+        match self.token() {
+            Token::Id(id) => {
+                let id = id.clone();
+                self.next_token();
+                Ok(id)
+            }
+            _ => Err(self.bad_syntax().into()),
+        }
+    }
+
+    /// Return a syntax error pointing at the current token.
+    pub fn bad_syntax(&self) -> BadSyntaxError {
+        let (token, position) = &self.tokens[self.current];
+        BadSyntaxError {
+            position: *position,
+            token: token.to_string(),
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.tokens[self.current].0
+    }
+
+    fn next_token(&mut self) {
+        if self.current < self.tokens.len() - 1 {
+            self.current += 1;
+        }
+    }
+
+    fn tokenize(s: &str) -> Result<Vec<(Token, usize)>, BadSyntaxError> {
+        let mut tokens = vec![];
+        let mut chars = s.char_indices().peekable();
+
+        while let Some(&(pos, c)) = chars.peek() {
+            if c.is_whitespace() {
+                chars.next();
+            } else if c.is_ascii_digit()
+                || (c == '-' && s[pos + 1..].starts_with(|c: char| c.is_ascii_digit()))
+            {
+                chars.next();
+                let mut end = pos + c.len_utf8();
+                while let Some(&(i, c)) = chars.peek() {
+                    if !c.is_ascii_digit() {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                let n = s[pos..end].parse().map_err(|_| BadSyntaxError {
+                    position: pos,
+                    token: s[pos..end].to_string(),
+                })?;
+                tokens.push((Token::IntConstant(n), pos));
+            } else if c == '\'' {
+                chars.next();
+                let mut value = String::new();
+                let mut terminated = false;
+                for (_, c) in chars.by_ref() {
+                    if c == '\'' {
+                        terminated = true;
+                        break;
+                    }
+                    value.push(c);
+                }
+                if !terminated {
+                    return Err(BadSyntaxError {
+                        position: pos,
+                        token: "unterminated string".to_string(),
+                    });
+                }
+                tokens.push((Token::StringConstant(value), pos));
+            } else if c.is_alphabetic() || c == '_' {
+                let mut word = String::new();
+                while let Some(&(_, c)) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    word.extend(c.to_lowercase());
+                    chars.next();
+                }
+                if KEYWORDS.contains(&word.as_str()) {
+                    tokens.push((Token::Keyword(word), pos));
+                } else {
+                    tokens.push((Token::Id(word), pos));
+                }
+            } else {
+                chars.next();
+                tokens.push((Token::Delim(c), pos));
+            }
+        }
+
+        tokens.push((Token::Eof, s.len()));
+        Ok(tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BadSyntaxError, Lexer};
+
+    #[test]
+    fn test_lexer() {
+        let mut lexer = Lexer::new("SELECT a, Name FROM t WHERE a = -12 and b='Hi there'").unwrap();
+        lexer.eat_keyword("select").unwrap();
+        assert_eq!(lexer.eat_id().unwrap(), "a");
+        lexer.eat_delim(',').unwrap();
+        assert_eq!(lexer.eat_id().unwrap(), "name");
+        lexer.eat_keyword("from").unwrap();
+        assert!(lexer.match_id());
+        assert_eq!(lexer.eat_id().unwrap(), "t");
+        lexer.eat_keyword("where").unwrap();
+        lexer.eat_id().unwrap();
+        lexer.eat_delim('=').unwrap();
+        assert_eq!(lexer.eat_int_constant().unwrap(), -12);
+        lexer.eat_keyword("and").unwrap();
+        lexer.eat_id().unwrap();
+        lexer.eat_delim('=').unwrap();
+        assert_eq!(lexer.eat_string_constant().unwrap(), "Hi there");
+        assert!(lexer.match_eof());
+    }
+
+    #[test]
+    fn test_lexer_errors() {
+        let mut lexer = Lexer::new("select from").unwrap();
+        lexer.eat_keyword("select").unwrap();
+        let err = lexer.eat_id().unwrap_err();
+        let err = err.downcast_ref::<BadSyntaxError>().unwrap();
+        assert_eq!(err.position(), 7);
+        assert_eq!(err.token(), "keyword from");
+
+        let err = Lexer::new("select 'abc").unwrap_err();
+        assert_eq!(err.downcast_ref::<BadSyntaxError>().unwrap().position(), 7);
+    }
+}
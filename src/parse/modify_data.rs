@@ -0,0 +1,39 @@
+use crate::query::{expression::Expression, predicate::Predicate};
+
+/// Data for the SQL update statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModifyData {
+    table_name: String,
+    field_name: String,
+    new_val: Expression,
+    pred: Predicate,
+}
+
+impl ModifyData {
+    pub fn new(table_name: &str, field_name: &str, new_val: Expression, pred: Predicate) -> Self {
+        Self {
+            table_name: table_name.to_string(),
+            field_name: field_name.to_string(),
+            new_val,
+            pred,
+        }
+    }
+
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    /// Return the field whose values will be modified.
+    pub fn target_field(&self) -> &str {
+        &self.field_name
+    }
+
+    /// Return an expression. Evaluating this expression for a record produces the value that will be stored in the record's target field.
+    pub fn new_val(&self) -> &Expression {
+        &self.new_val
+    }
+
+    pub fn pred(&self) -> &Predicate {
+        &self.pred
+    }
+}
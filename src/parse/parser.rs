@@ -0,0 +1,588 @@
+use anyhow::{Ok, Result};
+
+use crate::{
+    index::index::IndexType,
+    query::{
+        aggregation_fn::{Aggregation, AggregationKind},
+        constant::Constant,
+        expression::Expression,
+        predicate::Predicate,
+        term::{Operator, Term},
+    },
+    record::schema::Schema,
+};
+
+use super::{
+    create_index_data::CreateIndexData, create_table_data::CreateTableData,
+    create_view_data::CreateViewData, delete_data::DeleteData, insert_data::InsertData,
+    lexer::Lexer, modify_data::ModifyData, query_data::QueryData,
+};
+
+/// The parsed form of a SQL statement that modifies the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateCommand {
+    Insert(InsertData),
+    Delete(DeleteData),
+    Modify(ModifyData),
+    CreateTable(CreateTableData),
+    CreateView(CreateViewData),
+    CreateIndex(CreateIndexData),
+}
+
+/// The recursive-descent parser for the SimpleDB SQL subset.
+/// Each statement may be terminated by an optional semicolon.
+#[derive(Debug)]
+pub struct Parser {
+    lexer: Lexer,
+}
+
+impl Parser {
+    pub fn new(s: &str) -> Result<Self> {
+        Ok(Self {
+            lexer: Lexer::new(s)?,
+        })
+    }
+
+    /// `<Field> := IdTok`
+    pub fn field(&mut self) -> Result<String> {
+        self.lexer.eat_id()
+    }
+
+    /// `<Constant> := StrTok | IntTok`
+    pub fn constant(&mut self) -> Result<Constant> {
+        if self.lexer.match_string_constant() {
+            Ok(Constant::String(self.lexer.eat_string_constant()?))
+        } else {
+            Ok(Constant::Int(self.lexer.eat_int_constant()?))
+        }
+    }
+
+    /// `<Expression> := <Field> | <Constant>`
+    pub fn expression(&mut self) -> Result<Expression> {
+        if self.lexer.match_id() {
+            Ok(Expression::FieldName(self.field()?))
+        } else {
+            Ok(Expression::Constant(self.constant()?))
+        }
+    }
+
+    /// `<Term> := <Expression> <Operator> <Expression>`
+    pub fn term(&mut self) -> Result<Term> {
+        let lhs = self.expression()?;
+        self.comparison(lhs)
+    }
+
+    /// `<Predicate> := <Condition> [ AND <Predicate> ]`
+    ///
+    /// `<Condition> := <Term> | <Expression> BETWEEN <Expression> AND <Expression>`
+    pub fn predicate(&mut self) -> Result<Predicate> {
+        let lhs = self.expression()?;
+        let mut pred = if self.lexer.match_keyword("between") {
+            // a BETWEEN b AND c is the same as a >= b AND a <= c
+            self.lexer.eat_keyword("between")?;
+            let lo = self.expression()?;
+            self.lexer.eat_keyword("and")?;
+            let hi = self.expression()?;
+            let mut pred = Predicate::from_term(Term::with_operator(lhs.clone(), Operator::Ge, lo));
+            pred.conjoin_with(Predicate::from_term(Term::with_operator(
+                lhs,
+                Operator::Le,
+                hi,
+            )));
+            pred
+        } else {
+            Predicate::from_term(self.comparison(lhs)?)
+        };
+        if self.lexer.match_keyword("and") {
+            self.lexer.eat_keyword("and")?;
+            pred.conjoin_with(self.predicate()?);
+        }
+        Ok(pred)
+    }
+
+    /// `<Query> := SELECT <SelectList> FROM <TableList> [ WHERE <Predicate> ] [ GROUP BY <FieldList> ]
+    /// [ ORDER BY <FieldList> ]`
+    pub fn query(&mut self) -> Result<QueryData> {
+        let query_data = self.query_body()?;
+        self.end_of_statement()?;
+        Ok(query_data)
+    }
+
+    /// `<UpdateCmd> := <Insert> | <Delete> | <Modify> | <Create>`
+    pub fn update_cmd(&mut self) -> Result<UpdateCommand> {
+        let cmd = if self.lexer.match_keyword("insert") {
+            UpdateCommand::Insert(self.insert()?)
+        } else if self.lexer.match_keyword("delete") {
+            UpdateCommand::Delete(self.delete()?)
+        } else if self.lexer.match_keyword("update") {
+            UpdateCommand::Modify(self.modify()?)
+        } else {
+            self.create()?
+        };
+        self.end_of_statement()?;
+        Ok(cmd)
+    }
+
+    /// `<Insert> := INSERT INTO IdTok ( <FieldList> ) VALUES ( <ConstList> )`
+    pub fn insert(&mut self) -> Result<InsertData> {
+        self.lexer.eat_keyword("insert")?;
+        self.lexer.eat_keyword("into")?;
+        let table_name = self.lexer.eat_id()?;
+        self.lexer.eat_delim('(')?;
+        let fields = self.field_list()?;
+        self.lexer.eat_delim(')')?;
+        self.lexer.eat_keyword("values")?;
+        self.lexer.eat_delim('(')?;
+        let vals = self.const_list()?;
+        self.lexer.eat_delim(')')?;
+        Ok(InsertData::new(&table_name, fields, vals))
+    }
+
+    /// `<Delete> := DELETE FROM IdTok [ WHERE <Predicate> ]`
+    pub fn delete(&mut self) -> Result<DeleteData> {
+        self.lexer.eat_keyword("delete")?;
+        self.lexer.eat_keyword("from")?;
+        let table_name = self.lexer.eat_id()?;
+        let pred = self.where_clause()?;
+        Ok(DeleteData::new(&table_name, pred))
+    }
+
+    /// `<Modify> := UPDATE IdTok SET <Field> = <Expression> [ WHERE <Predicate> ]`
+    pub fn modify(&mut self) -> Result<ModifyData> {
+        self.lexer.eat_keyword("update")?;
+        let table_name = self.lexer.eat_id()?;
+        self.lexer.eat_keyword("set")?;
+        let field_name = self.field()?;
+        self.lexer.eat_delim('=')?;
+        let new_val = self.expression()?;
+        let pred = self.where_clause()?;
+        Ok(ModifyData::new(&table_name, &field_name, new_val, pred))
+    }
+
+    /// `<CreateTable> := CREATE TABLE IdTok ( <FieldDefs> )`
+    pub fn create_table(&mut self) -> Result<CreateTableData> {
+        self.lexer.eat_keyword("table")?;
+        let table_name = self.lexer.eat_id()?;
+        self.lexer.eat_delim('(')?;
+        let schema = self.field_defs()?;
+        self.lexer.eat_delim(')')?;
+        Ok(CreateTableData::new(&table_name, schema))
+    }
+
+    /// `<CreateView> := CREATE VIEW IdTok AS <Query>`
+    pub fn create_view(&mut self) -> Result<CreateViewData> {
+        self.lexer.eat_keyword("view")?;
+        let view_name = self.lexer.eat_id()?;
+        self.lexer.eat_keyword("as")?;
+        let query_data = self.query_body()?;
+        Ok(CreateViewData::new(&view_name, query_data))
+    }
+
+    /// `<CreateIndex> := CREATE INDEX IdTok ON IdTok ( <Field> ) [ USING IdTok ]`
+    pub fn create_index(&mut self) -> Result<CreateIndexData> {
+        self.lexer.eat_keyword("index")?;
+        let index_name = self.lexer.eat_id()?;
+        self.lexer.eat_keyword("on")?;
+        let table_name = self.lexer.eat_id()?;
+        self.lexer.eat_delim('(')?;
+        let field_name = self.field()?;
+        self.lexer.eat_delim(')')?;
+        let mut index_type = IndexType::default();
+        if self.lexer.match_keyword("using") {
+            self.lexer.eat_keyword("using")?;
+            let err = self.lexer.bad_syntax();
+            index_type = IndexType::from_name(&self.lexer.eat_id()?).ok_or(err)?;
+        }
+        Ok(CreateIndexData::new(
+            &index_name,
+            &table_name,
+            &field_name,
+            index_type,
+        ))
+    }
+
+    fn query_body(&mut self) -> Result<QueryData> {
+        self.lexer.eat_keyword("select")?;
+        let (fields, aggregations) = self.select_list()?;
+        self.lexer.eat_keyword("from")?;
+        let tables = self.table_list()?;
+        let pred = self.where_clause()?;
+        let group_by = self.group_by_clause()?;
+        let order_by = self.order_by_clause()?;
+        Ok(QueryData::new(fields, tables, pred)
+            .with_aggregations(aggregations)
+            .with_group_by(group_by)
+            .with_order_by(order_by))
+    }
+
+    /// `<SelectList> := <SelectItem> [ , <SelectList> ]`
+    ///
+    /// `<SelectItem> := <Field> | <AggFn> ( <Field> )`
+    ///
+    /// `<AggFn> := COUNT | SUM | MIN | MAX | AVG`
+    ///
+    /// The function names are not keywords, so an item is an aggregation when its name is followed by a parenthesis.
+    fn select_list(&mut self) -> Result<(Vec<String>, Vec<Aggregation>)> {
+        let mut fields = vec![];
+        let mut aggregations = vec![];
+        loop {
+            let name = self.lexer.eat_id()?;
+            if self.lexer.match_delim('(') {
+                let kind =
+                    AggregationKind::from_name(&name).ok_or_else(|| self.lexer.bad_syntax())?;
+                self.lexer.eat_delim('(')?;
+                let field_name = self.field()?;
+                self.lexer.eat_delim(')')?;
+                aggregations.push(Aggregation::new(kind, &field_name));
+            } else {
+                fields.push(name);
+            }
+            if !self.lexer.match_delim(',') {
+                return Ok((fields, aggregations));
+            }
+            self.lexer.eat_delim(',')?;
+        }
+    }
+
+    /// `<TableList> := IdTok [ , <TableList> ]`
+    fn table_list(&mut self) -> Result<Vec<String>> {
+        let mut tables = vec![self.lexer.eat_id()?];
+        while self.lexer.match_delim(',') {
+            self.lexer.eat_delim(',')?;
+            tables.push(self.lexer.eat_id()?);
+        }
+        Ok(tables)
+    }
+
+    fn group_by_clause(&mut self) -> Result<Vec<String>> {
+        if self.lexer.match_keyword("group") {
+            self.lexer.eat_keyword("group")?;
+            self.lexer.eat_keyword("by")?;
+            self.field_list()
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    fn order_by_clause(&mut self) -> Result<Vec<String>> {
+        if self.lexer.match_keyword("order") {
+            self.lexer.eat_keyword("order")?;
+            self.lexer.eat_keyword("by")?;
+            self.field_list()
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    fn where_clause(&mut self) -> Result<Predicate> {
+        if self.lexer.match_keyword("where") {
+            self.lexer.eat_keyword("where")?;
+            self.predicate()
+        } else {
+            Ok(Predicate::new())
+        }
+    }
+
+    fn comparison(&mut self, lhs: Expression) -> Result<Term> {
+        let op = self.operator()?;
+        let rhs = self.expression()?;
+        Ok(Term::with_operator(lhs, op, rhs))
+    }
+
+    /// `<Operator> := = | < | <= | > | >=`
+    fn operator(&mut self) -> Result<Operator> {
+        let (op, or_equal) = if self.lexer.match_delim('=') {
+            self.lexer.eat_delim('=')?;
+            return Ok(Operator::Eq);
+        } else if self.lexer.match_delim('<') {
+            self.lexer.eat_delim('<')?;
+            (Operator::Lt, Operator::Le)
+        } else {
+            self.lexer.eat_delim('>')?;
+            (Operator::Gt, Operator::Ge)
+        };
+        if self.lexer.match_delim('=') {
+            self.lexer.eat_delim('=')?;
+            return Ok(or_equal);
+        }
+        Ok(op)
+    }
+
+    /// `<Create> := <CreateTable> | <CreateView> | <CreateIndex>`
+    fn create(&mut self) -> Result<UpdateCommand> {
+        self.lexer.eat_keyword("create")?;
+        if self.lexer.match_keyword("table") {
+            Ok(UpdateCommand::CreateTable(self.create_table()?))
+        } else if self.lexer.match_keyword("view") {
+            Ok(UpdateCommand::CreateView(self.create_view()?))
+        } else if self.lexer.match_keyword("index") {
+            Ok(UpdateCommand::CreateIndex(self.create_index()?))
+        } else {
+            Err(self.lexer.bad_syntax().into())
+        }
+    }
+
+    /// `<FieldList> := <Field> [ , <FieldList> ]`
+    fn field_list(&mut self) -> Result<Vec<String>> {
+        let mut fields = vec![self.field()?];
+        while self.lexer.match_delim(',') {
+            self.lexer.eat_delim(',')?;
+            fields.push(self.field()?);
+        }
+        Ok(fields)
+    }
+
+    /// `<ConstList> := <Constant> [ , <ConstList> ]`
+    fn const_list(&mut self) -> Result<Vec<Constant>> {
+        let mut vals = vec![self.constant()?];
+        while self.lexer.match_delim(',') {
+            self.lexer.eat_delim(',')?;
+            vals.push(self.constant()?);
+        }
+        Ok(vals)
+    }
+
+    /// `<FieldDefs> := <FieldDef> [ , <FieldDefs> ]`
+    fn field_defs(&mut self) -> Result<Schema> {
+        let mut schema = self.field_def()?;
+        while self.lexer.match_delim(',') {
+            self.lexer.eat_delim(',')?;
+            schema.add_all(&self.field_def()?);
+        }
+        Ok(schema)
+    }
+
+    /// `<FieldDef> := IdTok <TypeDef>`
+    fn field_def(&mut self) -> Result<Schema> {
+        let field_name = self.field()?;
+        self.field_type(&field_name)
+    }
+
+    /// `<TypeDef> := INT | VARCHAR ( IntTok )`
+    fn field_type(&mut self, field_name: &str) -> Result<Schema> {
+        let mut schema = Schema::new();
+        if self.lexer.match_keyword("int") {
+            self.lexer.eat_keyword("int")?;
+            schema.add_int_field(field_name);
+        } else {
+            self.lexer.eat_keyword("varchar")?;
+            self.lexer.eat_delim('(')?;
+            let length_token = self.lexer.bad_syntax();
+            let length = self.lexer.eat_int_constant()?;
+            if length < 0 {
+                return Err(length_token.into());
+            }
+            self.lexer.eat_delim(')')?;
+            schema.add_string_field(field_name, length as usize);
+        }
+        Ok(schema)
+    }
+
+    /// Accept an optional trailing semicolon, and reject any tokens after it.
+    fn end_of_statement(&mut self) -> Result<()> {
+        if self.lexer.match_delim(';') {
+            self.lexer.eat_delim(';')?;
+        }
+        if !self.lexer.match_eof() {
+            return Err(self.lexer.bad_syntax().into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        index::index::IndexType,
+        parse::lexer::BadSyntaxError,
+        query::{
+            aggregation_fn::{Aggregation, AggregationKind},
+            constant::Constant,
+        },
+        record::schema::{FieldType, Schema},
+    };
+
+    use super::{Parser, UpdateCommand};
+
+    #[test]
+    fn test_query() {
+        let sql =
+            "SELECT sname, gradyear FROM student, dept WHERE did = deptid AND dname = 'math';";
+        let query_data = Parser::new(sql).unwrap().query().unwrap();
+        assert_eq!(query_data.fields(), ["sname", "gradyear"]);
+        assert_eq!(query_data.tables(), ["student", "dept"]);
+        assert_eq!(query_data.pred().terms().len(), 2);
+        assert_eq!(
+            query_data.pred().equates_with_constant("dname"),
+            Some(&Constant::String("math".to_string()))
+        );
+        assert_eq!(query_data.pred().equates_with_field("deptid"), Some("did"));
+        assert_eq!(
+            query_data.to_string(),
+            "select sname, gradyear from student, dept where did = deptid and dname = 'math'"
+        );
+    }
+
+    #[test]
+    fn test_update_commands() {
+        let cmd = Parser::new("insert into student (sid, sname) values (1, 'joe')")
+            .unwrap()
+            .update_cmd()
+            .unwrap();
+        let UpdateCommand::Insert(data) = cmd else {
+            panic!("expected insert, got {:?}", cmd);
+        };
+        assert_eq!(data.table_name(), "student");
+        assert_eq!(data.fields(), ["sid", "sname"]);
+        assert_eq!(
+            data.vals(),
+            [Constant::Int(1), Constant::String("joe".to_string())]
+        );
+
+        let cmd = Parser::new("delete from student where sid = 1")
+            .unwrap()
+            .update_cmd()
+            .unwrap();
+        let UpdateCommand::Delete(data) = cmd else {
+            panic!("expected delete, got {:?}", cmd);
+        };
+        assert_eq!(data.table_name(), "student");
+        assert_eq!(data.pred().to_string(), "sid = 1");
+
+        let cmd = Parser::new("update student set gradyear = 2020")
+            .unwrap()
+            .update_cmd()
+            .unwrap();
+        let UpdateCommand::Modify(data) = cmd else {
+            panic!("expected modify, got {:?}", cmd);
+        };
+        assert_eq!(data.target_field(), "gradyear");
+        assert_eq!(data.new_val().to_string(), "2020");
+        assert!(data.pred().is_empty());
+
+        let cmd = Parser::new("create table student (sid int, sname varchar(10))")
+            .unwrap()
+            .update_cmd()
+            .unwrap();
+        let UpdateCommand::CreateTable(data) = cmd else {
+            panic!("expected create table, got {:?}", cmd);
+        };
+        let mut schema = Schema::new();
+        schema.add_int_field("sid");
+        schema.add_string_field("sname", 10);
+        assert_eq!(data.new_schema(), &schema);
+        assert_eq!(
+            data.new_schema().field_type("sname"),
+            Some(FieldType::Varchar)
+        );
+
+        let cmd = Parser::new("create view math as select sname from student where did = 10")
+            .unwrap()
+            .update_cmd()
+            .unwrap();
+        let UpdateCommand::CreateView(data) = cmd else {
+            panic!("expected create view, got {:?}", cmd);
+        };
+        assert_eq!(data.view_name(), "math");
+        assert_eq!(data.view_def(), "select sname from student where did = 10");
+
+        let cmd = Parser::new("create index sidx on student (sid)")
+            .unwrap()
+            .update_cmd()
+            .unwrap();
+        let UpdateCommand::CreateIndex(data) = cmd else {
+            panic!("expected create index, got {:?}", cmd);
+        };
+        assert_eq!(data.index_name(), "sidx");
+        assert_eq!(data.table_name(), "student");
+        assert_eq!(data.field_name(), "sid");
+        assert_eq!(data.index_type(), IndexType::Hash);
+
+        let cmd = Parser::new("create index sidx on student (sid) using btree")
+            .unwrap()
+            .update_cmd()
+            .unwrap();
+        let UpdateCommand::CreateIndex(data) = cmd else {
+            panic!("expected create index, got {:?}", cmd);
+        };
+        assert_eq!(data.index_type(), IndexType::BTree);
+    }
+
+    #[test]
+    fn test_comparisons() {
+        let sql =
+            "select sname from student where gradyear >= 2019 and 2024 > gradyear and sid < 9";
+        let query_data = Parser::new(sql).unwrap().query().unwrap();
+        assert_eq!(
+            query_data.pred().to_string(),
+            "gradyear >= 2019 and 2024 > gradyear and sid < 9"
+        );
+        assert_eq!(query_data.pred().equates_with_constant("gradyear"), None);
+        assert_eq!(
+            query_data.pred().range_for("gradyear"),
+            Some((Some(Constant::Int(2019)), Some(Constant::Int(2024))))
+        );
+        assert_eq!(
+            query_data.pred().range_for("sid"),
+            Some((None, Some(Constant::Int(9))))
+        );
+        assert_eq!(query_data.pred().range_for("sname"), None);
+
+        let sql = "select sname from student where sid = 3 order by gradyear, sname";
+        let query_data = Parser::new(sql).unwrap().query().unwrap();
+        assert_eq!(query_data.order_by(), ["gradyear", "sname"]);
+        assert_eq!(query_data.to_string(), sql);
+
+        let sql = "select did, count(sid), max(gradyear) from student group by did order by did";
+        let query_data = Parser::new(sql).unwrap().query().unwrap();
+        assert_eq!(query_data.fields(), ["did"]);
+        assert_eq!(
+            query_data.aggregations(),
+            [
+                Aggregation::new(AggregationKind::Count, "sid"),
+                Aggregation::new(AggregationKind::Max, "gradyear")
+            ]
+        );
+        assert_eq!(query_data.group_by(), ["did"]);
+        assert_eq!(
+            query_data.output_fields(),
+            ["did", "countofsid", "maxofgradyear"]
+        );
+        assert_eq!(query_data.to_string(), sql);
+
+        let sql = "select sname from student where sid between 3 and 7 and sid <= 5";
+        let query_data = Parser::new(sql).unwrap().query().unwrap();
+        assert_eq!(
+            query_data.pred().to_string(),
+            "sid >= 3 and sid <= 7 and sid <= 5"
+        );
+        assert_eq!(
+            query_data.pred().range_for("sid"),
+            Some((Some(Constant::Int(3)), Some(Constant::Int(5))))
+        );
+    }
+
+    #[test]
+    fn test_bad_syntax() {
+        let position = |sql: &str, update: bool| {
+            let mut parser = Parser::new(sql).unwrap();
+            let err = if update {
+                parser.update_cmd().unwrap_err()
+            } else {
+                parser.query().unwrap_err()
+            };
+            err.downcast_ref::<BadSyntaxError>().unwrap().position()
+        };
+
+        assert_eq!(position("select from t", false), 7);
+        assert_eq!(position("select a from t where a", false), 23);
+        assert_eq!(position("select a from t extra", false), 16);
+        assert_eq!(position("select total(a) from t", false), 12);
+        assert_eq!(position("select a from t group a", false), 22);
+        assert_eq!(position("create table t (a varchar(-1))", true), 26);
+        assert_eq!(position("create sequence s", true), 7);
+        assert_eq!(position("drop table t", true), 0);
+        assert_eq!(position("select a from t where a between 1", false), 33);
+        assert_eq!(position("create index i on t (a) using tree", true), 30);
+    }
+}
@@ -0,0 +1,113 @@
+use core::fmt;
+
+use crate::query::{aggregation_fn::Aggregation, predicate::Predicate};
+
+/// Data for the SQL select statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryData {
+    fields: Vec<String>,
+    tables: Vec<String>,
+    pred: Predicate,
+    aggregations: Vec<Aggregation>,
+    group_by: Vec<String>,
+    order_by: Vec<String>,
+}
+
+/// Reconstruct the query string, which is how view definitions are stored in the catalog.
+impl fmt::Display for QueryData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let select_list: Vec<String> = self
+            .fields
+            .iter()
+            .cloned()
+            .chain(self.aggregations.iter().map(Aggregation::to_string))
+            .collect();
+        write!(
+            f,
+            "select {} from {}",
+            select_list.join(", "),
+            self.tables.join(", ")
+        )?;
+        if !self.pred.is_empty() {
+            write!(f, " where {}", self.pred)?;
+        }
+        if !self.group_by.is_empty() {
+            write!(f, " group by {}", self.group_by.join(", "))?;
+        }
+        if !self.order_by.is_empty() {
+            write!(f, " order by {}", self.order_by.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl QueryData {
+    pub fn new(fields: Vec<String>, tables: Vec<String>, pred: Predicate) -> Self {
+        Self {
+            fields,
+            tables,
+            pred,
+            aggregations: vec![],
+            group_by: vec![],
+            order_by: vec![],
+        }
+    }
+
+    /// Add the specified aggregations to the output of the query, after its fields.
+    pub fn with_aggregations(mut self, aggregations: Vec<Aggregation>) -> Self {
+        self.aggregations = aggregations;
+        self
+    }
+
+    /// Group the records of the query on the specified fields.
+    pub fn with_group_by(mut self, group_by: Vec<String>) -> Self {
+        self.group_by = group_by;
+        self
+    }
+
+    /// Sort the output of the query on the specified fields.
+    pub fn with_order_by(mut self, order_by: Vec<String>) -> Self {
+        self.order_by = order_by;
+        self
+    }
+
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    pub fn tables(&self) -> &[String] {
+        &self.tables
+    }
+
+    pub fn pred(&self) -> &Predicate {
+        &self.pred
+    }
+
+    pub fn aggregations(&self) -> &[Aggregation] {
+        &self.aggregations
+    }
+
+    /// Return the fields that the records are grouped on, or an empty list if the query does not group them.
+    pub fn group_by(&self) -> &[String] {
+        &self.group_by
+    }
+
+    /// Return true if the query groups its records, either on group fields or into a single group of aggregations.
+    pub fn is_grouped(&self) -> bool {
+        !self.group_by.is_empty() || !self.aggregations.is_empty()
+    }
+
+    /// Return the names of the output fields: the fields of the select list, followed by the aggregation fields.
+    pub fn output_fields(&self) -> Vec<String> {
+        self.fields
+            .iter()
+            .cloned()
+            .chain(self.aggregations.iter().map(Aggregation::output_field))
+            .collect()
+    }
+
+    /// Return the fields that the output is sorted on, or an empty list if the order does not matter.
+    pub fn order_by(&self) -> &[String] {
+        &self.order_by
+    }
+}
@@ -0,0 +1,21 @@
+pub mod basic_query_planner;
+pub mod basic_update_planner;
+pub mod group_by_plan;
+pub mod hash_join_plan;
+pub mod heuristic_query_planner;
+pub mod index_join_plan;
+pub mod index_select_plan;
+pub mod materialize_plan;
+pub mod merge_join_plan;
+pub mod multibuffer_product_plan;
+#[allow(clippy::module_inception)]
+pub mod plan;
+pub mod planner;
+pub mod product_plan;
+pub mod project_plan;
+pub mod query_planner;
+pub mod select_plan;
+pub mod sort_plan;
+pub mod table_plan;
+pub mod table_planner;
+pub mod update_planner;
@@ -0,0 +1,234 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    metadata::{index_manager::IndexInfo, manager::MetadataManager},
+    parse::{parser::Parser, query_data::QueryData},
+    query::predicate::Predicate,
+    tx::transaction::Transaction,
+};
+
+use super::{
+    group_by_plan::GroupByPlan, index_join_plan::IndexJoinPlan, index_select_plan::IndexSelectPlan,
+    plan::Plan, product_plan::ProductPlan, project_plan::ProjectPlan, query_planner::QueryPlanner,
+    select_plan::SelectPlan, sort_plan::SortPlan, table_plan::TablePlan,
+};
+
+#[derive(Debug)]
+enum BasicQueryPlannerError {
+    NoTables,
+}
+
+impl std::error::Error for BasicQueryPlannerError {}
+impl fmt::Display for BasicQueryPlannerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BasicQueryPlannerError::NoTables => write!(f, "query mentions no tables"),
+        }
+    }
+}
+
+/// The input of a join: either a table, whose indexes may still be used to join it,
+/// or any other plan.
+enum JoinInput {
+    Table(Box<TablePlan>, HashMap<String, IndexInfo>),
+    Plan(Box<dyn Plan>),
+}
+
+impl JoinInput {
+    fn into_plan(self) -> Box<dyn Plan> {
+        match self {
+            JoinInput::Table(plan, _) => plan,
+            JoinInput::Plan(plan) => plan,
+        }
+    }
+}
+
+/// A simple query planner, which joins the tables in the order they are mentioned.
+/// A table is read through an index when the predicate selects on an indexed field,
+/// and is joined through an index when the predicate equates an indexed field with a field of the tables before it.
+pub struct BasicQueryPlanner {
+    metadata_manager: Arc<MetadataManager>,
+}
+
+impl BasicQueryPlanner {
+    pub fn new(metadata_manager: Arc<MetadataManager>) -> Self {
+        Self { metadata_manager }
+    }
+
+    /// Create the input for the specified table, using an index select plan when one applies.
+    fn table_input(
+        &self,
+        table_name: &str,
+        pred: &Predicate,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<JoinInput> {
+        let plan = TablePlan::new(Arc::clone(&tx), table_name, &self.metadata_manager)?;
+        let indexes = self.metadata_manager.get_index_info(table_name, tx)?;
+        match IndexSelectPlan::find_search(&indexes, pred) {
+            Some((index_info, search)) => Ok(JoinInput::Plan(Box::new(IndexSelectPlan::new(
+                plan,
+                index_info.clone(),
+                search,
+            )))),
+            None => Ok(JoinInput::Table(Box::new(plan), indexes)),
+        }
+    }
+
+    /// Join the input to the plan, through an index of the input table if the predicate equates
+    /// an indexed field with a field of the plan; otherwise take their product.
+    fn join(plan: Box<dyn Plan>, input: JoinInput, pred: &Predicate) -> Box<dyn Plan> {
+        let JoinInput::Table(table_plan, indexes) = input else {
+            return Box::new(ProductPlan::new(plan, input.into_plan()));
+        };
+        match IndexJoinPlan::find_join(&indexes, pred, plan.schema()) {
+            Some((index_info, join_field)) => Box::new(IndexJoinPlan::new(
+                plan,
+                *table_plan,
+                index_info.clone(),
+                &join_field,
+            )),
+            None => Box::new(ProductPlan::new(plan, table_plan)),
+        }
+    }
+}
+
+impl QueryPlanner for BasicQueryPlanner {
+    /// Create a query plan as follows. It first joins all tables and views, using indexes where the predicate allows;
+    /// it then selects on the predicate; it groups and aggregates the records, if the query asks for it;
+    /// it sorts on the order by fields, if any; and finally it projects on the field list.
+    fn create_plan(&self, data: &QueryData, tx: Arc<Mutex<Transaction>>) -> Result<Box<dyn Plan>> {
+        // Step 1: Create an input for each mentioned table or view.
+        let mut inputs = vec![];
+        for table_name in data.tables() {
+            if let Some(view_def) = self
+                .metadata_manager
+                .get_view_def(table_name, Arc::clone(&tx))?
+            {
+                // Recursively plan the view.
+                let view_data = Parser::new(&view_def)?.query()?;
+                inputs.push(JoinInput::Plan(
+                    self.create_plan(&view_data, Arc::clone(&tx))?,
+                ));
+            } else {
+                inputs.push(self.table_input(table_name, data.pred(), Arc::clone(&tx))?);
+            }
+        }
+
+        // Step 2: Join the inputs in order.
+        let mut inputs = inputs.into_iter();
+        let first = inputs.next().ok_or(BasicQueryPlannerError::NoTables)?;
+        let mut plan = inputs.fold(first.into_plan(), |plan, input| {
+            Self::join(plan, input, data.pred())
+        });
+
+        // Step 3: Add a selection plan for the predicate.
+        // The index plans find candidate records only, so the whole predicate is still checked.
+        plan = Box::new(SelectPlan::new(plan, data.pred().clone()));
+
+        // Step 4: Group and aggregate the records, if required.
+        if data.is_grouped() {
+            plan = Box::new(GroupByPlan::new(
+                Arc::clone(&tx),
+                plan,
+                data.group_by(),
+                data.aggregations(),
+            )?);
+        }
+
+        // Step 5: Sort on the order by fields, if any.
+        if !data.order_by().is_empty() {
+            plan = Box::new(SortPlan::new(tx, plan, data.order_by())?);
+        }
+
+        // Step 6: Project on the output fields.
+        Ok(Box::new(ProjectPlan::new(plan, &data.output_fields())?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+
+    use crate::{query::constant::Constant, server::simpledb::SimpleDB};
+
+    #[test]
+    fn test_index_planning() {
+        let temp_dir = tempdir().unwrap();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+        let planner = db.planner();
+        let tx = db.new_tx().unwrap();
+        let update = |sql: &str| planner.execute_update(sql, Arc::clone(&tx)).unwrap();
+        let query = |sql: &str, field_name: &str| {
+            let plan = planner.create_query_plan(sql, Arc::clone(&tx)).unwrap();
+            let mut scan = plan.open().unwrap();
+            let mut vals = vec![];
+            while scan.next().unwrap() {
+                vals.push(scan.get_val(field_name).unwrap());
+            }
+            scan.close().unwrap();
+            vals
+        };
+        let ints = |vals: &[i32]| vals.iter().map(|&n| Constant::Int(n)).collect::<Vec<_>>();
+
+        // the records of T1 are stored in descending order of A,
+        // and the index on A is created after them
+        update("create table T1(A int, B varchar(9))");
+        for i in 0..100 {
+            update(&format!(
+                "insert into T1(A, B) values ({}, 'rec{}')",
+                99 - i,
+                i
+            ));
+        }
+        update("create index aidx on T1(A) using btree");
+        update("create table T2(C int, D varchar(9))");
+        update("create index cidx on T2(C)");
+        for i in 0..50 {
+            update(&format!("insert into T2(C, D) values ({}, 'd{}')", i, i));
+        }
+
+        // a range search over the B-tree returns the records in index order
+        assert_eq!(
+            query("select A from T1 where A >= 10 and A < 15", "a"),
+            ints(&[10, 11, 12, 13, 14])
+        );
+        assert_eq!(
+            query("select B from T1 where A = 42", "b"),
+            [Constant::String("rec57".to_string())]
+        );
+        assert_eq!(
+            query("select D from T2 where C = 7", "d"),
+            [Constant::String("d7".to_string())]
+        );
+        assert_eq!(
+            query("select A, D from T1, T2 where A = C and A < 3", "a"),
+            ints(&[0, 1, 2])
+        );
+
+        // the indexes follow modifications and deletions
+        update("update T1 set A = 200 where A = 42");
+        assert!(query("select B from T1 where A = 42", "b").is_empty());
+        assert_eq!(
+            query("select B from T1 where A = 200", "b"),
+            [Constant::String("rec57".to_string())]
+        );
+        update("delete from T2 where C = 1");
+        assert!(query("select D from T2 where C = 1", "d").is_empty());
+        assert_eq!(
+            query("select A, D from T1, T2 where A = C and A < 3", "a"),
+            ints(&[0, 2])
+        );
+
+        assert!(planner
+            .execute_update("create index bad on T1(Z)", Arc::clone(&tx))
+            .is_err());
+        tx.lock().unwrap().commit().unwrap();
+    }
+}
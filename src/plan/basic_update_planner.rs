@@ -0,0 +1,268 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    index::index::Index,
+    metadata::manager::MetadataManager,
+    parse::{
+        create_index_data::CreateIndexData, create_table_data::CreateTableData,
+        create_view_data::CreateViewData, delete_data::DeleteData, insert_data::InsertData,
+        modify_data::ModifyData,
+    },
+    query::{
+        constant::Constant,
+        predicate::Predicate,
+        scan::{Scan, UpdateScan},
+        select_scan::SelectScan,
+    },
+    record::schema::{FieldType, Schema},
+    tx::transaction::Transaction,
+};
+
+use super::{plan::Plan, table_plan::TablePlan, update_planner::UpdatePlanner};
+
+#[derive(Debug)]
+enum BasicUpdatePlannerError {
+    NotUpdatable(String),
+    FieldCountMismatch(usize, usize),
+    UnknownField(String),
+    TypeMismatch(String, FieldType, Constant),
+}
+
+impl std::error::Error for BasicUpdatePlannerError {}
+impl fmt::Display for BasicUpdatePlannerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BasicUpdatePlannerError::NotUpdatable(table_name) => {
+                write!(f, "table {} is not updatable", table_name)
+            }
+            BasicUpdatePlannerError::FieldCountMismatch(fields, vals) => {
+                write!(f, "{} fields but {} values", fields, vals)
+            }
+            BasicUpdatePlannerError::UnknownField(field_name) => {
+                write!(f, "field not found: {}", field_name)
+            }
+            BasicUpdatePlannerError::TypeMismatch(field_name, field_type, val) => {
+                write!(
+                    f,
+                    "value {} does not match the type {:?} of field {}",
+                    val, field_type, field_name
+                )
+            }
+        }
+    }
+}
+
+/// The basic planner for SQL update statements.
+/// Each statement is executed directly against a table scan, and every index of the table is updated to match.
+pub struct BasicUpdatePlanner {
+    metadata_manager: Arc<MetadataManager>,
+}
+
+impl BasicUpdatePlanner {
+    pub fn new(metadata_manager: Arc<MetadataManager>) -> Self {
+        Self { metadata_manager }
+    }
+
+    /// Open an update scan over the records of the table that satisfy the predicate of the statement.
+    fn open_selection(
+        &self,
+        table_name: &str,
+        pred: &Predicate,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<SelectScan> {
+        let plan = TablePlan::new(tx, table_name, &self.metadata_manager)?;
+        let scan = SelectScan::new(plan.open()?, pred.clone());
+        Ok(scan)
+    }
+
+    /// Open every index of the table, keyed by the indexed field.
+    fn open_indexes(
+        &self,
+        table_name: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Vec<(String, Box<dyn Index>)>> {
+        self.metadata_manager
+            .get_index_info(table_name, tx)?
+            .into_iter()
+            .map(|(field_name, index_info)| Ok((field_name, index_info.open()?)))
+            .collect()
+    }
+
+    /// Check that the value can be stored in the field, so that a value of the wrong type is never written as raw
+    /// bytes that the field's type would decode as garbage.
+    fn check_val(schema: &Schema, field_name: &str, val: &Constant) -> Result<()> {
+        let field_type = schema
+            .field_type(field_name)
+            .ok_or_else(|| BasicUpdatePlannerError::UnknownField(field_name.to_string()))?;
+        let matches = match val {
+            Constant::Int(_) => field_type == FieldType::Integer,
+            Constant::String(_) => field_type == FieldType::Varchar,
+        };
+        if !matches {
+            return Err(BasicUpdatePlannerError::TypeMismatch(
+                field_name.to_string(),
+                field_type,
+                val.clone(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    fn close_indexes(indexes: Vec<(String, Box<dyn Index>)>) -> Result<()> {
+        for (_, mut index) in indexes {
+            index.close()?;
+        }
+        Ok(())
+    }
+}
+
+impl UpdatePlanner for BasicUpdatePlanner {
+    fn execute_insert(&self, data: &InsertData, tx: Arc<Mutex<Transaction>>) -> Result<usize> {
+        if data.fields().len() != data.vals().len() {
+            return Err(BasicUpdatePlannerError::FieldCountMismatch(
+                data.fields().len(),
+                data.vals().len(),
+            )
+            .into());
+        }
+
+        let plan = TablePlan::new(Arc::clone(&tx), data.table_name(), &self.metadata_manager)?;
+        for (field_name, val) in data.fields().iter().zip(data.vals()) {
+            Self::check_val(plan.schema(), field_name, val)?;
+        }
+        let mut scan = plan.open()?;
+        let update_scan = scan
+            .as_update_scan()
+            .ok_or_else(|| BasicUpdatePlannerError::NotUpdatable(data.table_name().to_string()))?;
+        update_scan.insert()?;
+        for (field_name, val) in data.fields().iter().zip(data.vals()) {
+            update_scan.set_val(field_name, val)?;
+        }
+
+        // insert an index record for every index of the table
+        let rid = update_scan.get_rid()?;
+        let mut indexes = self.open_indexes(data.table_name(), tx)?;
+        for (field_name, index) in indexes.iter_mut() {
+            let val = update_scan.get_val(field_name)?;
+            index.insert(&val, &rid)?;
+        }
+        Self::close_indexes(indexes)?;
+        update_scan.close()?;
+        Ok(1)
+    }
+
+    fn execute_delete(&self, data: &DeleteData, tx: Arc<Mutex<Transaction>>) -> Result<usize> {
+        let mut indexes = self.open_indexes(data.table_name(), Arc::clone(&tx))?;
+        let mut scan = self.open_selection(data.table_name(), data.pred(), tx)?;
+        let mut count = 0;
+        while scan.next()? {
+            // delete the record's index records first, before its rid is invalidated
+            let rid = scan.get_rid()?;
+            for (field_name, index) in indexes.iter_mut() {
+                let val = scan.get_val(field_name)?;
+                index.delete(&val, &rid)?;
+            }
+            scan.delete()?;
+            count += 1;
+        }
+        Self::close_indexes(indexes)?;
+        scan.close()?;
+        Ok(count)
+    }
+
+    fn execute_modify(&self, data: &ModifyData, tx: Arc<Mutex<Transaction>>) -> Result<usize> {
+        let layout = self
+            .metadata_manager
+            .get_layout(data.table_name(), Arc::clone(&tx))?;
+        if !layout.schema().has_field(data.target_field()) {
+            return Err(
+                BasicUpdatePlannerError::UnknownField(data.target_field().to_string()).into(),
+            );
+        }
+        let mut index = self
+            .metadata_manager
+            .get_index_info(data.table_name(), Arc::clone(&tx))?
+            .get(data.target_field())
+            .map(|index_info| index_info.open())
+            .transpose()?;
+        let mut scan = self.open_selection(data.table_name(), data.pred(), tx)?;
+        let mut count = 0;
+        while scan.next()? {
+            let new_val = data.new_val().evaluate(&scan)?;
+            Self::check_val(layout.schema(), data.target_field(), &new_val)?;
+            let old_val = scan.get_val(data.target_field())?;
+            scan.set_val(data.target_field(), &new_val)?;
+
+            // update the index record of the modified field, if it is indexed
+            if let Some(index) = index.as_mut() {
+                let rid = scan.get_rid()?;
+                index.delete(&old_val, &rid)?;
+                index.insert(&new_val, &rid)?;
+            }
+            count += 1;
+        }
+        if let Some(mut index) = index {
+            index.close()?;
+        }
+        scan.close()?;
+        Ok(count)
+    }
+
+    fn execute_create_table(
+        &self,
+        data: &CreateTableData,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<usize> {
+        self.metadata_manager
+            .create_table(data.table_name(), data.new_schema(), tx)?;
+        Ok(0)
+    }
+
+    fn execute_create_view(
+        &self,
+        data: &CreateViewData,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<usize> {
+        self.metadata_manager
+            .create_view(data.view_name(), &data.view_def(), tx)?;
+        Ok(0)
+    }
+
+    fn execute_create_index(
+        &self,
+        data: &CreateIndexData,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<usize> {
+        let plan = TablePlan::new(Arc::clone(&tx), data.table_name(), &self.metadata_manager)?;
+        if !plan.schema().has_field(data.field_name()) {
+            return Err(
+                BasicUpdatePlannerError::UnknownField(data.field_name().to_string()).into(),
+            );
+        }
+        self.metadata_manager.create_index(
+            data.index_name(),
+            data.table_name(),
+            data.field_name(),
+            data.index_type(),
+            Arc::clone(&tx),
+        )?;
+
+        // index the records that the table already holds
+        let index_info = self
+            .metadata_manager
+            .get_index_info(data.table_name(), tx)?
+            .remove(data.field_name())
+            .ok_or_else(|| BasicUpdatePlannerError::UnknownField(data.field_name().to_string()))?;
+        let mut index = index_info.open()?;
+        let mut scan = plan.open_table_scan()?;
+        while scan.next()? {
+            index.insert(&scan.get_val(data.field_name())?, &scan.get_rid()?)?;
+        }
+        index.close()?;
+        scan.close()?;
+        Ok(0)
+    }
+}
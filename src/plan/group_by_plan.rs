@@ -0,0 +1,273 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    query::{
+        aggregation_fn::{Aggregation, AggregationKind},
+        group_by_scan::GroupByScan,
+        scan::Scan,
+    },
+    record::schema::{FieldType, Schema},
+    tx::transaction::Transaction,
+};
+
+use super::{plan::Plan, sort_plan::SortPlan};
+
+#[derive(Debug)]
+enum GroupByPlanError {
+    FieldNotFound(String),
+    NotAnInteger(String),
+}
+
+impl std::error::Error for GroupByPlanError {}
+impl fmt::Display for GroupByPlanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GroupByPlanError::FieldNotFound(field_name) => {
+                write!(f, "field {} not found", field_name)
+            }
+            GroupByPlanError::NotAnInteger(aggregation) => {
+                write!(f, "{} needs an integer field", aggregation)
+            }
+        }
+    }
+}
+
+/// The plan class for the groupby operator.
+/// The underlying query is sorted on the group fields, so that the scan sees the records of each group together.
+/// Without group fields, the whole input is a single group and no sort is needed.
+pub struct GroupByPlan {
+    plan: Box<dyn Plan>,
+    group_fields: Vec<String>,
+    aggregations: Vec<Aggregation>,
+    schema: Schema,
+}
+
+impl GroupByPlan {
+    /// Create a groupby plan for the underlying query.
+    /// Fail if a group field or aggregated field is not produced by the underlying plan,
+    /// or if a sum or an average is over a field that is not an integer.
+    pub fn new(
+        tx: Arc<Mutex<Transaction>>,
+        plan: Box<dyn Plan>,
+        group_fields: &[String],
+        aggregations: &[Aggregation],
+    ) -> Result<Self> {
+        let src_schema = plan.schema();
+        let mut schema = Schema::new();
+        for field_name in group_fields {
+            if !src_schema.has_field(field_name) {
+                return Err(GroupByPlanError::FieldNotFound(field_name.clone()).into());
+            }
+            schema.add(field_name, src_schema);
+        }
+        for aggregation in aggregations {
+            let field_name = aggregation.field_name();
+            let field_type = src_schema
+                .field_type(field_name)
+                .ok_or_else(|| GroupByPlanError::FieldNotFound(field_name.to_string()))?;
+            let output_field = aggregation.output_field();
+            match aggregation.kind() {
+                AggregationKind::Min | AggregationKind::Max => {
+                    let length = src_schema.length(field_name).unwrap_or(0);
+                    schema.add_field(&output_field, field_type, length);
+                }
+                AggregationKind::Sum | AggregationKind::Avg if field_type != FieldType::Integer => {
+                    return Err(GroupByPlanError::NotAnInteger(aggregation.to_string()).into());
+                }
+                _ => schema.add_int_field(&output_field),
+            }
+        }
+
+        let plan: Box<dyn Plan> = if group_fields.is_empty() {
+            plan
+        } else {
+            Box::new(SortPlan::new(tx, plan, group_fields)?)
+        };
+        Ok(Self {
+            plan,
+            group_fields: group_fields.to_vec(),
+            aggregations: aggregations.to_vec(),
+            schema,
+        })
+    }
+}
+
+impl Plan for GroupByPlan {
+    /// Open a sort plan for the underlying query, and a groupby scan over it.
+    fn open(&self) -> Result<Box<dyn Scan>> {
+        let scan = self.plan.open()?;
+        let agg_fns = self
+            .aggregations
+            .iter()
+            .map(Aggregation::create_fn)
+            .collect();
+        Ok(Box::new(GroupByScan::new(
+            scan,
+            self.group_fields.clone(),
+            agg_fns,
+        )?))
+    }
+
+    /// Return the number of blocks required to compute the aggregation,
+    /// which is one pass through the sorted table.
+    /// It does not include the one-time cost of materializing and sorting the records.
+    fn blocks_accessed(&self) -> usize {
+        self.plan.blocks_accessed()
+    }
+
+    /// Return the number of groups. Assuming equal distribution,
+    /// this is the product of the distinct values for each grouping field.
+    fn records_output(&self) -> usize {
+        self.group_fields
+            .iter()
+            .map(|field_name| self.plan.distinct_values(field_name))
+            .fold(1, usize::saturating_mul)
+            .min(self.plan.records_output().max(1))
+    }
+
+    /// Return the number of distinct values for the specified field.
+    /// If the field is a grouping field, then the number of distinct values is the same as in the underlying query.
+    /// If the field is an aggregate field, then we assume that all values are distinct.
+    fn distinct_values(&self, field_name: &str) -> usize {
+        if self.group_fields.iter().any(|f| f == field_name) {
+            self.plan.distinct_values(field_name)
+        } else {
+            self.records_output()
+        }
+    }
+
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+
+    use crate::{
+        plan::{plan::Plan, table_plan::TablePlan},
+        query::{
+            aggregation_fn::{Aggregation, AggregationKind},
+            constant::Constant,
+            scan::{Scan, UpdateScan},
+        },
+        record::table_scan::TableScan,
+        server::simpledb::SimpleDB,
+    };
+
+    use super::GroupByPlan;
+
+    #[test]
+    fn test_group_by_plan() {
+        let temp_dir = tempdir().unwrap();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+        let planner = db.planner();
+        let tx = db.new_tx().unwrap();
+
+        planner
+            .execute_update("create table T(A int, B varchar(9))", Arc::clone(&tx))
+            .unwrap();
+        let layout = db
+            .metadata_manager()
+            .get_layout("t", Arc::clone(&tx))
+            .unwrap();
+        let mut scan = TableScan::new(Arc::clone(&tx), "t", layout).unwrap();
+        for i in 0..300 {
+            scan.insert().unwrap();
+            scan.set_int("a", i).unwrap();
+            scan.set_string("b", &format!("b{}", i % 3)).unwrap();
+        }
+        scan.close().unwrap();
+        let table_plan =
+            || Box::new(TablePlan::new(Arc::clone(&tx), "t", &db.metadata_manager()).unwrap());
+
+        let aggregations = [
+            Aggregation::new(AggregationKind::Count, "a"),
+            Aggregation::new(AggregationKind::Sum, "a"),
+            Aggregation::new(AggregationKind::Min, "a"),
+            Aggregation::new(AggregationKind::Max, "a"),
+            Aggregation::new(AggregationKind::Avg, "a"),
+        ];
+        let plan = GroupByPlan::new(
+            Arc::clone(&tx),
+            table_plan(),
+            &["b".to_string()],
+            &aggregations,
+        )
+        .unwrap();
+        assert_eq!(
+            plan.schema().fields(),
+            ["b", "countofa", "sumofa", "minofa", "maxofa", "avgofa"]
+        );
+        let mut scan = plan.open().unwrap();
+        let mut groups = vec![];
+        while scan.next().unwrap() {
+            groups.push(
+                plan.schema()
+                    .fields()
+                    .iter()
+                    .map(|field_name| scan.get_val(field_name).unwrap())
+                    .collect::<Vec<_>>(),
+            );
+        }
+        scan.close().unwrap();
+        let expected: Vec<Vec<Constant>> = (0..3)
+            .map(|k| {
+                vec![
+                    Constant::String(format!("b{}", k)),
+                    Constant::Int(100),
+                    Constant::Int((0..100).map(|i| 3 * i + k).sum()),
+                    Constant::Int(k),
+                    Constant::Int(297 + k),
+                    Constant::Int(148 + k),
+                ]
+            })
+            .collect();
+        assert_eq!(groups, expected);
+
+        // without group fields, the whole table is one group
+        let aggregations = [Aggregation::new(AggregationKind::Max, "b")];
+        let plan = GroupByPlan::new(Arc::clone(&tx), table_plan(), &[], &aggregations).unwrap();
+        let mut scan = plan.open().unwrap();
+        assert!(scan.next().unwrap());
+        assert_eq!(scan.get_string("maxofb").unwrap(), "b2");
+        assert!(!scan.next().unwrap());
+        scan.close().unwrap();
+
+        let sum_of_string = [Aggregation::new(AggregationKind::Sum, "b")];
+        assert!(GroupByPlan::new(Arc::clone(&tx), table_plan(), &[], &sum_of_string).is_err());
+        assert!(GroupByPlan::new(Arc::clone(&tx), table_plan(), &["c".to_string()], &[]).is_err());
+
+        let plan = planner
+            .create_query_plan(
+                "select b, count(a), max(a) from t where a < 10 group by b order by b",
+                Arc::clone(&tx),
+            )
+            .unwrap();
+        let mut scan = plan.open().unwrap();
+        let mut groups = vec![];
+        while scan.next().unwrap() {
+            groups.push((
+                scan.get_string("b").unwrap(),
+                scan.get_int("countofa").unwrap(),
+                scan.get_int("maxofa").unwrap(),
+            ));
+        }
+        scan.close().unwrap();
+        assert_eq!(
+            groups,
+            [
+                ("b0".to_string(), 4, 9),
+                ("b1".to_string(), 3, 7),
+                ("b2".to_string(), 3, 8)
+            ]
+        );
+
+        tx.lock().unwrap().commit().unwrap();
+    }
+}
@@ -0,0 +1,279 @@
+use anyhow::{Ok, Result};
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    query::{
+        constant::Constant,
+        hash_join_scan::HashJoinScan,
+        scan::{Scan, UpdateScan},
+    },
+    record::{schema::Schema, temp_table::TempTable},
+    tx::transaction::Transaction,
+};
+
+use super::{materialize_plan::MaterializePlan, plan::Plan};
+
+/// The plan class corresponding to the hash join relational algebra operator.
+/// The RHS is the build side, whose records are held in memory while the LHS is probed.
+/// If the RHS does not fit in the available buffers, then the plan performs a grace hash join: both inputs are
+/// split by the hash of their join values into partitions of about as many blocks as there are available buffers,
+/// and each pair of partitions is joined separately.
+pub struct HashJoinPlan {
+    tx: Arc<Mutex<Transaction>>,
+    plan1: Box<dyn Plan>,
+    plan2: Box<dyn Plan>,
+    join_field1: String,
+    join_field2: String,
+    schema: Schema,
+}
+
+impl HashJoinPlan {
+    /// Implement the equijoin of the two plans on the specified fields, the first of the LHS and the second of the RHS.
+    pub fn new(
+        tx: Arc<Mutex<Transaction>>,
+        plan1: Box<dyn Plan>,
+        plan2: Box<dyn Plan>,
+        join_field1: &str,
+        join_field2: &str,
+    ) -> Self {
+        let mut schema = Schema::new();
+        schema.add_all(plan1.schema());
+        schema.add_all(plan2.schema());
+        Self {
+            tx,
+            plan1,
+            plan2,
+            join_field1: join_field1.to_string(),
+            join_field2: join_field2.to_string(),
+            schema,
+        }
+    }
+
+    /// Return the number of partitions to split the inputs into, or 1 if the RHS fits in the available buffers.
+    /// Writing the partitions pins a buffer for each of them, so there are at most half as many partitions
+    /// as available buffers, leaving the rest to the other scans of the query.
+    fn num_partitions(&self) -> usize {
+        let available = self.tx.lock().unwrap().available_buffs();
+        let blocks2 = self.materialized_blocks(self.plan2.as_ref());
+        if blocks2 <= available || available < 4 {
+            return 1;
+        }
+        blocks2.div_ceil(available).min(available / 2)
+    }
+
+    fn materialized_blocks(&self, plan: &dyn Plan) -> usize {
+        MaterializePlan::estimate_blocks(&self.tx, plan.schema(), plan.records_output())
+    }
+
+    /// Copy the output of the plan into the specified number of temporary tables,
+    /// choosing the table of each record by the hash of its join value.
+    fn partition(&self, plan: &dyn Plan, join_field: &str, n: usize) -> Result<Vec<TempTable>> {
+        let schema = plan.schema();
+        let temps: Vec<TempTable> = (0..n)
+            .map(|_| TempTable::new(Arc::clone(&self.tx), schema.clone()))
+            .collect();
+        let mut src = plan.open()?;
+        let mut dests = temps
+            .iter()
+            .map(TempTable::open)
+            .collect::<Result<Vec<_>>>()?;
+        while src.next()? {
+            let dest = &mut dests[partition_of(&src.get_val(join_field)?, n)];
+            dest.insert()?;
+            for field_name in schema.fields() {
+                dest.set_val(field_name, &src.get_val(field_name)?)?;
+            }
+        }
+        src.close()?;
+        for dest in dests.iter_mut() {
+            dest.close()?;
+        }
+        Ok(temps)
+    }
+}
+
+/// Return the partition of the specified join value, among n partitions.
+fn partition_of(val: &Constant, n: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    val.hash(&mut hasher);
+    (hasher.finish() % n as u64) as usize
+}
+
+impl Plan for HashJoinPlan {
+    fn open(&self) -> Result<Box<dyn Scan>> {
+        let fields2 = self.plan2.schema().fields();
+        let n = self.num_partitions();
+        if n == 1 {
+            let scan1 = self.plan1.open()?;
+            let scan2 = self.plan2.open()?;
+            return Ok(Box::new(HashJoinScan::new(
+                scan1,
+                scan2,
+                &self.join_field1,
+                &self.join_field2,
+                fields2,
+            )?));
+        }
+
+        let partitions1 = self.partition(self.plan1.as_ref(), &self.join_field1, n)?;
+        let partitions2 = self.partition(self.plan2.as_ref(), &self.join_field2, n)?;
+        Ok(Box::new(HashJoinScan::with_partitions(
+            partitions1.into_iter().zip(partitions2).collect(),
+            &self.join_field1,
+            &self.join_field2,
+            fields2,
+        )?))
+    }
+
+    /// If the RHS fits in memory, then each input is read once, so B(hashjoin(p1,p2)) = B(p1) + B(p2).
+    /// Otherwise, the inputs are also written to the partitions and read back,
+    /// so B(hashjoin(p1,p2)) = B(p1) + B(p2) + 2*(B(partitions(p1)) + B(partitions(p2))).
+    fn blocks_accessed(&self) -> usize {
+        let blocks = self.plan1.blocks_accessed() + self.plan2.blocks_accessed();
+        if self.num_partitions() == 1 {
+            return blocks;
+        }
+        let partition_blocks = self.materialized_blocks(self.plan1.as_ref())
+            + self.materialized_blocks(self.plan2.as_ref());
+        blocks + 2 * partition_blocks
+    }
+
+    /// Estimate the number of output records as R(p1)*R(p2)/max(V(p1,F1),V(p2,F2)),
+    /// the same as selecting the product on the join term.
+    fn records_output(&self) -> usize {
+        let max_vals = self
+            .plan1
+            .distinct_values(&self.join_field1)
+            .max(self.plan2.distinct_values(&self.join_field2))
+            .max(1);
+        self.plan1.records_output() * self.plan2.records_output() / max_vals
+    }
+
+    fn distinct_values(&self, field_name: &str) -> usize {
+        if self.plan1.schema().has_field(field_name) {
+            self.plan1.distinct_values(field_name)
+        } else {
+            self.plan2.distinct_values(field_name)
+        }
+    }
+
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+
+    use crate::{
+        plan::{merge_join_plan::MergeJoinPlan, plan::Plan, table_plan::TablePlan},
+        query::scan::{Scan, UpdateScan},
+        record::table_scan::TableScan,
+        server::simpledb::SimpleDB,
+    };
+
+    use super::HashJoinPlan;
+
+    #[test]
+    fn test_join_plans() {
+        let temp_dir = tempdir().unwrap();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+        let planner = db.planner();
+        let tx = db.new_tx().unwrap();
+
+        // the RHS takes many more blocks than there are buffers
+        planner
+            .execute_update("create table T1(A int)", Arc::clone(&tx))
+            .unwrap();
+        planner
+            .execute_update("create table T2(C int, D varchar(9))", Arc::clone(&tx))
+            .unwrap();
+        let layout1 = db
+            .metadata_manager()
+            .get_layout("t1", Arc::clone(&tx))
+            .unwrap();
+        let mut scan = TableScan::new(Arc::clone(&tx), "t1", layout1).unwrap();
+        for i in 0..100 {
+            scan.insert().unwrap();
+            scan.set_int("a", i).unwrap();
+        }
+        scan.close().unwrap();
+        // the values of C are multiples of 5, each appearing four times
+        let layout2 = db
+            .metadata_manager()
+            .get_layout("t2", Arc::clone(&tx))
+            .unwrap();
+        let mut scan = TableScan::new(Arc::clone(&tx), "t2", layout2).unwrap();
+        for i in 0..1000 {
+            scan.insert().unwrap();
+            scan.set_int("c", (i % 250) * 5).unwrap();
+            scan.set_string("d", &format!("d{}", i)).unwrap();
+        }
+        scan.close().unwrap();
+        let table_plan = |table_name: &str| {
+            Box::new(TablePlan::new(Arc::clone(&tx), table_name, &db.metadata_manager()).unwrap())
+        };
+
+        let hash_join = HashJoinPlan::new(
+            Arc::clone(&tx),
+            table_plan("t1"),
+            table_plan("t2"),
+            "a",
+            "c",
+        );
+        assert!(hash_join.num_partitions() > 1);
+        let table_blocks = table_plan("t1").blocks_accessed() + table_plan("t2").blocks_accessed();
+        assert!(hash_join.blocks_accessed() > table_blocks);
+        let merge_join = MergeJoinPlan::new(
+            Arc::clone(&tx),
+            table_plan("t1"),
+            table_plan("t2"),
+            "a",
+            "c",
+        )
+        .unwrap();
+        assert!(merge_join.blocks_accessed() > table_blocks);
+        assert!(MergeJoinPlan::new(
+            Arc::clone(&tx),
+            table_plan("t1"),
+            table_plan("t2"),
+            "x",
+            "c"
+        )
+        .is_err());
+
+        let plans: [Box<dyn Plan>; 2] = [Box::new(hash_join), Box::new(merge_join)];
+        for plan in plans {
+            let mut scan = plan.open().unwrap();
+            for _ in 0..2 {
+                let mut rows = vec![];
+                while scan.next().unwrap() {
+                    let a = scan.get_int("a").unwrap();
+                    assert_eq!(scan.get_int("c").unwrap(), a);
+                    rows.push((a, scan.get_string("d").unwrap()));
+                }
+                rows.sort();
+                assert_eq!(rows.len(), 80);
+                assert!(rows.iter().all(|(a, _)| a % 5 == 0));
+                assert_eq!(rows[..4].iter().map(|(a, _)| *a).sum::<i32>(), 0);
+                assert_eq!(
+                    rows[76..]
+                        .iter()
+                        .map(|(_, d)| d.as_str())
+                        .collect::<Vec<_>>(),
+                    ["d19", "d269", "d519", "d769"]
+                );
+                scan.before_first().unwrap();
+            }
+            scan.close().unwrap();
+        }
+
+        tx.lock().unwrap().commit().unwrap();
+    }
+}
@@ -0,0 +1,253 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    metadata::manager::MetadataManager,
+    parse::{parser::Parser, query_data::QueryData},
+    record::schema::Schema,
+    tx::transaction::Transaction,
+};
+
+use super::{
+    group_by_plan::GroupByPlan, plan::Plan, project_plan::ProjectPlan, query_planner::QueryPlanner,
+    sort_plan::SortPlan, table_planner::TablePlanner,
+};
+
+#[derive(Debug)]
+enum HeuristicQueryPlannerError {
+    NoTables,
+    UnknownTerm(String),
+}
+
+impl std::error::Error for HeuristicQueryPlannerError {}
+impl fmt::Display for HeuristicQueryPlannerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HeuristicQueryPlannerError::NoTables => write!(f, "query mentions no tables"),
+            HeuristicQueryPlannerError::UnknownTerm(term) => {
+                write!(f, "term {} refers to a field not in the query", term)
+            }
+        }
+    }
+}
+
+/// A query planner that optimizes using a heuristic-based algorithm.
+/// Selections are pushed down to the tables, and the tables are joined greedily: the join order starts with the
+/// table whose selection outputs the fewest records, and then adds the table whose join with the plan so far
+/// outputs the fewest records, preferring tables that are joined by the predicate over products.
+pub struct HeuristicQueryPlanner {
+    metadata_manager: Arc<MetadataManager>,
+}
+
+impl HeuristicQueryPlanner {
+    pub fn new(metadata_manager: Arc<MetadataManager>) -> Self {
+        Self { metadata_manager }
+    }
+
+    fn lowest_select_plan(planners: &mut Vec<TablePlanner>) -> Option<Box<dyn Plan>> {
+        let (i, plan) = planners
+            .iter()
+            .map(|planner| planner.make_select_plan())
+            .enumerate()
+            .min_by_key(|(_, plan)| plan.records_output())?;
+        planners.remove(i);
+        Some(plan)
+    }
+
+    fn lowest_join_plan(
+        planners: &mut Vec<TablePlanner>,
+        current: &Arc<dyn Plan>,
+    ) -> Option<Box<dyn Plan>> {
+        let (i, plan) = planners
+            .iter()
+            .enumerate()
+            .filter_map(|(i, planner)| Some((i, planner.make_join_plan(current)?)))
+            .min_by_key(|(_, plan)| plan.records_output())?;
+        planners.remove(i);
+        Some(plan)
+    }
+
+    fn lowest_product_plan(
+        planners: &mut Vec<TablePlanner>,
+        current: &Arc<dyn Plan>,
+    ) -> Option<Box<dyn Plan>> {
+        let (i, plan) = planners
+            .iter()
+            .map(|planner| planner.make_product_plan(current))
+            .enumerate()
+            .min_by_key(|(_, plan)| plan.records_output())?;
+        planners.remove(i);
+        Some(plan)
+    }
+}
+
+impl QueryPlanner for HeuristicQueryPlanner {
+    /// Create an optimized left-deep query plan using the following heuristics.
+    /// H1. Choose the smallest table (considering selection predicates) to be first in the join order.
+    /// H2. Add the table to the join order which results in the smallest output.
+    fn create_plan(&self, data: &QueryData, tx: Arc<Mutex<Transaction>>) -> Result<Box<dyn Plan>> {
+        // Step 1: Create a TablePlanner object for each mentioned table or view.
+        let mut planners = vec![];
+        for table_name in data.tables() {
+            let planner = match self
+                .metadata_manager
+                .get_view_def(table_name, Arc::clone(&tx))?
+            {
+                Some(view_def) => {
+                    let view_data = Parser::new(&view_def)?.query()?;
+                    let plan = self.create_plan(&view_data, Arc::clone(&tx))?;
+                    TablePlanner::for_view(plan, data.pred(), Arc::clone(&tx))
+                }
+                None => TablePlanner::for_table(
+                    table_name,
+                    data.pred(),
+                    &self.metadata_manager,
+                    Arc::clone(&tx),
+                )?,
+            };
+            planners.push(planner);
+        }
+
+        // Every term must be placed on some selection or join.
+        let mut schema = Schema::new();
+        for planner in &planners {
+            schema.add_all(planner.schema());
+        }
+        if let Some(term) = data
+            .pred()
+            .terms()
+            .iter()
+            .find(|term| !term.applies_to(&schema))
+        {
+            return Err(HeuristicQueryPlannerError::UnknownTerm(term.to_string()).into());
+        }
+
+        // Step 2: Choose the lowest-size plan to begin the join order.
+        let mut current: Arc<dyn Plan> = Arc::from(
+            Self::lowest_select_plan(&mut planners).ok_or(HeuristicQueryPlannerError::NoTables)?,
+        );
+
+        // Step 3: Repeatedly add a plan to the join order.
+        while !planners.is_empty() {
+            let plan = match Self::lowest_join_plan(&mut planners, &current) {
+                Some(plan) => plan,
+                None => Self::lowest_product_plan(&mut planners, &current)
+                    .ok_or(HeuristicQueryPlannerError::NoTables)?,
+            };
+            current = Arc::from(plan);
+        }
+
+        // Step 4: Group and aggregate the records, if required.
+        let mut plan: Box<dyn Plan> = Box::new(current);
+        if data.is_grouped() {
+            plan = Box::new(GroupByPlan::new(
+                Arc::clone(&tx),
+                plan,
+                data.group_by(),
+                data.aggregations(),
+            )?);
+        }
+
+        // Step 5: Sort on the order by fields, if any.
+        if !data.order_by().is_empty() {
+            plan = Box::new(SortPlan::new(tx, plan, data.order_by())?);
+        }
+
+        // Step 6: Project on the output fields.
+        Ok(Box::new(ProjectPlan::new(plan, &data.output_fields())?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+
+    use crate::{plan::planner::Planner, server::simpledb::SimpleDB};
+
+    use super::HeuristicQueryPlanner;
+
+    fn rows(planner: &Planner, sql: &str, db: &SimpleDB) -> Vec<Vec<String>> {
+        let tx = db.new_tx().unwrap();
+        let plan = planner.create_query_plan(sql, Arc::clone(&tx)).unwrap();
+        let fields = plan.schema().fields().to_vec();
+        let mut scan = plan.open().unwrap();
+        let mut rows = vec![];
+        while scan.next().unwrap() {
+            rows.push(
+                fields
+                    .iter()
+                    .map(|field_name| scan.get_val(field_name).unwrap().to_string())
+                    .collect(),
+            );
+        }
+        scan.close().unwrap();
+        tx.lock().unwrap().commit().unwrap();
+        rows.sort();
+        rows
+    }
+
+    #[test]
+    fn test_heuristic_query_planner() {
+        let temp_dir = tempdir().unwrap();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+        let basic = db.planner();
+
+        let tx = db.new_tx().unwrap();
+        let update = |sql: &str| basic.execute_update(sql, Arc::clone(&tx)).unwrap();
+        update("create table student(sid int, sname varchar(10), majorid int)");
+        update("create table dept(did int, dname varchar(10))");
+        update("create table enroll(studentid int, grade varchar(2))");
+        update("create index sididx on student(sid) using btree");
+        update("create index studentidx on enroll(studentid)");
+        for i in 0..10 {
+            update(&format!(
+                "insert into dept(did, dname) values ({}, 'dept{}')",
+                i, i
+            ));
+        }
+        for i in 0..60 {
+            update(&format!(
+                "insert into student(sid, sname, majorid) values ({}, 's{}', {})",
+                i,
+                i,
+                i % 10
+            ));
+            update(&format!(
+                "insert into enroll(studentid, grade) values ({}, '{}')",
+                i,
+                if i % 2 == 0 { "A" } else { "B" }
+            ));
+        }
+        update("create view math as select sid, sname from student, dept where majorid = did and dname = 'dept3'");
+        tx.lock().unwrap().commit().unwrap();
+
+        let metadata_manager = db.metadata_manager();
+        let db = db.with_query_planner(Box::new(HeuristicQueryPlanner::new(metadata_manager)));
+        let heuristic = db.planner();
+
+        // the heuristic planner answers queries the same way as the basic planner
+        for sql in [
+            "select sname, dname from student, dept where majorid = did and sid < 25",
+            "select sname, grade from enroll, student, dept where sid = studentid and majorid = did and dname = 'dept4'",
+            "select sname, grade from enroll, student where sid = studentid and sid = 7",
+            "select dname, grade from dept, enroll where did = 2",
+            "select sname, grade from math, enroll where sid = studentid and grade = 'B'",
+        ] {
+            let expected = rows(&basic, sql, &db);
+            assert!(!expected.is_empty(), "{}", sql);
+            assert_eq!(rows(&heuristic, sql, &db), expected, "{}", sql);
+        }
+
+        let tx = db.new_tx().unwrap();
+        assert!(heuristic
+            .create_query_plan(
+                "select sname from student where nosuchfield = 1",
+                Arc::clone(&tx)
+            )
+            .is_err());
+        tx.lock().unwrap().commit().unwrap();
+    }
+}
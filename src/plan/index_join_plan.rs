@@ -0,0 +1,98 @@
+use anyhow::{Ok, Result};
+use std::collections::HashMap;
+
+use crate::{
+    metadata::index_manager::IndexInfo,
+    query::{index_join_scan::IndexJoinScan, predicate::Predicate, scan::Scan},
+    record::schema::Schema,
+};
+
+use super::{plan::Plan, table_plan::TablePlan};
+
+/// The plan class corresponding to the index join relational algebra operator.
+pub struct IndexJoinPlan {
+    plan1: Box<dyn Plan>,
+    plan2: TablePlan,
+    index_info: IndexInfo,
+    join_field: String,
+    schema: Schema,
+}
+
+impl IndexJoinPlan {
+    /// Implement the join operator, using the specified LHS plan, the RHS table and an index on the RHS table.
+    /// The join field is the LHS field whose values are looked up in the index.
+    pub fn new(
+        plan1: Box<dyn Plan>,
+        plan2: TablePlan,
+        index_info: IndexInfo,
+        join_field: &str,
+    ) -> Self {
+        let mut schema = Schema::new();
+        schema.add_all(plan1.schema());
+        schema.add_all(plan2.schema());
+        Self {
+            plan1,
+            plan2,
+            index_info,
+            join_field: join_field.to_string(),
+            schema,
+        }
+    }
+
+    /// Find an index among the indexes of a table that can join the table to a plan having the specified schema,
+    /// because the predicate equates the indexed field with a field of the plan.
+    /// Return the index together with the plan's join field.
+    pub fn find_join<'a>(
+        indexes: &'a HashMap<String, IndexInfo>,
+        pred: &Predicate,
+        schema: &Schema,
+    ) -> Option<(&'a IndexInfo, String)> {
+        let mut fields: Vec<&String> = indexes.keys().collect();
+        fields.sort();
+        fields.into_iter().find_map(|field_name| {
+            let join_field = pred.equates_with_field(field_name)?;
+            schema
+                .has_field(join_field)
+                .then(|| (&indexes[field_name], join_field.to_string()))
+        })
+    }
+}
+
+impl Plan for IndexJoinPlan {
+    fn open(&self) -> Result<Box<dyn Scan>> {
+        let scan = self.plan1.open()?;
+        let table_scan = self.plan2.open_table_scan()?;
+        let index = self.index_info.open()?;
+        Ok(Box::new(IndexJoinScan::new(
+            scan,
+            index,
+            &self.join_field,
+            table_scan,
+        )?))
+    }
+
+    /// Estimate the number of block accesses to compute the join as
+    /// B(indexjoin(p1,p2,idx)) = B(p1) + R(p1)*B(idx) + R(indexjoin(p1,p2,idx)).
+    fn blocks_accessed(&self) -> usize {
+        self.plan1.blocks_accessed()
+            + self.plan1.records_output() * self.index_info.blocks_accessed()
+            + self.records_output()
+    }
+
+    /// Estimate the number of output records as R(indexjoin(p1,p2,idx)) = R(p1)*R(idx).
+    fn records_output(&self) -> usize {
+        self.plan1.records_output() * self.index_info.records_output()
+    }
+
+    fn distinct_values(&self, field_name: &str) -> usize {
+        if self.plan1.schema().has_field(field_name) {
+            self.plan1.distinct_values(field_name)
+        } else {
+            self.plan2.distinct_values(field_name)
+        }
+    }
+
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+}
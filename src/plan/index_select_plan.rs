@@ -0,0 +1,98 @@
+use anyhow::{Ok, Result};
+use std::collections::HashMap;
+
+use crate::{
+    index::index::IndexType,
+    metadata::index_manager::IndexInfo,
+    query::{
+        index_select_scan::{IndexSearch, IndexSelectScan},
+        predicate::Predicate,
+        scan::Scan,
+        term::RANGE_REDUCTION_FACTOR,
+    },
+    record::schema::Schema,
+};
+
+use super::{plan::Plan, table_plan::TablePlan};
+
+/// The plan class corresponding to the index select relational algebra operator.
+pub struct IndexSelectPlan {
+    plan: TablePlan,
+    index_info: IndexInfo,
+    search: IndexSearch,
+}
+
+impl IndexSelectPlan {
+    /// Create a new index select node in the query tree for the specified index and search values.
+    pub fn new(plan: TablePlan, index_info: IndexInfo, search: IndexSearch) -> Self {
+        Self {
+            plan,
+            index_info,
+            search,
+        }
+    }
+
+    /// Find an index among the indexes of a table that can search for the records satisfying the predicate.
+    /// An equality on an indexed field is preferred; otherwise a B-tree index can search a range.
+    pub fn find_search<'a>(
+        indexes: &'a HashMap<String, IndexInfo>,
+        pred: &Predicate,
+    ) -> Option<(&'a IndexInfo, IndexSearch)> {
+        let mut fields: Vec<&String> = indexes.keys().collect();
+        fields.sort();
+        let equality = fields.iter().find_map(|field_name| {
+            let val = pred.equates_with_constant(field_name)?;
+            Some((&indexes[*field_name], IndexSearch::Eq(val.clone())))
+        });
+        equality.or_else(|| {
+            fields.iter().find_map(|field_name| {
+                let index_info = &indexes[*field_name];
+                if index_info.index_type() != IndexType::BTree {
+                    return None;
+                }
+                let (lo, hi) = pred.range_for(field_name)?;
+                Some((index_info, IndexSearch::Range(lo, hi)))
+            })
+        })
+    }
+}
+
+impl Plan for IndexSelectPlan {
+    fn open(&self) -> Result<Box<dyn Scan>> {
+        let table_scan = self.plan.open_table_scan()?;
+        let index = self.index_info.open()?;
+        Ok(Box::new(IndexSelectScan::new(
+            table_scan,
+            index,
+            self.search.clone(),
+        )?))
+    }
+
+    /// Estimate the number of block accesses to compute the index selection,
+    /// which is the same as the index traversal cost plus the number of matching data records.
+    fn blocks_accessed(&self) -> usize {
+        self.index_info.blocks_accessed() + self.records_output()
+    }
+
+    /// Estimate the number of output records in the index selection.
+    /// An equality search outputs the number of records per search key value of the index;
+    /// a range search is assumed to output the same share of the table as a range term.
+    fn records_output(&self) -> usize {
+        match self.search {
+            IndexSearch::Eq(_) => self.index_info.records_output(),
+            IndexSearch::Range(_, _) => self.plan.records_output() / RANGE_REDUCTION_FACTOR,
+        }
+    }
+
+    /// Return the distinct values as defined by the index.
+    fn distinct_values(&self, field_name: &str) -> usize {
+        match self.search {
+            IndexSearch::Eq(_) => self.index_info.distinct_values(field_name),
+            IndexSearch::Range(_, _) => self.plan.distinct_values(field_name),
+        }
+    }
+
+    fn schema(&self) -> &Schema {
+        self.plan.schema()
+    }
+}
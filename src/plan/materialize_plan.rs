@@ -0,0 +1,84 @@
+use anyhow::{Ok, Result};
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    query::scan::{Scan, UpdateScan},
+    record::{layout::Layout, schema::Schema, temp_table::TempTable},
+    tx::transaction::Transaction,
+};
+
+use super::plan::Plan;
+
+/// The plan class for the materialize operator.
+/// Opening the plan copies the output of the underlying query into a temporary table,
+/// and returns a scan of that table.
+pub struct MaterializePlan {
+    tx: Arc<Mutex<Transaction>>,
+    plan: Box<dyn Plan>,
+}
+
+impl MaterializePlan {
+    pub fn new(tx: Arc<Mutex<Transaction>>, plan: Box<dyn Plan>) -> Self {
+        Self { tx, plan }
+    }
+
+    /// Return the number of records of the schema that fit in a block.
+    pub fn records_per_block(tx: &Arc<Mutex<Transaction>>, schema: &Schema) -> usize {
+        let layout = Layout::new(schema.clone());
+        (tx.lock().unwrap().block_size() / layout.slot_size()).max(1)
+    }
+
+    /// Estimate the number of blocks of a temporary table holding the specified number of records of the schema.
+    pub fn estimate_blocks(
+        tx: &Arc<Mutex<Transaction>>,
+        schema: &Schema,
+        num_records: usize,
+    ) -> usize {
+        num_records.div_ceil(Self::records_per_block(tx, schema))
+    }
+
+    /// Return the estimated one-time cost of materializing: reading the input and writing the temporary table.
+    pub fn materialize_cost(&self) -> usize {
+        self.plan.blocks_accessed() + self.blocks_accessed()
+    }
+
+    /// Copy the output of the underlying query into a new temporary table.
+    pub fn materialize(&self) -> Result<TempTable> {
+        let temp = TempTable::new(Arc::clone(&self.tx), self.plan.schema().clone());
+        let mut src = self.plan.open()?;
+        let mut dest = temp.open()?;
+        while src.next()? {
+            dest.insert()?;
+            for field_name in self.plan.schema().fields() {
+                dest.set_val(field_name, &src.get_val(field_name)?)?;
+            }
+        }
+        src.close()?;
+        dest.close()?;
+        Ok(temp)
+    }
+}
+
+impl Plan for MaterializePlan {
+    fn open(&self) -> Result<Box<dyn Scan>> {
+        Ok(Box::new(self.materialize()?.open()?))
+    }
+
+    /// Return the estimated number of blocks in the materialized table.
+    /// It does not include the one-time cost of materializing the records.
+    fn blocks_accessed(&self) -> usize {
+        Self::estimate_blocks(&self.tx, self.plan.schema(), self.plan.records_output())
+    }
+
+    fn records_output(&self) -> usize {
+        self.plan.records_output()
+    }
+
+    fn distinct_values(&self, field_name: &str) -> usize {
+        self.plan.distinct_values(field_name)
+    }
+
+    fn schema(&self) -> &Schema {
+        self.plan.schema()
+    }
+}
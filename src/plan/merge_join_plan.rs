@@ -0,0 +1,92 @@
+use anyhow::{Ok, Result};
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    query::{merge_join_scan::MergeJoinScan, scan::Scan},
+    record::schema::Schema,
+    tx::transaction::Transaction,
+};
+
+use super::{plan::Plan, sort_plan::SortPlan};
+
+/// The plan class for the mergejoin operator.
+/// Both inputs are sorted on their join fields, and the sorted outputs are merged.
+pub struct MergeJoinPlan {
+    plan1: SortPlan,
+    plan2: SortPlan,
+    join_field1: String,
+    join_field2: String,
+    schema: Schema,
+}
+
+impl MergeJoinPlan {
+    /// Implement the equijoin of the two plans on the specified fields, the first of the LHS and the second of the RHS.
+    /// Fail if a join field is not produced by its plan.
+    pub fn new(
+        tx: Arc<Mutex<Transaction>>,
+        plan1: Box<dyn Plan>,
+        plan2: Box<dyn Plan>,
+        join_field1: &str,
+        join_field2: &str,
+    ) -> Result<Self> {
+        let plan1 = SortPlan::new(Arc::clone(&tx), plan1, &[join_field1.to_string()])?;
+        let plan2 = SortPlan::new(tx, plan2, &[join_field2.to_string()])?;
+        let mut schema = Schema::new();
+        schema.add_all(plan1.schema());
+        schema.add_all(plan2.schema());
+        Ok(Self {
+            plan1,
+            plan2,
+            join_field1: join_field1.to_string(),
+            join_field2: join_field2.to_string(),
+            schema,
+        })
+    }
+}
+
+impl Plan for MergeJoinPlan {
+    /// Sort both inputs, and open a mergejoin scan over the sorted outputs.
+    fn open(&self) -> Result<Box<dyn Scan>> {
+        let scan1 = self.plan1.open()?;
+        let scan2 = self.plan2.open_sort_scan()?;
+        Ok(Box::new(MergeJoinScan::new(
+            scan1,
+            scan2,
+            &self.join_field1,
+            &self.join_field2,
+        )?))
+    }
+
+    /// Return the cost of sorting both inputs, plus one pass through each sorted output.
+    /// Unlike the sort plan itself, the sorting is counted, so that the cost compares with the other joins.
+    /// B(mergejoin(p1,p2)) = sortcost(p1) + sortcost(p2) + B(sort(p1)) + B(sort(p2)).
+    fn blocks_accessed(&self) -> usize {
+        self.plan1.sort_cost()
+            + self.plan2.sort_cost()
+            + self.plan1.blocks_accessed()
+            + self.plan2.blocks_accessed()
+    }
+
+    /// Estimate the number of output records as R(p1)*R(p2)/max(V(p1,F1),V(p2,F2)),
+    /// the same as selecting the product on the join term.
+    fn records_output(&self) -> usize {
+        let max_vals = self
+            .plan1
+            .distinct_values(&self.join_field1)
+            .max(self.plan2.distinct_values(&self.join_field2))
+            .max(1);
+        self.plan1.records_output() * self.plan2.records_output() / max_vals
+    }
+
+    fn distinct_values(&self, field_name: &str) -> usize {
+        if self.plan1.schema().has_field(field_name) {
+            self.plan1.distinct_values(field_name)
+        } else {
+            self.plan2.distinct_values(field_name)
+        }
+    }
+
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+}
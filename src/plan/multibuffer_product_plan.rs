@@ -0,0 +1,172 @@
+use anyhow::{Ok, Result};
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    buffer::needs::best_factor,
+    query::{multibuffer_product_scan::MultibufferProductScan, scan::Scan},
+    record::schema::Schema,
+    tx::transaction::Transaction,
+};
+
+use super::{materialize_plan::MaterializePlan, plan::Plan};
+
+/// The plan class for the multi-buffer version of the product operator.
+/// Both inputs are materialized. The RHS table is then read in chunks of as many blocks as the available buffers
+/// allow, and the LHS table is read once per chunk, which gives a block nested-loop join when the product is selected
+/// on a join predicate.
+pub struct MultibufferProductPlan {
+    tx: Arc<Mutex<Transaction>>,
+    lhs: MaterializePlan,
+    rhs: MaterializePlan,
+    schema: Schema,
+}
+
+impl MultibufferProductPlan {
+    pub fn new(tx: Arc<Mutex<Transaction>>, plan1: Box<dyn Plan>, plan2: Box<dyn Plan>) -> Self {
+        let mut schema = Schema::new();
+        schema.add_all(plan1.schema());
+        schema.add_all(plan2.schema());
+        Self {
+            lhs: MaterializePlan::new(Arc::clone(&tx), plan1),
+            rhs: MaterializePlan::new(Arc::clone(&tx), plan2),
+            tx,
+            schema,
+        }
+    }
+}
+
+impl Plan for MultibufferProductPlan {
+    /// Materialize both inputs, and open a multi-buffer product scan over the temporary tables.
+    fn open(&self) -> Result<Box<dyn Scan>> {
+        let scan1 = self.lhs.open()?;
+        let rhs = self.rhs.materialize()?;
+        Ok(Box::new(MultibufferProductScan::new(
+            Arc::clone(&self.tx),
+            scan1,
+            rhs,
+        )?))
+    }
+
+    /// Estimate the block accesses of materializing both inputs, reading the RHS chunks once,
+    /// and reading the LHS table once per chunk:
+    /// B(mbproduct(p1,p2)) = matcost(p1) + matcost(p2) + B(mat(p2)) + B(mat(p1))*C, where C is the number of chunks.
+    /// Unlike the materialize plan itself, the materialization is counted, so that the cost compares with the
+    /// other products and joins.
+    fn blocks_accessed(&self) -> usize {
+        let available = self.tx.lock().unwrap().available_buffs();
+        let rhs_blocks = self.rhs.blocks_accessed();
+        let num_chunks = rhs_blocks.div_ceil(best_factor(available, rhs_blocks));
+        self.lhs.materialize_cost()
+            + self.rhs.materialize_cost()
+            + rhs_blocks
+            + self.lhs.blocks_accessed() * num_chunks
+    }
+
+    /// Estimate the number of output records as R(product(p1,p2)) = R(p1)*R(p2).
+    fn records_output(&self) -> usize {
+        self.lhs.records_output() * self.rhs.records_output()
+    }
+
+    fn distinct_values(&self, field_name: &str) -> usize {
+        if self.lhs.schema().has_field(field_name) {
+            self.lhs.distinct_values(field_name)
+        } else {
+            self.rhs.distinct_values(field_name)
+        }
+    }
+
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+
+    use crate::{
+        plan::{plan::Plan, product_plan::ProductPlan, table_plan::TablePlan},
+        query::scan::{Scan, UpdateScan},
+        record::table_scan::TableScan,
+        server::simpledb::SimpleDB,
+    };
+
+    use super::MultibufferProductPlan;
+
+    #[test]
+    fn test_multibuffer_product_plan() {
+        let temp_dir = tempdir().unwrap();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+        let planner = db.planner();
+        let tx = db.new_tx().unwrap();
+
+        // the RHS takes more blocks than there are buffers
+        planner
+            .execute_update("create table T1(A int)", Arc::clone(&tx))
+            .unwrap();
+        planner
+            .execute_update("create table T2(B int, C varchar(9))", Arc::clone(&tx))
+            .unwrap();
+        let layout1 = db
+            .metadata_manager()
+            .get_layout("t1", Arc::clone(&tx))
+            .unwrap();
+        let mut scan = TableScan::new(Arc::clone(&tx), "t1", layout1).unwrap();
+        for i in 0..30 {
+            scan.insert().unwrap();
+            scan.set_int("a", i).unwrap();
+        }
+        scan.close().unwrap();
+        let layout2 = db
+            .metadata_manager()
+            .get_layout("t2", Arc::clone(&tx))
+            .unwrap();
+        let mut scan = TableScan::new(Arc::clone(&tx), "t2", layout2).unwrap();
+        for i in 0..400 {
+            scan.insert().unwrap();
+            scan.set_int("b", i).unwrap();
+            scan.set_string("c", &format!("c{}", i)).unwrap();
+        }
+        scan.close().unwrap();
+        let table_plan = |table_name: &str| {
+            Box::new(TablePlan::new(Arc::clone(&tx), table_name, &db.metadata_manager()).unwrap())
+        };
+
+        let product = ProductPlan::new(table_plan("t1"), table_plan("t2"));
+        let mb_product =
+            MultibufferProductPlan::new(Arc::clone(&tx), table_plan("t1"), table_plan("t2"));
+        assert_eq!(mb_product.records_output(), product.records_output());
+        assert!(mb_product.blocks_accessed() < product.blocks_accessed());
+
+        // count the pairs and the blocks read from disk for each plan
+        let file_manager = db.file_manager();
+        let mut results = vec![];
+        let plans: [Box<dyn Plan>; 2] = [Box::new(product), Box::new(mb_product)];
+        for plan in plans {
+            let blocks_read = file_manager.get_total_blocks_read();
+            let mut scan = plan.open().unwrap();
+            let mut count = 0;
+            let mut sum = 0;
+            while scan.next().unwrap() {
+                count += 1;
+                sum += scan.get_int("a").unwrap() * scan.get_int("b").unwrap();
+                assert_eq!(
+                    scan.get_string("c").unwrap(),
+                    format!("c{}", scan.get_int("b").unwrap())
+                );
+            }
+            scan.close().unwrap();
+            let blocks_read = file_manager.get_total_blocks_read() - blocks_read;
+            results.push((count, sum, blocks_read));
+        }
+        assert_eq!(results[0].0, 30 * 400);
+        assert_eq!(results[0].1, (0..30).sum::<i32>() * (0..400).sum::<i32>());
+        assert_eq!(results[1].0, results[0].0);
+        assert_eq!(results[1].1, results[0].1);
+        assert!(results[1].2 * 5 < results[0].2);
+
+        tx.lock().unwrap().commit().unwrap();
+    }
+}
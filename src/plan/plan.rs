@@ -0,0 +1,47 @@
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::{query::scan::Scan, record::schema::Schema};
+
+/// The interface implemented by each query plan.
+/// There is a plan for each relational algebra operator.
+/// A plan estimates the cost of its query, so that a planner can compare alternatives without executing them.
+pub trait Plan {
+    /// Open a scan corresponding to this plan.
+    fn open(&self) -> Result<Box<dyn Scan>>;
+
+    /// Return an estimate of the number of block accesses that will occur when the scan is read to completion.
+    fn blocks_accessed(&self) -> usize;
+
+    /// Return an estimate of the number of records in the query's output table.
+    fn records_output(&self) -> usize;
+
+    /// Return an estimate of the number of distinct values for the specified field in the query's output table.
+    fn distinct_values(&self, field_name: &str) -> usize;
+
+    /// Return the schema of the query.
+    fn schema(&self) -> &Schema;
+}
+
+/// A shared plan is a plan, so that a planner can try out several alternatives built on the same subplan.
+impl<P: Plan + ?Sized> Plan for Arc<P> {
+    fn open(&self) -> Result<Box<dyn Scan>> {
+        (**self).open()
+    }
+
+    fn blocks_accessed(&self) -> usize {
+        (**self).blocks_accessed()
+    }
+
+    fn records_output(&self) -> usize {
+        (**self).records_output()
+    }
+
+    fn distinct_values(&self, field_name: &str) -> usize {
+        (**self).distinct_values(field_name)
+    }
+
+    fn schema(&self) -> &Schema {
+        (**self).schema()
+    }
+}
@@ -0,0 +1,219 @@
+use anyhow::Result;
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    parse::parser::{Parser, UpdateCommand},
+    tx::transaction::Transaction,
+};
+
+use super::{plan::Plan, query_planner::QueryPlanner, update_planner::UpdatePlanner};
+
+/// The object that executes SQL statements.
+/// Queries are turned into plans by the query planner, and update statements are executed by the update planner.
+pub struct Planner {
+    query_planner: Box<dyn QueryPlanner>,
+    update_planner: Box<dyn UpdatePlanner>,
+}
+
+impl Planner {
+    pub fn new(
+        query_planner: Box<dyn QueryPlanner>,
+        update_planner: Box<dyn UpdatePlanner>,
+    ) -> Self {
+        Self {
+            query_planner,
+            update_planner,
+        }
+    }
+
+    /// Create a plan for an SQL select statement, using the supplied planner.
+    pub fn create_query_plan(
+        &self,
+        sql: &str,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Box<dyn Plan>> {
+        let data = Parser::new(sql)?.query()?;
+        self.query_planner.create_plan(&data, tx)
+    }
+
+    /// Execute an SQL insert, delete, modify, or create statement.
+    /// The method dispatches to the appropriate method of the supplied update planner, depending on what the parser returns.
+    pub fn execute_update(&self, sql: &str, tx: Arc<Mutex<Transaction>>) -> Result<usize> {
+        match Parser::new(sql)?.update_cmd()? {
+            UpdateCommand::Insert(data) => self.update_planner.execute_insert(&data, tx),
+            UpdateCommand::Delete(data) => self.update_planner.execute_delete(&data, tx),
+            UpdateCommand::Modify(data) => self.update_planner.execute_modify(&data, tx),
+            UpdateCommand::CreateTable(data) => self.update_planner.execute_create_table(&data, tx),
+            UpdateCommand::CreateView(data) => self.update_planner.execute_create_view(&data, tx),
+            UpdateCommand::CreateIndex(data) => self.update_planner.execute_create_index(&data, tx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+
+    use crate::server::simpledb::SimpleDB;
+
+    #[test]
+    fn test_planner() {
+        let temp_dir = tempdir().unwrap();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+        let planner = db.planner();
+        let tx = db.new_tx().unwrap();
+
+        planner
+            .execute_update("create table T1(A int, B varchar(9))", Arc::clone(&tx))
+            .unwrap();
+        for i in 0..200 {
+            let sql = format!("insert into T1(A, B) values ({}, 'rec{}')", i % 50, i);
+            assert_eq!(planner.execute_update(&sql, Arc::clone(&tx)).unwrap(), 1);
+        }
+
+        planner
+            .execute_update("create table T2(C int, D varchar(9))", Arc::clone(&tx))
+            .unwrap();
+        for i in 0..50 {
+            let sql = format!("insert into T2(C, D) values ({}, 'd{}')", i, i);
+            planner.execute_update(&sql, Arc::clone(&tx)).unwrap();
+        }
+
+        // a selection
+        let plan = planner
+            .create_query_plan("select B from T1 where A = 10", Arc::clone(&tx))
+            .unwrap();
+        assert_eq!(plan.schema().fields(), ["b"]);
+        let mut scan = plan.open().unwrap();
+        let mut result = vec![];
+        while scan.next().unwrap() {
+            result.push(scan.get_string("b").unwrap());
+        }
+        scan.close().unwrap();
+        assert_eq!(result, ["rec10", "rec60", "rec110", "rec160"]);
+
+        // a range selection
+        let plan = planner
+            .create_query_plan(
+                "select B from T1 where A between 47 and 49 and A > 47",
+                Arc::clone(&tx),
+            )
+            .unwrap();
+        let mut scan = plan.open().unwrap();
+        let mut count = 0;
+        while scan.next().unwrap() {
+            count += 1;
+        }
+        scan.close().unwrap();
+        assert_eq!(count, 8);
+
+        // a join
+        let plan = planner
+            .create_query_plan(
+                "select B, D from T1, T2 where A = C and C = 7",
+                Arc::clone(&tx),
+            )
+            .unwrap();
+        let mut scan = plan.open().unwrap();
+        let mut count = 0;
+        while scan.next().unwrap() {
+            assert_eq!(scan.get_string("d").unwrap(), "d7");
+            count += 1;
+        }
+        scan.close().unwrap();
+        assert_eq!(count, 4);
+
+        // updates and deletes report the number of affected records
+        assert_eq!(
+            planner
+                .execute_update("update T1 set B = 'changed' where A = 10", Arc::clone(&tx))
+                .unwrap(),
+            4
+        );
+        assert_eq!(
+            planner
+                .execute_update("delete from T1 where B = 'changed'", Arc::clone(&tx))
+                .unwrap(),
+            4
+        );
+
+        // a view is planned by planning its definition
+        planner
+            .execute_update(
+                "create view V as select A, B from T1 where A = 11",
+                Arc::clone(&tx),
+            )
+            .unwrap();
+        let plan = planner
+            .create_query_plan("select B from V", Arc::clone(&tx))
+            .unwrap();
+        let mut scan = plan.open().unwrap();
+        let mut count = 0;
+        while scan.next().unwrap() {
+            count += 1;
+        }
+        scan.close().unwrap();
+        assert_eq!(count, 4);
+
+        assert!(planner
+            .execute_update("insert into T1(A, B) values (1)", Arc::clone(&tx))
+            .is_err());
+        assert!(planner
+            .create_query_plan("select A from NoTable", Arc::clone(&tx))
+            .is_err());
+        assert!(planner
+            .create_query_plan("select C from T1", Arc::clone(&tx))
+            .is_err());
+
+        tx.lock().unwrap().commit().unwrap();
+    }
+
+    #[test]
+    fn test_update_type_checks() {
+        let temp_dir = tempdir().unwrap();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+        let planner = db.planner();
+        let tx = db.new_tx().unwrap();
+
+        planner
+            .execute_update("create table T(A int, B varchar(9))", Arc::clone(&tx))
+            .unwrap();
+        planner
+            .execute_update("insert into T(A, B) values (1, 'one')", Arc::clone(&tx))
+            .unwrap();
+
+        let failing = [
+            "insert into T(A, B) values ('x', 'two')",
+            "insert into T(A, B) values (2, 2)",
+            "insert into T(A, B) values (2)",
+            "insert into T(A) values (2, 'two')",
+            "insert into T(A, C) values (2, 'two')",
+            "update T set B = 5",
+            "update T set A = 'x'",
+            "update T set A = B",
+            "update T set C = 1",
+        ];
+        for sql in failing {
+            assert!(
+                planner.execute_update(sql, Arc::clone(&tx)).is_err(),
+                "{}",
+                sql
+            );
+        }
+
+        // none of the failed statements wrote anything
+        let plan = planner
+            .create_query_plan("select A, B from T", Arc::clone(&tx))
+            .unwrap();
+        let mut scan = plan.open().unwrap();
+        assert!(scan.next().unwrap());
+        assert_eq!(scan.get_int("a").unwrap(), 1);
+        assert_eq!(scan.get_string("b").unwrap(), "one");
+        assert!(!scan.next().unwrap());
+        scan.close().unwrap();
+
+        tx.lock().unwrap().commit().unwrap();
+    }
+}
@@ -0,0 +1,58 @@
+use anyhow::{Ok, Result};
+
+use crate::{
+    query::{product_scan::ProductScan, scan::Scan},
+    record::schema::Schema,
+};
+
+use super::plan::Plan;
+
+/// The plan class corresponding to the product relational algebra operator.
+pub struct ProductPlan {
+    plan1: Box<dyn Plan>,
+    plan2: Box<dyn Plan>,
+    schema: Schema,
+}
+
+impl ProductPlan {
+    pub fn new(plan1: Box<dyn Plan>, plan2: Box<dyn Plan>) -> Self {
+        let mut schema = Schema::new();
+        schema.add_all(plan1.schema());
+        schema.add_all(plan2.schema());
+        Self {
+            plan1,
+            plan2,
+            schema,
+        }
+    }
+}
+
+impl Plan for ProductPlan {
+    fn open(&self) -> Result<Box<dyn Scan>> {
+        let scan1 = self.plan1.open()?;
+        let scan2 = self.plan2.open()?;
+        Ok(Box::new(ProductScan::new(scan1, scan2)?))
+    }
+
+    /// Estimate the required block accesses as B(product(p1,p2)) = B(p1) + R(p1)*B(p2).
+    fn blocks_accessed(&self) -> usize {
+        self.plan1.blocks_accessed() + self.plan1.records_output() * self.plan2.blocks_accessed()
+    }
+
+    /// Estimate the number of output records as R(product(p1,p2)) = R(p1)*R(p2).
+    fn records_output(&self) -> usize {
+        self.plan1.records_output() * self.plan2.records_output()
+    }
+
+    fn distinct_values(&self, field_name: &str) -> usize {
+        if self.plan1.schema().has_field(field_name) {
+            self.plan1.distinct_values(field_name)
+        } else {
+            self.plan2.distinct_values(field_name)
+        }
+    }
+
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+}
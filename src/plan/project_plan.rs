@@ -0,0 +1,71 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+
+use crate::{
+    query::{project_scan::ProjectScan, scan::Scan},
+    record::schema::Schema,
+};
+
+use super::plan::Plan;
+
+#[derive(Debug)]
+enum ProjectPlanError {
+    FieldNotFound(String),
+}
+
+impl std::error::Error for ProjectPlanError {}
+impl fmt::Display for ProjectPlanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProjectPlanError::FieldNotFound(field_name) => {
+                write!(f, "field {} not found", field_name)
+            }
+        }
+    }
+}
+
+/// The plan class corresponding to the project relational algebra operator.
+pub struct ProjectPlan {
+    plan: Box<dyn Plan>,
+    schema: Schema,
+}
+
+impl ProjectPlan {
+    /// Fail if one of the fields is not produced by the underlying plan.
+    pub fn new(plan: Box<dyn Plan>, fields: &[String]) -> Result<Self> {
+        let mut schema = Schema::new();
+        for field_name in fields {
+            if !plan.schema().has_field(field_name) {
+                return Err(ProjectPlanError::FieldNotFound(field_name.clone()).into());
+            }
+            schema.add(field_name, plan.schema());
+        }
+        Ok(Self { plan, schema })
+    }
+}
+
+impl Plan for ProjectPlan {
+    fn open(&self) -> Result<Box<dyn Scan>> {
+        let scan = self.plan.open()?;
+        Ok(Box::new(ProjectScan::new(
+            scan,
+            self.schema.fields().to_vec(),
+        )))
+    }
+
+    fn blocks_accessed(&self) -> usize {
+        self.plan.blocks_accessed()
+    }
+
+    fn records_output(&self) -> usize {
+        self.plan.records_output()
+    }
+
+    fn distinct_values(&self, field_name: &str) -> usize {
+        self.plan.distinct_values(field_name)
+    }
+
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+}
@@ -0,0 +1,12 @@
+use anyhow::Result;
+use std::sync::{Arc, Mutex};
+
+use crate::{parse::query_data::QueryData, tx::transaction::Transaction};
+
+use super::plan::Plan;
+
+/// The interface implemented by planners for the SQL select statement.
+pub trait QueryPlanner: Send + Sync {
+    /// Create a plan for the parsed query.
+    fn create_plan(&self, data: &QueryData, tx: Arc<Mutex<Transaction>>) -> Result<Box<dyn Plan>>;
+}
@@ -0,0 +1,56 @@
+use anyhow::{Ok, Result};
+
+use crate::{
+    query::{predicate::Predicate, scan::Scan, select_scan::SelectScan},
+    record::schema::Schema,
+};
+
+use super::plan::Plan;
+
+/// The plan class corresponding to the select relational algebra operator.
+pub struct SelectPlan {
+    plan: Box<dyn Plan>,
+    pred: Predicate,
+}
+
+impl SelectPlan {
+    pub fn new(plan: Box<dyn Plan>, pred: Predicate) -> Self {
+        Self { plan, pred }
+    }
+}
+
+impl Plan for SelectPlan {
+    fn open(&self) -> Result<Box<dyn Scan>> {
+        let scan = self.plan.open()?;
+        Ok(Box::new(SelectScan::new(scan, self.pred.clone())))
+    }
+
+    /// A select reads the same blocks as its underlying query.
+    fn blocks_accessed(&self) -> usize {
+        self.plan.blocks_accessed()
+    }
+
+    /// Estimate the number of output records, based on the reduction factor of the predicate.
+    fn records_output(&self) -> usize {
+        self.plan.records_output() / self.pred.reduction_factor(self.plan.as_ref())
+    }
+
+    /// If the predicate equates the field with a constant, the value is 1.
+    /// If it equates the field with another field, the value is the minimum of the two fields' distinct values.
+    /// Otherwise, the value is the same as in the underlying query.
+    fn distinct_values(&self, field_name: &str) -> usize {
+        if self.pred.equates_with_constant(field_name).is_some() {
+            1
+        } else if let Some(other) = self.pred.equates_with_field(field_name) {
+            self.plan
+                .distinct_values(field_name)
+                .min(self.plan.distinct_values(other))
+        } else {
+            self.plan.distinct_values(field_name)
+        }
+    }
+
+    fn schema(&self) -> &Schema {
+        self.plan.schema()
+    }
+}
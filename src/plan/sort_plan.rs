@@ -0,0 +1,314 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::{
+    cmp::Ordering,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    query::{
+        constant::Constant,
+        record_comparator::RecordComparator,
+        scan::{Scan, UpdateScan},
+        sort_scan::SortScan,
+    },
+    record::{schema::Schema, temp_table::TempTable},
+    tx::transaction::Transaction,
+};
+
+use super::{materialize_plan::MaterializePlan, plan::Plan};
+
+#[derive(Debug)]
+enum SortPlanError {
+    FieldNotFound(String),
+}
+
+impl std::error::Error for SortPlanError {}
+impl fmt::Display for SortPlanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SortPlanError::FieldNotFound(field_name) => {
+                write!(f, "field {} not found", field_name)
+            }
+        }
+    }
+}
+
+/// The plan class for the sort operator.
+/// Opening the plan sorts the output of the underlying query with an external merge sort: the input is split into
+/// runs that are sorted in memory, and the runs are merged several at a time until few enough remain for the sort
+/// scan to merge them on the fly. The size of a run and the number of runs merged at a time are based on the number
+/// of available buffers.
+pub struct SortPlan {
+    tx: Arc<Mutex<Transaction>>,
+    plan: Box<dyn Plan>,
+    comparator: RecordComparator,
+}
+
+impl SortPlan {
+    /// Create a sort plan for the specified query, sorting on the specified fields.
+    /// Fail if one of the fields is not produced by the underlying plan.
+    pub fn new(
+        tx: Arc<Mutex<Transaction>>,
+        plan: Box<dyn Plan>,
+        sort_fields: &[String],
+    ) -> Result<Self> {
+        if let Some(field_name) = sort_fields
+            .iter()
+            .find(|field_name| !plan.schema().has_field(field_name))
+        {
+            return Err(SortPlanError::FieldNotFound(field_name.clone()).into());
+        }
+        Ok(Self {
+            tx,
+            plan,
+            comparator: RecordComparator::new(sort_fields),
+        })
+    }
+
+    /// Sort the output of the underlying query, and open a sort scan over the sorted runs.
+    pub fn open_sort_scan(&self) -> Result<SortScan> {
+        let mut src = self.plan.open()?;
+        let mut runs = self.split_into_runs(src.as_mut())?;
+        src.close()?;
+        let merge_width = self.merge_width();
+        while runs.len() > merge_width {
+            runs = runs
+                .chunks(merge_width)
+                .map(|group| self.merge_runs(group))
+                .collect::<Result<_>>()?;
+        }
+        SortScan::new(&runs, self.comparator.clone())
+    }
+
+    /// Return the estimated one-time cost of sorting: reading the input and writing the runs,
+    /// and then reading and writing every block again for each merge iteration.
+    pub fn sort_cost(&self) -> usize {
+        let merge_width = self.merge_width();
+        let blocks = self.blocks_accessed();
+        let mut num_runs = blocks.div_ceil(merge_width);
+        let mut cost = self.plan.blocks_accessed() + blocks;
+        while num_runs > merge_width {
+            num_runs = num_runs.div_ceil(merge_width);
+            cost += 2 * blocks;
+        }
+        cost
+    }
+
+    /// The number of blocks that a run or a merge may use: half of the available buffers,
+    /// leaving the rest to the other scans of the query.
+    fn merge_width(&self) -> usize {
+        (self.tx.lock().unwrap().available_buffs() / 2).max(2)
+    }
+
+    /// Split the input into runs of as many records as fit in `merge_width` blocks, each sorted in memory
+    /// and written to a temporary table. An empty input has a single empty run.
+    fn split_into_runs(&self, src: &mut dyn Scan) -> Result<Vec<TempTable>> {
+        let records_per_block = MaterializePlan::records_per_block(&self.tx, self.schema());
+        let run_size = self.merge_width() * records_per_block;
+        let fields = self.schema().fields();
+        let sort_positions: Vec<usize> = self
+            .comparator
+            .fields()
+            .iter()
+            .filter_map(|sort_field| fields.iter().position(|f| f == sort_field))
+            .collect();
+
+        let mut runs = vec![];
+        let mut records: Vec<Vec<Constant>> = Vec::with_capacity(run_size);
+        while src.next()? {
+            records.push(
+                fields
+                    .iter()
+                    .map(|field_name| src.get_val(field_name))
+                    .collect::<Result<_>>()?,
+            );
+            if records.len() == run_size {
+                runs.push(self.write_run(&mut records, &sort_positions)?);
+            }
+        }
+        if !records.is_empty() || runs.is_empty() {
+            runs.push(self.write_run(&mut records, &sort_positions)?);
+        }
+        Ok(runs)
+    }
+
+    /// Sort the records on the fields at the specified positions, and move them to a new run.
+    fn write_run(
+        &self,
+        records: &mut Vec<Vec<Constant>>,
+        sort_positions: &[usize],
+    ) -> Result<TempTable> {
+        records.sort_by(|r1, r2| {
+            sort_positions
+                .iter()
+                .map(|&pos| r1[pos].cmp(&r2[pos]))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+        let run = TempTable::new(Arc::clone(&self.tx), self.schema().clone());
+        let mut dest = run.open()?;
+        for record in records.drain(..) {
+            dest.insert()?;
+            for (field_name, val) in self.schema().fields().iter().zip(&record) {
+                dest.set_val(field_name, val)?;
+            }
+        }
+        dest.close()?;
+        Ok(run)
+    }
+
+    /// Merge the specified runs into a single run.
+    fn merge_runs(&self, runs: &[TempTable]) -> Result<TempTable> {
+        let result = TempTable::new(Arc::clone(&self.tx), self.schema().clone());
+        let mut src = SortScan::new(runs, self.comparator.clone())?;
+        let mut dest = result.open()?;
+        while src.next()? {
+            dest.insert()?;
+            for field_name in self.schema().fields() {
+                dest.set_val(field_name, &src.get_val(field_name)?)?;
+            }
+        }
+        src.close()?;
+        dest.close()?;
+        Ok(result)
+    }
+}
+
+impl Plan for SortPlan {
+    fn open(&self) -> Result<Box<dyn Scan>> {
+        Ok(Box::new(self.open_sort_scan()?))
+    }
+
+    /// Return the number of blocks in the sorted table, which is the same as in the materialized table.
+    /// It does not include the one-time cost of splitting and merging the runs.
+    fn blocks_accessed(&self) -> usize {
+        MaterializePlan::estimate_blocks(&self.tx, self.schema(), self.records_output())
+    }
+
+    fn records_output(&self) -> usize {
+        self.plan.records_output()
+    }
+
+    fn distinct_values(&self, field_name: &str) -> usize {
+        self.plan.distinct_values(field_name)
+    }
+
+    fn schema(&self) -> &Schema {
+        self.plan.schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+
+    use crate::{
+        plan::{materialize_plan::MaterializePlan, plan::Plan, table_plan::TablePlan},
+        query::{
+            constant::Constant,
+            scan::{Scan, UpdateScan},
+        },
+        record::table_scan::TableScan,
+        server::simpledb::SimpleDB,
+    };
+
+    use super::SortPlan;
+
+    #[test]
+    fn test_sort_plan() {
+        let temp_dir = tempdir().unwrap();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+        let planner = db.planner();
+        let tx = db.new_tx().unwrap();
+
+        // enough records for several runs, which take more than one merge iteration
+        planner
+            .execute_update("create table T(A int, B varchar(9))", Arc::clone(&tx))
+            .unwrap();
+        let layout = db
+            .metadata_manager()
+            .get_layout("t", Arc::clone(&tx))
+            .unwrap();
+        let mut scan = TableScan::new(Arc::clone(&tx), "t", layout).unwrap();
+        for i in 0..2000 {
+            scan.insert().unwrap();
+            scan.set_int("a", (i * 7919) % 2000).unwrap();
+            scan.set_string("b", &format!("b{}", i % 7)).unwrap();
+        }
+        scan.close().unwrap();
+        let table_plan =
+            || Box::new(TablePlan::new(Arc::clone(&tx), "t", &db.metadata_manager()).unwrap());
+
+        let sort_fields = ["b".to_string(), "a".to_string()];
+        let plan = SortPlan::new(Arc::clone(&tx), table_plan(), &sort_fields).unwrap();
+        let mut scan = plan.open_sort_scan().unwrap();
+        let mut records = vec![];
+        while scan.next().unwrap() {
+            records.push((scan.get_string("b").unwrap(), scan.get_int("a").unwrap()));
+        }
+        assert_eq!(records.len(), 2000);
+        assert!(records.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // the scan can return to a saved position
+        scan.before_first().unwrap();
+        for _ in 0..100 {
+            scan.next().unwrap();
+        }
+        let position = scan.save_position().unwrap();
+        let expected = (scan.get_string("b").unwrap(), scan.get_int("a").unwrap());
+        assert_eq!(expected, records[99]);
+        for _ in 0..500 {
+            scan.next().unwrap();
+        }
+        scan.restore_position(&position).unwrap();
+        assert_eq!(
+            (scan.get_string("b").unwrap(), scan.get_int("a").unwrap()),
+            expected
+        );
+        assert!(scan.next().unwrap());
+        assert_eq!(
+            (scan.get_string("b").unwrap(), scan.get_int("a").unwrap()),
+            records[100]
+        );
+        scan.close().unwrap();
+
+        // a materialized table holds the same records
+        let plan = MaterializePlan::new(Arc::clone(&tx), table_plan());
+        let mut scan = plan.open().unwrap();
+        let mut count = 0;
+        while scan.next().unwrap() {
+            count += 1;
+        }
+        scan.close().unwrap();
+        assert_eq!(count, 2000);
+
+        assert!(SortPlan::new(Arc::clone(&tx), table_plan(), &["c".to_string()]).is_err());
+
+        // sorting an empty input gives an empty output
+        planner
+            .execute_update("create table E(A int)", Arc::clone(&tx))
+            .unwrap();
+        let empty = TablePlan::new(Arc::clone(&tx), "e", &db.metadata_manager()).unwrap();
+        let plan = SortPlan::new(Arc::clone(&tx), Box::new(empty), &["a".to_string()]).unwrap();
+        let mut scan = plan.open().unwrap();
+        assert!(!scan.next().unwrap());
+        assert!(scan.has_field("a"));
+        scan.close().unwrap();
+
+        let plan = planner
+            .create_query_plan("select a from t where a < 5 order by a", Arc::clone(&tx))
+            .unwrap();
+        let mut scan = plan.open().unwrap();
+        let mut vals = vec![];
+        while scan.next().unwrap() {
+            vals.push(scan.get_val("a").unwrap());
+        }
+        scan.close().unwrap();
+        assert_eq!(vals, (0..5).map(Constant::Int).collect::<Vec<_>>());
+
+        tx.lock().unwrap().commit().unwrap();
+    }
+}
@@ -0,0 +1,66 @@
+use anyhow::{Ok, Result};
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    metadata::{manager::MetadataManager, stat_manager::StatInfo},
+    query::scan::Scan,
+    record::{layout::Layout, schema::Schema, table_scan::TableScan},
+    tx::transaction::Transaction,
+};
+
+use super::plan::Plan;
+
+/// The plan class corresponding to a table.
+#[derive(Clone)]
+pub struct TablePlan {
+    table_name: String,
+    tx: Arc<Mutex<Transaction>>,
+    layout: Layout,
+    stat_info: StatInfo,
+}
+
+impl TablePlan {
+    /// Create a leaf node in the query tree corresponding to the specified table.
+    pub fn new(
+        tx: Arc<Mutex<Transaction>>,
+        table_name: &str,
+        metadata_manager: &MetadataManager,
+    ) -> Result<Self> {
+        let layout = metadata_manager.get_layout(table_name, Arc::clone(&tx))?;
+        let stat_info = metadata_manager.get_stat_info(table_name, &layout, Arc::clone(&tx))?;
+        Ok(Self {
+            table_name: table_name.to_string(),
+            tx,
+            layout,
+            stat_info,
+        })
+    }
+
+    /// Open a table scan over the table, for plans that need to position it by record id.
+    pub fn open_table_scan(&self) -> Result<TableScan> {
+        TableScan::new(Arc::clone(&self.tx), &self.table_name, self.layout.clone())
+    }
+}
+
+impl Plan for TablePlan {
+    fn open(&self) -> Result<Box<dyn Scan>> {
+        Ok(Box::new(self.open_table_scan()?))
+    }
+
+    /// Estimate the number of block accesses for the table, which is obtainable from the statistics manager.
+    fn blocks_accessed(&self) -> usize {
+        self.stat_info.blocks_accessed()
+    }
+
+    fn records_output(&self) -> usize {
+        self.stat_info.records_output()
+    }
+
+    fn distinct_values(&self, field_name: &str) -> usize {
+        self.stat_info.distinct_values(field_name)
+    }
+
+    fn schema(&self) -> &Schema {
+        self.layout.schema()
+    }
+}
@@ -0,0 +1,201 @@
+use anyhow::{Ok, Result};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    metadata::{index_manager::IndexInfo, manager::MetadataManager},
+    query::predicate::Predicate,
+    record::schema::Schema,
+    tx::transaction::Transaction,
+};
+
+use super::{
+    hash_join_plan::HashJoinPlan, index_join_plan::IndexJoinPlan,
+    index_select_plan::IndexSelectPlan, merge_join_plan::MergeJoinPlan,
+    multibuffer_product_plan::MultibufferProductPlan, plan::Plan, product_plan::ProductPlan,
+    select_plan::SelectPlan, table_plan::TablePlan,
+};
+
+/// This class contains methods for planning a single table or view of a query.
+/// The plans it creates push the terms of the predicate that apply to the table down onto it,
+/// and use the indexes of a table where the predicate allows.
+pub struct TablePlanner {
+    tx: Arc<Mutex<Transaction>>,
+    plan: Arc<dyn Plan>,
+    // the plan of the table, or None for a view, which has no indexes
+    table_plan: Option<TablePlan>,
+    indexes: HashMap<String, IndexInfo>,
+    pred: Predicate,
+}
+
+impl TablePlanner {
+    /// Create a table planner for the specified table of the query.
+    pub fn for_table(
+        table_name: &str,
+        pred: &Predicate,
+        metadata_manager: &MetadataManager,
+        tx: Arc<Mutex<Transaction>>,
+    ) -> Result<Self> {
+        let table_plan = TablePlan::new(Arc::clone(&tx), table_name, metadata_manager)?;
+        let indexes = metadata_manager.get_index_info(table_name, Arc::clone(&tx))?;
+        Ok(Self {
+            tx,
+            plan: Arc::new(table_plan.clone()),
+            table_plan: Some(table_plan),
+            indexes,
+            pred: pred.clone(),
+        })
+    }
+
+    /// Create a table planner for a view of the query, given the plan of its definition.
+    pub fn for_view(plan: Box<dyn Plan>, pred: &Predicate, tx: Arc<Mutex<Transaction>>) -> Self {
+        Self {
+            tx,
+            plan: Arc::from(plan),
+            table_plan: None,
+            indexes: HashMap::new(),
+            pred: pred.clone(),
+        }
+    }
+
+    pub fn schema(&self) -> &Schema {
+        self.plan.schema()
+    }
+
+    /// Construct a select plan for the table.
+    /// The plan will use an index select, if possible.
+    pub fn make_select_plan(&self) -> Box<dyn Plan> {
+        let plan = self
+            .make_index_select()
+            .unwrap_or_else(|| Box::new(Arc::clone(&self.plan)));
+        self.add_select_pred(plan)
+    }
+
+    /// Construct a join plan of the specified plan and the table.
+    /// The method considers an index join, a hash join, a merge join and a product, and returns the one having the
+    /// fewest block accesses. Return None if no term of the predicate joins the table to the plan.
+    pub fn make_join_plan(&self, current: &Arc<dyn Plan>) -> Option<Box<dyn Plan>> {
+        let current_schema = current.schema();
+        let join_pred = self.pred.join_sub_pred(self.schema(), current_schema)?;
+        [
+            self.make_index_join(current, &join_pred),
+            self.make_hash_join(current, &join_pred),
+            self.make_merge_join(current, &join_pred),
+            Some(self.make_product_plan(current)),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|plan| self.add_join_pred(plan, current_schema))
+        .min_by_key(|plan| plan.blocks_accessed())
+    }
+
+    /// Construct a product plan of the specified plan and the table.
+    /// The method considers a basic product and a multi-buffer product, and returns the one having the fewest
+    /// block accesses.
+    pub fn make_product_plan(&self, current: &Arc<dyn Plan>) -> Box<dyn Plan> {
+        let product: Box<dyn Plan> = Box::new(ProductPlan::new(
+            Box::new(Arc::clone(current)),
+            self.make_select_plan(),
+        ));
+        let mb_product: Box<dyn Plan> = Box::new(MultibufferProductPlan::new(
+            Arc::clone(&self.tx),
+            Box::new(Arc::clone(current)),
+            self.make_select_plan(),
+        ));
+        if mb_product.blocks_accessed() < product.blocks_accessed() {
+            mb_product
+        } else {
+            product
+        }
+    }
+
+    fn make_index_select(&self) -> Option<Box<dyn Plan>> {
+        let table_plan = self.table_plan.as_ref()?;
+        let (index_info, search) = IndexSelectPlan::find_search(&self.indexes, &self.pred)?;
+        Some(Box::new(IndexSelectPlan::new(
+            table_plan.clone(),
+            index_info.clone(),
+            search,
+        )))
+    }
+
+    fn make_index_join(
+        &self,
+        current: &Arc<dyn Plan>,
+        join_pred: &Predicate,
+    ) -> Option<Box<dyn Plan>> {
+        let table_plan = self.table_plan.as_ref()?;
+        let (index_info, join_field) =
+            IndexJoinPlan::find_join(&self.indexes, join_pred, current.schema())?;
+        let plan = Box::new(IndexJoinPlan::new(
+            Box::new(Arc::clone(current)),
+            table_plan.clone(),
+            index_info.clone(),
+            &join_field,
+        ));
+        Some(self.add_select_pred(plan))
+    }
+
+    fn make_hash_join(
+        &self,
+        current: &Arc<dyn Plan>,
+        join_pred: &Predicate,
+    ) -> Option<Box<dyn Plan>> {
+        let (join_field1, join_field2) = self.find_equijoin(current, join_pred)?;
+        Some(Box::new(HashJoinPlan::new(
+            Arc::clone(&self.tx),
+            Box::new(Arc::clone(current)),
+            self.make_select_plan(),
+            &join_field1,
+            &join_field2,
+        )))
+    }
+
+    fn make_merge_join(
+        &self,
+        current: &Arc<dyn Plan>,
+        join_pred: &Predicate,
+    ) -> Option<Box<dyn Plan>> {
+        let (join_field1, join_field2) = self.find_equijoin(current, join_pred)?;
+        let plan = MergeJoinPlan::new(
+            Arc::clone(&self.tx),
+            Box::new(Arc::clone(current)),
+            self.make_select_plan(),
+            &join_field1,
+            &join_field2,
+        )
+        .ok()?;
+        Some(Box::new(plan))
+    }
+
+    /// Return a field of the plan and a field of the table that the join predicate equates.
+    fn find_equijoin(
+        &self,
+        current: &Arc<dyn Plan>,
+        join_pred: &Predicate,
+    ) -> Option<(String, String)> {
+        self.schema().fields().iter().find_map(|field_name| {
+            let other = join_pred.equates_with_field(field_name)?;
+            current
+                .schema()
+                .has_field(other)
+                .then(|| (other.to_string(), field_name.clone()))
+        })
+    }
+
+    fn add_select_pred(&self, plan: Box<dyn Plan>) -> Box<dyn Plan> {
+        match self.pred.select_sub_pred(self.schema()) {
+            Some(select_pred) => Box::new(SelectPlan::new(plan, select_pred)),
+            None => plan,
+        }
+    }
+
+    fn add_join_pred(&self, plan: Box<dyn Plan>, current_schema: &Schema) -> Box<dyn Plan> {
+        match self.pred.join_sub_pred(current_schema, self.schema()) {
+            Some(join_pred) => Box::new(SelectPlan::new(plan, join_pred)),
+            None => plan,
+        }
+    }
+}
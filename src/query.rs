@@ -0,0 +1,17 @@
+pub mod aggregation_fn;
+pub mod constant;
+pub mod expression;
+pub mod group_by_scan;
+pub mod hash_join_scan;
+pub mod index_join_scan;
+pub mod index_select_scan;
+pub mod merge_join_scan;
+pub mod multibuffer_product_scan;
+pub mod predicate;
+pub mod product_scan;
+pub mod project_scan;
+pub mod record_comparator;
+pub mod scan;
+pub mod select_scan;
+pub mod sort_scan;
+pub mod term;
@@ -0,0 +1,335 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+
+use super::{constant::Constant, scan::Scan};
+
+#[derive(Debug)]
+enum AggregationFnError {
+    NotAnInteger(String),
+    Overflow(String),
+}
+
+impl std::error::Error for AggregationFnError {}
+impl fmt::Display for AggregationFnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AggregationFnError::NotAnInteger(field_name) => {
+                write!(f, "field {} is not an integer", field_name)
+            }
+            AggregationFnError::Overflow(field_name) => {
+                write!(f, "aggregate {} overflows an integer", field_name)
+            }
+        }
+    }
+}
+
+/// The interface implemented by aggregation functions.
+/// Aggregation functions are used by the groupby operator.
+pub trait AggregationFn {
+    /// Use the current record of the specified scan to be the first record in the group.
+    fn process_first(&mut self, scan: &dyn Scan) -> Result<()>;
+
+    /// Use the current record of the specified scan to be the next record in the group.
+    fn process_next(&mut self, scan: &dyn Scan) -> Result<()>;
+
+    /// Return the name of the new aggregation field.
+    fn field_name(&self) -> &str;
+
+    /// Return the computed aggregation value.
+    fn value(&self) -> Result<Constant>;
+}
+
+/// The aggregation functions of the SQL select list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregationKind {
+    Count,
+    Sum,
+    Min,
+    Max,
+    Avg,
+}
+
+impl fmt::Display for AggregationKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl AggregationKind {
+    /// Return the name of the function, as used in SQL.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AggregationKind::Count => "count",
+            AggregationKind::Sum => "sum",
+            AggregationKind::Min => "min",
+            AggregationKind::Max => "max",
+            AggregationKind::Avg => "avg",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "count" => Some(AggregationKind::Count),
+            "sum" => Some(AggregationKind::Sum),
+            "min" => Some(AggregationKind::Min),
+            "max" => Some(AggregationKind::Max),
+            "avg" => Some(AggregationKind::Avg),
+            _ => None,
+        }
+    }
+}
+
+/// An aggregation of the SQL select list, such as `max(gradyear)`.
+/// Its output field is named after the function and the field, such as `maxofgradyear`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aggregation {
+    kind: AggregationKind,
+    field_name: String,
+}
+
+impl fmt::Display for Aggregation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}({})", self.kind, self.field_name)
+    }
+}
+
+impl Aggregation {
+    pub fn new(kind: AggregationKind, field_name: &str) -> Self {
+        Self {
+            kind,
+            field_name: field_name.to_string(),
+        }
+    }
+
+    pub fn kind(&self) -> AggregationKind {
+        self.kind
+    }
+
+    /// Return the name of the aggregated field.
+    pub fn field_name(&self) -> &str {
+        &self.field_name
+    }
+
+    /// Return the name of the output field.
+    pub fn output_field(&self) -> String {
+        format!("{}of{}", self.kind, self.field_name)
+    }
+
+    /// Create a new aggregation function that computes this aggregation.
+    pub fn create_fn(&self) -> Box<dyn AggregationFn> {
+        let field_name = self.field_name.clone();
+        let output_field = self.output_field();
+        match self.kind {
+            AggregationKind::Count => Box::new(CountFn::new(output_field)),
+            AggregationKind::Sum => Box::new(SumFn::new(field_name, output_field)),
+            AggregationKind::Min => Box::new(MinFn::new(field_name, output_field)),
+            AggregationKind::Max => Box::new(MaxFn::new(field_name, output_field)),
+            AggregationKind::Avg => Box::new(AvgFn::new(field_name, output_field)),
+        }
+    }
+}
+
+/// The count aggregation function.
+pub struct CountFn {
+    output_field: String,
+    count: i32,
+}
+
+impl CountFn {
+    pub fn new(output_field: String) -> Self {
+        Self {
+            output_field,
+            count: 0,
+        }
+    }
+}
+
+impl AggregationFn for CountFn {
+    fn process_first(&mut self, _scan: &dyn Scan) -> Result<()> {
+        self.count = 1;
+        Ok(())
+    }
+
+    fn process_next(&mut self, _scan: &dyn Scan) -> Result<()> {
+        self.count += 1;
+        Ok(())
+    }
+
+    fn field_name(&self) -> &str {
+        &self.output_field
+    }
+
+    fn value(&self) -> Result<Constant> {
+        Ok(Constant::Int(self.count))
+    }
+}
+
+/// The sum aggregation function, for integer fields.
+pub struct SumFn {
+    src_field: String,
+    output_field: String,
+    sum: i64,
+}
+
+impl SumFn {
+    pub fn new(src_field: String, output_field: String) -> Self {
+        Self {
+            src_field,
+            output_field,
+            sum: 0,
+        }
+    }
+}
+
+impl AggregationFn for SumFn {
+    fn process_first(&mut self, scan: &dyn Scan) -> Result<()> {
+        self.sum = int_val(scan, &self.src_field)?;
+        Ok(())
+    }
+
+    fn process_next(&mut self, scan: &dyn Scan) -> Result<()> {
+        self.sum += int_val(scan, &self.src_field)?;
+        Ok(())
+    }
+
+    fn field_name(&self) -> &str {
+        &self.output_field
+    }
+
+    fn value(&self) -> Result<Constant> {
+        let sum = i32::try_from(self.sum)
+            .map_err(|_| AggregationFnError::Overflow(self.output_field.clone()))?;
+        Ok(Constant::Int(sum))
+    }
+}
+
+/// The min aggregation function.
+pub struct MinFn {
+    src_field: String,
+    output_field: String,
+    val: Option<Constant>,
+}
+
+impl MinFn {
+    pub fn new(src_field: String, output_field: String) -> Self {
+        Self {
+            src_field,
+            output_field,
+            val: None,
+        }
+    }
+}
+
+impl AggregationFn for MinFn {
+    fn process_first(&mut self, scan: &dyn Scan) -> Result<()> {
+        self.val = Some(scan.get_val(&self.src_field)?);
+        Ok(())
+    }
+
+    /// Replace the current minimum by the field value in the current record, if it is lower.
+    fn process_next(&mut self, scan: &dyn Scan) -> Result<()> {
+        let new_val = scan.get_val(&self.src_field)?;
+        if self.val.as_ref().is_none_or(|val| new_val < *val) {
+            self.val = Some(new_val);
+        }
+        Ok(())
+    }
+
+    fn field_name(&self) -> &str {
+        &self.output_field
+    }
+
+    fn value(&self) -> Result<Constant> {
+        Ok(self.val.clone().unwrap_or(Constant::Int(0)))
+    }
+}
+
+/// The max aggregation function.
+pub struct MaxFn {
+    src_field: String,
+    output_field: String,
+    val: Option<Constant>,
+}
+
+impl MaxFn {
+    pub fn new(src_field: String, output_field: String) -> Self {
+        Self {
+            src_field,
+            output_field,
+            val: None,
+        }
+    }
+}
+
+impl AggregationFn for MaxFn {
+    fn process_first(&mut self, scan: &dyn Scan) -> Result<()> {
+        self.val = Some(scan.get_val(&self.src_field)?);
+        Ok(())
+    }
+
+    /// Replace the current maximum by the field value in the current record, if it is higher.
+    fn process_next(&mut self, scan: &dyn Scan) -> Result<()> {
+        let new_val = scan.get_val(&self.src_field)?;
+        if self.val.as_ref().is_none_or(|val| new_val > *val) {
+            self.val = Some(new_val);
+        }
+        Ok(())
+    }
+
+    fn field_name(&self) -> &str {
+        &self.output_field
+    }
+
+    fn value(&self) -> Result<Constant> {
+        Ok(self.val.clone().unwrap_or(Constant::Int(0)))
+    }
+}
+
+/// The average aggregation function, for integer fields.
+/// The average is rounded towards zero.
+pub struct AvgFn {
+    src_field: String,
+    output_field: String,
+    sum: i64,
+    count: i64,
+}
+
+impl AvgFn {
+    pub fn new(src_field: String, output_field: String) -> Self {
+        Self {
+            src_field,
+            output_field,
+            sum: 0,
+            count: 0,
+        }
+    }
+}
+
+impl AggregationFn for AvgFn {
+    fn process_first(&mut self, scan: &dyn Scan) -> Result<()> {
+        self.sum = int_val(scan, &self.src_field)?;
+        self.count = 1;
+        Ok(())
+    }
+
+    fn process_next(&mut self, scan: &dyn Scan) -> Result<()> {
+        self.sum += int_val(scan, &self.src_field)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    fn field_name(&self) -> &str {
+        &self.output_field
+    }
+
+    fn value(&self) -> Result<Constant> {
+        Ok(Constant::Int((self.sum / self.count.max(1)) as i32))
+    }
+}
+
+fn int_val(scan: &dyn Scan, field_name: &str) -> Result<i64> {
+    scan.get_val(field_name)?
+        .as_int()
+        .map(i64::from)
+        .ok_or_else(|| AggregationFnError::NotAnInteger(field_name.to_string()).into())
+}
@@ -0,0 +1,33 @@
+use core::fmt;
+
+/// The value of a field: either an integer or a string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Constant {
+    Int(i32),
+    String(String),
+}
+
+impl fmt::Display for Constant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Constant::Int(n) => write!(f, "{}", n),
+            Constant::String(s) => write!(f, "'{}'", s),
+        }
+    }
+}
+
+impl Constant {
+    pub fn as_int(&self) -> Option<i32> {
+        match self {
+            Constant::Int(n) => Some(*n),
+            Constant::String(_) => None,
+        }
+    }
+
+    pub fn as_string(&self) -> Option<&str> {
+        match self {
+            Constant::Int(_) => None,
+            Constant::String(s) => Some(s),
+        }
+    }
+}
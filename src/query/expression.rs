@@ -0,0 +1,58 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+
+use crate::record::schema::Schema;
+
+use super::{constant::Constant, scan::Scan};
+
+/// The interface corresponding to SQL expressions: either a constant or a field name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expression {
+    Constant(Constant),
+    FieldName(String),
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expression::Constant(val) => write!(f, "{}", val),
+            Expression::FieldName(field_name) => write!(f, "{}", field_name),
+        }
+    }
+}
+
+impl Expression {
+    /// Evaluate the expression with respect to the current record of the specified scan.
+    pub fn evaluate(&self, scan: &dyn Scan) -> Result<Constant> {
+        match self {
+            Expression::Constant(val) => Ok(val.clone()),
+            Expression::FieldName(field_name) => scan.get_val(field_name),
+        }
+    }
+
+    pub fn is_field_name(&self) -> bool {
+        matches!(self, Expression::FieldName(_))
+    }
+
+    pub fn as_constant(&self) -> Option<&Constant> {
+        match self {
+            Expression::Constant(val) => Some(val),
+            Expression::FieldName(_) => None,
+        }
+    }
+
+    pub fn as_field_name(&self) -> Option<&str> {
+        match self {
+            Expression::Constant(_) => None,
+            Expression::FieldName(field_name) => Some(field_name),
+        }
+    }
+
+    /// Determine if all of the fields mentioned in this expression are contained in the specified schema.
+    pub fn applies_to(&self, schema: &Schema) -> bool {
+        match self {
+            Expression::Constant(_) => true,
+            Expression::FieldName(field_name) => schema.has_field(field_name),
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+
+use super::{aggregation_fn::AggregationFn, constant::Constant, scan::Scan};
+
+#[derive(Debug)]
+enum GroupByScanError {
+    FieldNotFound(String),
+    NoCurrentGroup,
+    FieldType(String),
+}
+
+impl std::error::Error for GroupByScanError {}
+impl fmt::Display for GroupByScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GroupByScanError::FieldNotFound(field_name) => {
+                write!(f, "field {} not found", field_name)
+            }
+            GroupByScanError::NoCurrentGroup => write!(f, "group by scan has no current group"),
+            GroupByScanError::FieldType(field_name) => {
+                write!(f, "field {} has the wrong type", field_name)
+            }
+        }
+    }
+}
+
+/// The scan class for the groupby operator.
+/// The underlying scan is sorted on the group fields, so that the records of a group are consecutive.
+/// Each record of the scan is a group: the values of its group fields, and the aggregations of its records.
+pub struct GroupByScan {
+    scan: Box<dyn Scan>,
+    group_fields: Vec<String>,
+    agg_fns: Vec<Box<dyn AggregationFn>>,
+    // the values of the group fields of the current group
+    group_val: Option<Vec<Constant>>,
+    more_groups: bool,
+}
+
+impl GroupByScan {
+    /// Create a groupby scan, given a grouped underlying scan.
+    pub fn new(
+        scan: Box<dyn Scan>,
+        group_fields: Vec<String>,
+        agg_fns: Vec<Box<dyn AggregationFn>>,
+    ) -> Result<Self> {
+        let mut scan = Self {
+            scan,
+            group_fields,
+            agg_fns,
+            group_val: None,
+            more_groups: false,
+        };
+        scan.before_first()?;
+        Ok(scan)
+    }
+
+    fn current_group_val(&self) -> Result<Vec<Constant>> {
+        self.group_fields
+            .iter()
+            .map(|field_name| self.scan.get_val(field_name))
+            .collect()
+    }
+}
+
+impl Scan for GroupByScan {
+    /// Position the scan before the first group.
+    /// Internally, the underlying scan is always positioned at the first record of a group,
+    /// which means that this method moves to the first underlying record.
+    fn before_first(&mut self) -> Result<()> {
+        self.group_val = None;
+        self.scan.before_first()?;
+        self.more_groups = self.scan.next()?;
+        Ok(())
+    }
+
+    /// Move to the next group.
+    /// The key of the group is determined by the group values at the current record.
+    /// The method repeatedly reads underlying records until it encounters a record having a different key.
+    /// The aggregation functions are called for each record in the group.
+    fn next(&mut self) -> Result<bool> {
+        if !self.more_groups {
+            self.group_val = None;
+            return Ok(false);
+        }
+        for agg_fn in self.agg_fns.iter_mut() {
+            agg_fn.process_first(self.scan.as_ref())?;
+        }
+        let group_val = self.current_group_val()?;
+        loop {
+            self.more_groups = self.scan.next()?;
+            if !self.more_groups || self.current_group_val()? != group_val {
+                break;
+            }
+            for agg_fn in self.agg_fns.iter_mut() {
+                agg_fn.process_next(self.scan.as_ref())?;
+            }
+        }
+        self.group_val = Some(group_val);
+        Ok(true)
+    }
+
+    fn get_int(&self, field_name: &str) -> Result<i32> {
+        self.get_val(field_name)?
+            .as_int()
+            .ok_or_else(|| GroupByScanError::FieldType(field_name.to_string()).into())
+    }
+
+    fn get_string(&self, field_name: &str) -> Result<String> {
+        self.get_val(field_name)?
+            .as_string()
+            .map(str::to_string)
+            .ok_or_else(|| GroupByScanError::FieldType(field_name.to_string()).into())
+    }
+
+    /// Return the value of the specified field in the current group.
+    /// If the field is a group field, then its value is the group's value;
+    /// otherwise, it is the value of the aggregation function of that name.
+    fn get_val(&self, field_name: &str) -> Result<Constant> {
+        let group_val = self
+            .group_val
+            .as_ref()
+            .ok_or(GroupByScanError::NoCurrentGroup)?;
+        if let Some(pos) = self.group_fields.iter().position(|f| f == field_name) {
+            return Ok(group_val[pos].clone());
+        }
+        self.agg_fns
+            .iter()
+            .find(|agg_fn| agg_fn.field_name() == field_name)
+            .ok_or_else(|| GroupByScanError::FieldNotFound(field_name.to_string()))?
+            .value()
+    }
+
+    /// Return true if the specified field is either a group field, or is the output field of an aggregation function.
+    fn has_field(&self, field_name: &str) -> bool {
+        self.group_fields.iter().any(|f| f == field_name)
+            || self
+                .agg_fns
+                .iter()
+                .any(|agg_fn| agg_fn.field_name() == field_name)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.scan.close()
+    }
+}
@@ -0,0 +1,272 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::record::temp_table::TempTable;
+
+use super::{constant::Constant, scan::Scan};
+
+#[derive(Debug)]
+enum HashJoinScanError {
+    NoCurrentRecord,
+    NoPartitions,
+    FieldType(String),
+}
+
+impl std::error::Error for HashJoinScanError {}
+impl fmt::Display for HashJoinScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HashJoinScanError::NoCurrentRecord => write!(f, "hash join scan has no current record"),
+            HashJoinScanError::NoPartitions => write!(f, "hash join scan has no partitions"),
+            HashJoinScanError::FieldType(field_name) => {
+                write!(f, "field {} does not have the requested type", field_name)
+            }
+        }
+    }
+}
+
+/// The scan class corresponding to the hash join relational algebra operator.
+/// The records of the RHS scan are read into a hash table keyed by their join value;
+/// each record of the LHS scan is then paired with the RHS records having its join value.
+/// For a grace hash join, both inputs are split into partitions by the hash of their join values,
+/// and each LHS partition is joined with the hash table of the matching RHS partition in turn.
+pub struct HashJoinScan {
+    lhs: Box<dyn Scan>,
+    join_field1: String,
+    join_field2: String,
+    // the RHS fields, in the order of the values of each RHS record in the hash table
+    fields2: Vec<String>,
+    table: HashMap<Constant, Vec<Vec<Constant>>>,
+    // the join value of the current LHS record, and the position of the current RHS record among its matches
+    current: Option<(Constant, usize)>,
+    // the LHS and RHS partitions of a grace hash join, and the index of the partition being joined
+    partitions: Vec<(TempTable, TempTable)>,
+    current_partition: usize,
+}
+
+impl HashJoinScan {
+    /// Create a hash join scan, building the hash table from the RHS scan, which is closed afterwards.
+    pub fn new(
+        lhs: Box<dyn Scan>,
+        mut rhs: Box<dyn Scan>,
+        join_field1: &str,
+        join_field2: &str,
+        fields2: &[String],
+    ) -> Result<Self> {
+        let table = Self::build_table(rhs.as_mut(), join_field2, fields2)?;
+        let mut scan = Self {
+            lhs,
+            join_field1: join_field1.to_string(),
+            join_field2: join_field2.to_string(),
+            fields2: fields2.to_vec(),
+            table,
+            current: None,
+            partitions: vec![],
+            current_partition: 0,
+        };
+        scan.before_first()?;
+        Ok(scan)
+    }
+
+    /// Create a hash join scan over pairs of LHS and RHS partitions, whose records having the same join value
+    /// are in the same pair. Fail if there are no partitions.
+    pub fn with_partitions(
+        partitions: Vec<(TempTable, TempTable)>,
+        join_field1: &str,
+        join_field2: &str,
+        fields2: &[String],
+    ) -> Result<Self> {
+        let (lhs, rhs) = partitions.first().ok_or(HashJoinScanError::NoPartitions)?;
+        let mut scan = Self::new(
+            Box::new(lhs.open()?),
+            Box::new(rhs.open()?),
+            join_field1,
+            join_field2,
+            fields2,
+        )?;
+        scan.partitions = partitions;
+        Ok(scan)
+    }
+
+    fn build_table(
+        rhs: &mut dyn Scan,
+        join_field2: &str,
+        fields2: &[String],
+    ) -> Result<HashMap<Constant, Vec<Vec<Constant>>>> {
+        let mut table: HashMap<Constant, Vec<Vec<Constant>>> = HashMap::new();
+        while rhs.next()? {
+            let vals = fields2
+                .iter()
+                .map(|field_name| rhs.get_val(field_name))
+                .collect::<Result<Vec<_>>>()?;
+            table
+                .entry(rhs.get_val(join_field2)?)
+                .or_default()
+                .push(vals);
+        }
+        rhs.close()?;
+        Ok(table)
+    }
+
+    /// Close the current LHS partition, and open the specified pair of partitions instead.
+    fn move_to_partition(&mut self, index: usize) -> Result<()> {
+        self.lhs.close()?;
+        let (lhs, rhs) = &self.partitions[index];
+        let mut rhs = rhs.open()?;
+        self.table = Self::build_table(&mut rhs, &self.join_field2, &self.fields2)?;
+        self.lhs = Box::new(lhs.open()?);
+        self.current_partition = index;
+        Ok(())
+    }
+
+    fn rhs_val(&self, field_name: &str) -> Option<Result<Constant>> {
+        let pos = self.fields2.iter().position(|f| f == field_name)?;
+        Some(
+            self.current
+                .as_ref()
+                .map(|(join_val, current)| self.table[join_val][*current][pos].clone())
+                .ok_or_else(|| HashJoinScanError::NoCurrentRecord.into()),
+        )
+    }
+}
+
+impl Scan for HashJoinScan {
+    /// Position the scan before the first record of the first partition.
+    fn before_first(&mut self) -> Result<()> {
+        self.current = None;
+        if self.current_partition != 0 {
+            self.move_to_partition(0)
+        } else {
+            self.lhs.before_first()
+        }
+    }
+
+    /// Move to the next RHS record matching the current LHS record,
+    /// or else to the next LHS record that has matching RHS records, moving on to the next partition as needed.
+    fn next(&mut self) -> Result<bool> {
+        if let Some((join_val, current)) = self.current.as_mut() {
+            if *current + 1 < self.table[join_val].len() {
+                *current += 1;
+                return Ok(true);
+            }
+        }
+        self.current = None;
+        loop {
+            while self.lhs.next()? {
+                let join_val = self.lhs.get_val(&self.join_field1)?;
+                if self.table.contains_key(&join_val) {
+                    self.current = Some((join_val, 0));
+                    return Ok(true);
+                }
+            }
+            if self.current_partition + 1 >= self.partitions.len() {
+                return Ok(false);
+            }
+            self.move_to_partition(self.current_partition + 1)?;
+        }
+    }
+
+    fn get_int(&self, field_name: &str) -> Result<i32> {
+        match self.rhs_val(field_name) {
+            Some(val) => val?
+                .as_int()
+                .ok_or_else(|| HashJoinScanError::FieldType(field_name.to_string()).into()),
+            None => self.lhs.get_int(field_name),
+        }
+    }
+
+    fn get_string(&self, field_name: &str) -> Result<String> {
+        match self.rhs_val(field_name) {
+            Some(val) => val?
+                .as_string()
+                .map(str::to_string)
+                .ok_or_else(|| HashJoinScanError::FieldType(field_name.to_string()).into()),
+            None => self.lhs.get_string(field_name),
+        }
+    }
+
+    fn get_val(&self, field_name: &str) -> Result<Constant> {
+        match self.rhs_val(field_name) {
+            Some(val) => val,
+            None => self.lhs.get_val(field_name),
+        }
+    }
+
+    fn has_field(&self, field_name: &str) -> bool {
+        self.fields2.iter().any(|f| f == field_name) || self.lhs.has_field(field_name)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.lhs.close()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+
+    use crate::{
+        query::scan::{Scan, UpdateScan},
+        record::{layout::Layout, schema::Schema, table_scan::TableScan},
+        server::simpledb::SimpleDB,
+    };
+
+    use super::HashJoinScan;
+
+    #[test]
+    fn test_hash_join_scan() {
+        let temp_dir = tempdir().unwrap();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+        let tx = db.new_tx().unwrap();
+
+        let mut schema1 = Schema::new();
+        schema1.add_int_field("A");
+        let layout1 = Layout::new(schema1);
+        let mut scan1 = TableScan::new(Arc::clone(&tx), "T1", layout1.clone()).unwrap();
+        for i in 0..20 {
+            scan1.insert().unwrap();
+            scan1.set_int("A", i).unwrap();
+        }
+        scan1.close().unwrap();
+
+        // every even value of C appears twice, and the odd values not at all
+        let mut schema2 = Schema::new();
+        schema2.add_int_field("C");
+        schema2.add_string_field("D", 9);
+        let layout2 = Layout::new(schema2.clone());
+        let mut scan2 = TableScan::new(Arc::clone(&tx), "T2", layout2.clone()).unwrap();
+        for i in 0..20 {
+            scan2.insert().unwrap();
+            scan2.set_int("C", (i % 10) * 2).unwrap();
+            scan2.set_string("D", &format!("d{}", i)).unwrap();
+        }
+        scan2.close().unwrap();
+
+        let scan1 = TableScan::new(Arc::clone(&tx), "T1", layout1).unwrap();
+        let scan2 = TableScan::new(Arc::clone(&tx), "T2", layout2).unwrap();
+        let mut scan =
+            HashJoinScan::new(Box::new(scan1), Box::new(scan2), "A", "C", schema2.fields())
+                .unwrap();
+        for _ in 0..2 {
+            let mut rows = vec![];
+            while scan.next().unwrap() {
+                let a = scan.get_int("A").unwrap();
+                assert_eq!(scan.get_int("C").unwrap(), a);
+                rows.push((a, scan.get_string("D").unwrap()));
+            }
+            assert_eq!(rows.len(), 20);
+            assert!(rows.iter().all(|(a, _)| a % 2 == 0));
+            assert!(
+                rows.contains(&(4, "d2".to_string())) && rows.contains(&(4, "d12".to_string()))
+            );
+            scan.before_first().unwrap();
+        }
+        assert!(scan.get_int("D").is_err());
+        scan.close().unwrap();
+
+        tx.lock().unwrap().commit().unwrap();
+    }
+}
@@ -0,0 +1,111 @@
+use anyhow::{Ok, Result};
+
+use crate::{index::index::Index, record::table_scan::TableScan};
+
+use super::{
+    constant::Constant,
+    scan::{Scan, UpdateScan},
+};
+
+/// The scan class corresponding to the index join relational algebra operator.
+/// For each record of the LHS scan, the scan looks up the join value in the index of the RHS table,
+/// and moves the RHS table scan to each matching record.
+pub struct IndexJoinScan {
+    lhs: Box<dyn Scan>,
+    index: Box<dyn Index>,
+    join_field: String,
+    rhs: TableScan,
+    // whether the LHS scan is positioned at a record
+    has_current: bool,
+}
+
+impl IndexJoinScan {
+    /// Create an index join scan for the specified LHS scan and RHS index.
+    pub fn new(
+        lhs: Box<dyn Scan>,
+        index: Box<dyn Index>,
+        join_field: &str,
+        rhs: TableScan,
+    ) -> Result<Self> {
+        let mut scan = Self {
+            lhs,
+            index,
+            join_field: join_field.to_string(),
+            rhs,
+            has_current: false,
+        };
+        scan.before_first()?;
+        Ok(scan)
+    }
+
+    fn reset_index(&mut self) -> Result<()> {
+        let search_key = self.lhs.get_val(&self.join_field)?;
+        self.index.before_first(&search_key)
+    }
+}
+
+impl Scan for IndexJoinScan {
+    /// Position the scan before the first record.
+    /// That is, the LHS scan will be positioned at its first record, and the index will be positioned before the first
+    /// record for the join value.
+    fn before_first(&mut self) -> Result<()> {
+        self.lhs.before_first()?;
+        self.has_current = self.lhs.next()?;
+        if self.has_current {
+            self.reset_index()?;
+        }
+        Ok(())
+    }
+
+    /// Move the scan to the next record.
+    /// The method moves to the next index record, if possible.
+    /// Otherwise, it moves to the next LHS record and the first index record.
+    fn next(&mut self) -> Result<bool> {
+        while self.has_current {
+            if self.index.next()? {
+                let rid = self.index.get_data_rid()?;
+                self.rhs.move_to_rid(rid)?;
+                return Ok(true);
+            }
+            self.has_current = self.lhs.next()?;
+            if self.has_current {
+                self.reset_index()?;
+            }
+        }
+        Ok(false)
+    }
+
+    fn get_int(&self, field_name: &str) -> Result<i32> {
+        if self.rhs.has_field(field_name) {
+            self.rhs.get_int(field_name)
+        } else {
+            self.lhs.get_int(field_name)
+        }
+    }
+
+    fn get_string(&self, field_name: &str) -> Result<String> {
+        if self.rhs.has_field(field_name) {
+            self.rhs.get_string(field_name)
+        } else {
+            self.lhs.get_string(field_name)
+        }
+    }
+
+    fn get_val(&self, field_name: &str) -> Result<Constant> {
+        if self.rhs.has_field(field_name) {
+            self.rhs.get_val(field_name)
+        } else {
+            self.lhs.get_val(field_name)
+        }
+    }
+
+    fn has_field(&self, field_name: &str) -> bool {
+        self.rhs.has_field(field_name) || self.lhs.has_field(field_name)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.lhs.close()?;
+        self.index.close()?;
+        self.rhs.close()
+    }
+}
@@ -0,0 +1,79 @@
+use anyhow::{Ok, Result};
+
+use crate::{index::index::Index, record::table_scan::TableScan};
+
+use super::{
+    constant::Constant,
+    scan::{Scan, UpdateScan},
+};
+
+/// The values that an index select searches the index for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexSearch {
+    /// The records whose indexed field equals the constant.
+    Eq(Constant),
+    /// The records whose indexed field lies between the bounds, inclusive; a missing bound leaves that end open.
+    Range(Option<Constant>, Option<Constant>),
+}
+
+/// The scan class corresponding to the index select relational algebra operator.
+/// The scan reads the index records having the search values, and moves the table scan to each of their data records.
+pub struct IndexSelectScan {
+    table_scan: TableScan,
+    index: Box<dyn Index>,
+    search: IndexSearch,
+}
+
+impl IndexSelectScan {
+    /// Create an index select scan for the specified index and search values.
+    pub fn new(table_scan: TableScan, index: Box<dyn Index>, search: IndexSearch) -> Result<Self> {
+        let mut scan = Self {
+            table_scan,
+            index,
+            search,
+        };
+        scan.before_first()?;
+        Ok(scan)
+    }
+}
+
+impl Scan for IndexSelectScan {
+    /// Position the scan before the first index record having the search values.
+    fn before_first(&mut self) -> Result<()> {
+        match &self.search {
+            IndexSearch::Eq(val) => self.index.before_first(val),
+            IndexSearch::Range(lo, hi) => self.index.before_first_range(lo.as_ref(), hi.as_ref()),
+        }
+    }
+
+    /// Move to the next index record, and position the table scan at the data record it refers to.
+    fn next(&mut self) -> Result<bool> {
+        if !self.index.next()? {
+            return Ok(false);
+        }
+        let rid = self.index.get_data_rid()?;
+        self.table_scan.move_to_rid(rid)?;
+        Ok(true)
+    }
+
+    fn get_int(&self, field_name: &str) -> Result<i32> {
+        self.table_scan.get_int(field_name)
+    }
+
+    fn get_string(&self, field_name: &str) -> Result<String> {
+        self.table_scan.get_string(field_name)
+    }
+
+    fn get_val(&self, field_name: &str) -> Result<Constant> {
+        self.table_scan.get_val(field_name)
+    }
+
+    fn has_field(&self, field_name: &str) -> bool {
+        self.table_scan.has_field(field_name)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.index.close()?;
+        self.table_scan.close()
+    }
+}
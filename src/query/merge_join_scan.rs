@@ -0,0 +1,125 @@
+use anyhow::{Ok, Result};
+use std::cmp::Ordering;
+
+use super::{
+    constant::Constant,
+    scan::Scan,
+    sort_scan::{SortPosition, SortScan},
+};
+
+/// The scan class for the mergejoin operator.
+/// Both inputs are sorted on their join fields. The scan moves through them together, and pairs each LHS record
+/// with the group of RHS records having its join value, returning to the start of the group for each LHS record
+/// that has the same join value as the previous one.
+pub struct MergeJoinScan {
+    lhs: Box<dyn Scan>,
+    rhs: SortScan,
+    join_field1: String,
+    join_field2: String,
+    // the join value of the current group of RHS records, and the position of its first record
+    join_val: Option<Constant>,
+    group_start: Option<SortPosition>,
+}
+
+impl MergeJoinScan {
+    /// Create a mergejoin scan for the two sorted underlying scans.
+    pub fn new(
+        lhs: Box<dyn Scan>,
+        rhs: SortScan,
+        join_field1: &str,
+        join_field2: &str,
+    ) -> Result<Self> {
+        let mut scan = Self {
+            lhs,
+            rhs,
+            join_field1: join_field1.to_string(),
+            join_field2: join_field2.to_string(),
+            join_val: None,
+            group_start: None,
+        };
+        scan.before_first()?;
+        Ok(scan)
+    }
+
+    /// Return true if the join value of the specified field is that of the current group.
+    fn in_group(&self, scan: &dyn Scan, field_name: &str) -> Result<bool> {
+        Ok(self.join_val.as_ref() == Some(&scan.get_val(field_name)?))
+    }
+}
+
+impl Scan for MergeJoinScan {
+    fn before_first(&mut self) -> Result<()> {
+        self.join_val = None;
+        self.group_start = None;
+        self.lhs.before_first()?;
+        self.rhs.before_first()
+    }
+
+    /// Move to the next record.
+    /// If the next RHS record has the same join value as the current group, then move to it.
+    /// Otherwise, if the next LHS record has the join value of the group, then move the RHS back to the start of
+    /// the group. Otherwise, repeatedly move the scan having the smaller join value until a common value is found;
+    /// the RHS record having that value starts a new group.
+    fn next(&mut self) -> Result<bool> {
+        let mut has_more2 = self.rhs.next()?;
+        if has_more2 && self.in_group(&self.rhs, &self.join_field2)? {
+            return Ok(true);
+        }
+
+        let mut has_more1 = self.lhs.next()?;
+        if has_more1 && self.in_group(self.lhs.as_ref(), &self.join_field1)? {
+            if let Some(group_start) = &self.group_start {
+                self.rhs.restore_position(group_start)?;
+            }
+            return Ok(true);
+        }
+
+        while has_more1 && has_more2 {
+            let val1 = self.lhs.get_val(&self.join_field1)?;
+            let val2 = self.rhs.get_val(&self.join_field2)?;
+            match val1.cmp(&val2) {
+                Ordering::Less => has_more1 = self.lhs.next()?,
+                Ordering::Greater => has_more2 = self.rhs.next()?,
+                Ordering::Equal => {
+                    self.group_start = Some(self.rhs.save_position()?);
+                    self.join_val = Some(val2);
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    fn get_int(&self, field_name: &str) -> Result<i32> {
+        if self.rhs.has_field(field_name) {
+            self.rhs.get_int(field_name)
+        } else {
+            self.lhs.get_int(field_name)
+        }
+    }
+
+    fn get_string(&self, field_name: &str) -> Result<String> {
+        if self.rhs.has_field(field_name) {
+            self.rhs.get_string(field_name)
+        } else {
+            self.lhs.get_string(field_name)
+        }
+    }
+
+    fn get_val(&self, field_name: &str) -> Result<Constant> {
+        if self.rhs.has_field(field_name) {
+            self.rhs.get_val(field_name)
+        } else {
+            self.lhs.get_val(field_name)
+        }
+    }
+
+    fn has_field(&self, field_name: &str) -> bool {
+        self.lhs.has_field(field_name) || self.rhs.has_field(field_name)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.lhs.close()?;
+        self.rhs.close()
+    }
+}
@@ -0,0 +1,163 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    buffer::needs::best_factor,
+    record::{chunk_scan::ChunkScan, temp_table::TempTable},
+    tx::transaction::Transaction,
+};
+
+use super::{constant::Constant, scan::Scan};
+
+#[derive(Debug)]
+enum MultibufferProductScanError {
+    NoCurrentRecord,
+}
+
+impl std::error::Error for MultibufferProductScanError {}
+impl fmt::Display for MultibufferProductScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MultibufferProductScanError::NoCurrentRecord => {
+                write!(f, "multibuffer product scan has no current record")
+            }
+        }
+    }
+}
+
+/// The scan class for the multi-buffer version of the product operator.
+/// The RHS table is read a chunk at a time, each chunk having as many blocks as the available buffers allow.
+/// The LHS scan is read once per chunk, and each of its records is paired with every record of the pinned chunk,
+/// so that the blocks of the RHS are read only once.
+pub struct MultibufferProductScan {
+    tx: Arc<Mutex<Transaction>>,
+    lhs: Box<dyn Scan>,
+    rhs: TempTable,
+    chunk: Option<ChunkScan>,
+    file_size: usize,
+    chunk_size: usize,
+    next_block: usize,
+    // whether the LHS scan is positioned at a record
+    has_current: bool,
+}
+
+impl MultibufferProductScan {
+    /// Create a multi-buffer product scan of the LHS scan and the RHS table.
+    pub fn new(tx: Arc<Mutex<Transaction>>, lhs: Box<dyn Scan>, rhs: TempTable) -> Result<Self> {
+        let filename = format!("{}.tbl", rhs.table_name());
+        let (file_size, available) = {
+            let mut tx = tx.lock().unwrap();
+            (tx.size(&filename)?, tx.available_buffs())
+        };
+        let mut scan = Self {
+            tx,
+            lhs,
+            rhs,
+            chunk: None,
+            file_size,
+            chunk_size: best_factor(available, file_size),
+            next_block: 0,
+            has_current: false,
+        };
+        scan.before_first()?;
+        Ok(scan)
+    }
+
+    /// Close the current chunk and pin the next one, then move the LHS scan to its first record.
+    /// Return false if there are no more chunks.
+    fn use_next_chunk(&mut self) -> Result<bool> {
+        if let Some(mut chunk) = self.chunk.take() {
+            chunk.close()?;
+        }
+        if self.next_block >= self.file_size {
+            return Ok(false);
+        }
+        let end_block = (self.next_block + self.chunk_size).min(self.file_size) - 1;
+        self.chunk = Some(ChunkScan::new(
+            Arc::clone(&self.tx),
+            self.rhs.table_name(),
+            self.rhs.layout().clone(),
+            self.next_block,
+            end_block,
+        )?);
+        self.next_block = end_block + 1;
+        self.lhs.before_first()?;
+        self.has_current = self.lhs.next()?;
+        Ok(true)
+    }
+
+    fn current_chunk(&self) -> Result<&ChunkScan> {
+        self.chunk
+            .as_ref()
+            .ok_or_else(|| MultibufferProductScanError::NoCurrentRecord.into())
+    }
+}
+
+impl Scan for MultibufferProductScan {
+    /// Position the scan before the first record of the first chunk.
+    fn before_first(&mut self) -> Result<()> {
+        if let Some(mut chunk) = self.chunk.take() {
+            chunk.close()?;
+        }
+        self.next_block = 0;
+        self.has_current = false;
+        Ok(())
+    }
+
+    /// Move to the next record in the current chunk, for the current LHS record.
+    /// If there is none, then move to the next LHS record and the start of the chunk;
+    /// once the LHS scan is exhausted, move on to the next chunk.
+    fn next(&mut self) -> Result<bool> {
+        loop {
+            if let Some(chunk) = self.chunk.as_mut() {
+                if self.has_current {
+                    if chunk.next()? {
+                        return Ok(true);
+                    }
+                    chunk.before_first()?;
+                    self.has_current = self.lhs.next()?;
+                    continue;
+                }
+            }
+            if !self.use_next_chunk()? {
+                return Ok(false);
+            }
+        }
+    }
+
+    fn get_int(&self, field_name: &str) -> Result<i32> {
+        if self.rhs.layout().schema().has_field(field_name) {
+            self.current_chunk()?.get_int(field_name)
+        } else {
+            self.lhs.get_int(field_name)
+        }
+    }
+
+    fn get_string(&self, field_name: &str) -> Result<String> {
+        if self.rhs.layout().schema().has_field(field_name) {
+            self.current_chunk()?.get_string(field_name)
+        } else {
+            self.lhs.get_string(field_name)
+        }
+    }
+
+    fn get_val(&self, field_name: &str) -> Result<Constant> {
+        if self.rhs.layout().schema().has_field(field_name) {
+            self.current_chunk()?.get_val(field_name)
+        } else {
+            self.lhs.get_val(field_name)
+        }
+    }
+
+    fn has_field(&self, field_name: &str) -> bool {
+        self.lhs.has_field(field_name) || self.rhs.layout().schema().has_field(field_name)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        if let Some(mut chunk) = self.chunk.take() {
+            chunk.close()?;
+        }
+        self.lhs.close()
+    }
+}
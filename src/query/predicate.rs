@@ -0,0 +1,145 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+
+use crate::{plan::plan::Plan, record::schema::Schema};
+
+use super::{
+    constant::Constant,
+    scan::Scan,
+    term::{Operator, Term},
+};
+
+/// A predicate is a Boolean combination of terms.
+/// An empty predicate is always satisfied.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Predicate {
+    terms: Vec<Term>,
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let terms: Vec<String> = self.terms.iter().map(|term| term.to_string()).collect();
+        write!(f, "{}", terms.join(" and "))
+    }
+}
+
+impl Predicate {
+    /// Create an empty predicate, corresponding to "true".
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a predicate containing a single term.
+    pub fn from_term(term: Term) -> Self {
+        Self { terms: vec![term] }
+    }
+
+    pub fn terms(&self) -> &[Term] {
+        &self.terms
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Modify the predicate to be the conjunction of itself and the specified predicate.
+    pub fn conjoin_with(&mut self, pred: Predicate) {
+        self.terms.extend(pred.terms);
+    }
+
+    /// Return true if the predicate evaluates to true with respect to the specified scan.
+    pub fn is_satisfied(&self, scan: &dyn Scan) -> Result<bool> {
+        for term in &self.terms {
+            if !term.is_satisfied(scan)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Calculate the extent to which selecting on the predicate reduces the number of records output by a query.
+    pub fn reduction_factor(&self, plan: &dyn Plan) -> usize {
+        self.terms.iter().fold(1, |factor, term| {
+            factor.saturating_mul(term.reduction_factor(plan))
+        })
+    }
+
+    /// Return the subpredicate that applies to the specified schema, if any.
+    pub fn select_sub_pred(&self, schema: &Schema) -> Option<Predicate> {
+        let terms: Vec<Term> = self
+            .terms
+            .iter()
+            .filter(|term| term.applies_to(schema))
+            .cloned()
+            .collect();
+
+        if terms.is_empty() {
+            None
+        } else {
+            Some(Predicate { terms })
+        }
+    }
+
+    /// Return the subpredicate consisting of terms that apply to the union of the two specified schemas,
+    /// but not to either schema separately.
+    pub fn join_sub_pred(&self, schema1: &Schema, schema2: &Schema) -> Option<Predicate> {
+        let mut union = Schema::new();
+        union.add_all(schema1);
+        union.add_all(schema2);
+
+        let terms: Vec<Term> = self
+            .terms
+            .iter()
+            .filter(|term| {
+                !term.applies_to(schema1) && !term.applies_to(schema2) && term.applies_to(&union)
+            })
+            .cloned()
+            .collect();
+
+        if terms.is_empty() {
+            None
+        } else {
+            Some(Predicate { terms })
+        }
+    }
+
+    /// Determine if there is a term of the form "F=c" where F is the specified field and c is some constant.
+    /// If so, the method returns that constant.
+    pub fn equates_with_constant(&self, field_name: &str) -> Option<&Constant> {
+        self.terms
+            .iter()
+            .find_map(|term| term.equates_with_constant(field_name))
+    }
+
+    /// Return the range of values that the terms comparing the specified field with a constant allow,
+    /// as an inclusive lower bound and an inclusive upper bound; a missing bound leaves that end open.
+    /// Strict comparisons are widened to their inclusive counterparts, so the predicate must still be
+    /// checked against the records in the range. Return None if no term restricts the field.
+    pub fn range_for(&self, field_name: &str) -> Option<(Option<Constant>, Option<Constant>)> {
+        let mut lo: Option<&Constant> = None;
+        let mut hi: Option<&Constant> = None;
+        let mut found = false;
+        for (op, val) in self
+            .terms
+            .iter()
+            .filter_map(|term| term.compares_with_constant(field_name))
+        {
+            found = true;
+            if matches!(op, Operator::Eq | Operator::Gt | Operator::Ge) {
+                lo = lo.max(Some(val));
+            }
+            if matches!(op, Operator::Eq | Operator::Lt | Operator::Le) {
+                hi = Some(hi.map_or(val, |hi| hi.min(val)));
+            }
+        }
+        found.then(|| (lo.cloned(), hi.cloned()))
+    }
+
+    /// Determine if there is a term of the form "F1=F2" where F1 is the specified field and F2 is another field.
+    /// If so, the method returns the name of that field.
+    pub fn equates_with_field(&self, field_name: &str) -> Option<&str> {
+        self.terms
+            .iter()
+            .find_map(|term| term.equates_with_field(field_name))
+    }
+}
@@ -0,0 +1,181 @@
+use anyhow::{Ok, Result};
+
+use super::{constant::Constant, scan::Scan};
+
+/// The scan class corresponding to the product relational algebra operator.
+/// The scan pairs every record of the first scan with every record of the second scan.
+pub struct ProductScan {
+    scan1: Box<dyn Scan>,
+    scan2: Box<dyn Scan>,
+    // whether the first scan is positioned at a record
+    has_current: bool,
+}
+
+impl ProductScan {
+    pub fn new(scan1: Box<dyn Scan>, scan2: Box<dyn Scan>) -> Result<Self> {
+        let mut scan = Self {
+            scan1,
+            scan2,
+            has_current: false,
+        };
+        scan.before_first()?;
+        Ok(scan)
+    }
+}
+
+impl Scan for ProductScan {
+    /// Position the scan before its first record.
+    /// In particular, the LHS scan is positioned at its first record, and the RHS scan is positioned before its first record.
+    fn before_first(&mut self) -> Result<()> {
+        self.scan1.before_first()?;
+        self.has_current = self.scan1.next()?;
+        self.scan2.before_first()
+    }
+
+    /// Move the scan to the next record.
+    /// The method moves to the next RHS record, if possible.
+    /// Otherwise, it moves to the next LHS record and the first RHS record.
+    fn next(&mut self) -> Result<bool> {
+        if !self.has_current {
+            return Ok(false);
+        }
+
+        if self.scan2.next()? {
+            return Ok(true);
+        }
+
+        self.scan2.before_first()?;
+        if !self.scan2.next()? {
+            return Ok(false);
+        }
+        self.has_current = self.scan1.next()?;
+        Ok(self.has_current)
+    }
+
+    fn get_int(&self, field_name: &str) -> Result<i32> {
+        if self.scan1.has_field(field_name) {
+            self.scan1.get_int(field_name)
+        } else {
+            self.scan2.get_int(field_name)
+        }
+    }
+
+    fn get_string(&self, field_name: &str) -> Result<String> {
+        if self.scan1.has_field(field_name) {
+            self.scan1.get_string(field_name)
+        } else {
+            self.scan2.get_string(field_name)
+        }
+    }
+
+    fn get_val(&self, field_name: &str) -> Result<Constant> {
+        if self.scan1.has_field(field_name) {
+            self.scan1.get_val(field_name)
+        } else {
+            self.scan2.get_val(field_name)
+        }
+    }
+
+    fn has_field(&self, field_name: &str) -> bool {
+        self.scan1.has_field(field_name) || self.scan2.has_field(field_name)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.scan1.close()?;
+        self.scan2.close()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+
+    use crate::{
+        query::{
+            expression::Expression,
+            predicate::Predicate,
+            project_scan::ProjectScan,
+            scan::{Scan, UpdateScan},
+            select_scan::SelectScan,
+            term::Term,
+        },
+        record::{layout::Layout, schema::Schema, table_scan::TableScan},
+        server::simpledb::SimpleDB,
+    };
+
+    use super::ProductScan;
+
+    #[test]
+    fn test_product_scan() {
+        let temp_dir = tempdir().unwrap();
+        let db_dir = temp_dir.path().to_str().unwrap();
+
+        let db = SimpleDB::new(db_dir, 800, 8).unwrap();
+        let tx = db.new_tx().unwrap();
+
+        let mut schema1 = Schema::new();
+        schema1.add_int_field("A");
+        schema1.add_string_field("B", 9);
+        let layout1 = Layout::new(schema1);
+        let mut scan1 = TableScan::new(Arc::clone(&tx), "T1", layout1.clone()).unwrap();
+        for i in 0..20 {
+            scan1.insert().unwrap();
+            scan1.set_int("A", i).unwrap();
+            scan1.set_string("B", &format!("aaa{}", i)).unwrap();
+        }
+        scan1.close().unwrap();
+
+        let mut schema2 = Schema::new();
+        schema2.add_int_field("C");
+        schema2.add_string_field("D", 9);
+        let layout2 = Layout::new(schema2);
+        let mut scan2 = TableScan::new(Arc::clone(&tx), "T2", layout2.clone()).unwrap();
+        for i in 0..20 {
+            scan2.insert().unwrap();
+            scan2.set_int("C", 19 - i).unwrap();
+            scan2.set_string("D", &format!("bbb{}", 19 - i)).unwrap();
+        }
+        scan2.close().unwrap();
+
+        let scan1 = TableScan::new(Arc::clone(&tx), "T1", layout1.clone()).unwrap();
+        let scan2 = TableScan::new(Arc::clone(&tx), "T2", layout2).unwrap();
+        let mut product_scan = ProductScan::new(Box::new(scan1), Box::new(scan2)).unwrap();
+        let mut count = 0;
+        while product_scan.next().unwrap() {
+            count += 1;
+        }
+        assert_eq!(count, 400);
+
+        // select B, D from T1, T2 where A = C
+        let term = Term::new(
+            Expression::FieldName("A".to_string()),
+            Expression::FieldName("C".to_string()),
+        );
+        product_scan.before_first().unwrap();
+        let select_scan = SelectScan::new(Box::new(product_scan), Predicate::from_term(term));
+        let mut project_scan = ProjectScan::new(
+            Box::new(select_scan),
+            vec!["B".to_string(), "D".to_string()],
+        );
+        let mut count = 0;
+        while project_scan.next().unwrap() {
+            let b = project_scan.get_string("B").unwrap();
+            let d = project_scan.get_string("D").unwrap();
+            assert_eq!(b[3..], d[3..]);
+            count += 1;
+        }
+        project_scan.close().unwrap();
+        assert_eq!(count, 20);
+
+        // the product of a table with an empty table is empty
+        let scan1 = TableScan::new(Arc::clone(&tx), "T1", layout1.clone()).unwrap();
+        let empty = TableScan::new(Arc::clone(&tx), "T3", layout1).unwrap();
+        let mut product_scan = ProductScan::new(Box::new(scan1), Box::new(empty)).unwrap();
+        assert!(!product_scan.next().unwrap());
+        product_scan.close().unwrap();
+
+        tx.lock().unwrap().commit().unwrap();
+    }
+}
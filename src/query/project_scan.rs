@@ -0,0 +1,75 @@
+use anyhow::Result;
+use core::fmt;
+
+use super::{constant::Constant, scan::Scan};
+
+#[derive(Debug)]
+enum ProjectScanError {
+    FieldNotFound(String),
+}
+
+impl std::error::Error for ProjectScanError {}
+impl fmt::Display for ProjectScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProjectScanError::FieldNotFound(field_name) => {
+                write!(f, "field {} not found", field_name)
+            }
+        }
+    }
+}
+
+/// The scan class corresponding to the project relational algebra operator.
+/// All methods except has_field delegate their work to the underlying scan.
+pub struct ProjectScan {
+    scan: Box<dyn Scan>,
+    fields: Vec<String>,
+}
+
+impl ProjectScan {
+    pub fn new(scan: Box<dyn Scan>, fields: Vec<String>) -> Self {
+        Self { scan, fields }
+    }
+
+    fn check_field(&self, field_name: &str) -> Result<()> {
+        if self.has_field(field_name) {
+            Ok(())
+        } else {
+            Err(ProjectScanError::FieldNotFound(field_name.to_string()).into())
+        }
+    }
+}
+
+impl Scan for ProjectScan {
+    fn before_first(&mut self) -> Result<()> {
+        self.scan.before_first()
+    }
+
+    fn next(&mut self) -> Result<bool> {
+        self.scan.next()
+    }
+
+    fn get_int(&self, field_name: &str) -> Result<i32> {
+        self.check_field(field_name)?;
+        self.scan.get_int(field_name)
+    }
+
+    fn get_string(&self, field_name: &str) -> Result<String> {
+        self.check_field(field_name)?;
+        self.scan.get_string(field_name)
+    }
+
+    fn get_val(&self, field_name: &str) -> Result<Constant> {
+        self.check_field(field_name)?;
+        self.scan.get_val(field_name)
+    }
+
+    /// Return true if the specified field is in the projection list.
+    fn has_field(&self, field_name: &str) -> bool {
+        self.fields.iter().any(|field| field == field_name)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.scan.close()
+    }
+}
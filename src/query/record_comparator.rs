@@ -0,0 +1,35 @@
+use anyhow::{Ok, Result};
+use std::cmp::Ordering;
+
+use super::scan::Scan;
+
+/// A comparator for scans, which compares the current records of two scans on a list of sort fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordComparator {
+    fields: Vec<String>,
+}
+
+impl RecordComparator {
+    /// Create a comparator using the specified fields, in order of significance.
+    pub fn new(fields: &[String]) -> Self {
+        Self {
+            fields: fields.to_vec(),
+        }
+    }
+
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    /// Compare the current records of the two specified scans.
+    /// The sort fields are considered in turn; the first field whose values differ determines the result.
+    pub fn compare(&self, scan1: &dyn Scan, scan2: &dyn Scan) -> Result<Ordering> {
+        for field_name in &self.fields {
+            let ordering = scan1.get_val(field_name)?.cmp(&scan2.get_val(field_name)?);
+            if ordering != Ordering::Equal {
+                return Ok(ordering);
+            }
+        }
+        Ok(Ordering::Equal)
+    }
+}
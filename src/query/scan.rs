@@ -0,0 +1,53 @@
+use anyhow::Result;
+
+use crate::record::rid::RecordId;
+
+use super::constant::Constant;
+
+/// The interface implemented by all query scans.
+/// A scan is positioned before its first record on creation, and moves forward with `next`.
+pub trait Scan {
+    /// Position the scan before its first record. A subsequent call to `next` will return the first record.
+    fn before_first(&mut self) -> Result<()>;
+
+    /// Move the scan to the next record. Return false if there is no next record.
+    fn next(&mut self) -> Result<bool>;
+
+    fn get_int(&self, field_name: &str) -> Result<i32>;
+
+    fn get_string(&self, field_name: &str) -> Result<String>;
+
+    /// Return the value of the specified field in the current record, expressed as a constant.
+    fn get_val(&self, field_name: &str) -> Result<Constant>;
+
+    fn has_field(&self, field_name: &str) -> bool;
+
+    /// Close the scan and its subscans, if any.
+    fn close(&mut self) -> Result<()>;
+
+    /// Return this scan as an update scan, if its records can be modified.
+    fn as_update_scan(&mut self) -> Option<&mut dyn UpdateScan> {
+        None
+    }
+}
+
+/// The interface implemented by all updateable scans.
+pub trait UpdateScan: Scan {
+    fn set_val(&mut self, field_name: &str, val: &Constant) -> Result<()>;
+
+    fn set_int(&mut self, field_name: &str, val: i32) -> Result<()>;
+
+    fn set_string(&mut self, field_name: &str, val: &str) -> Result<()>;
+
+    /// Insert a new record somewhere in the scan, and make it the current record.
+    fn insert(&mut self) -> Result<()>;
+
+    /// Delete the current record from the scan.
+    fn delete(&mut self) -> Result<()>;
+
+    /// Return the id of the current record.
+    fn get_rid(&mut self) -> Result<RecordId>;
+
+    /// Position the scan so that the current record has the specified id.
+    fn move_to_rid(&mut self, rid: RecordId) -> Result<()>;
+}
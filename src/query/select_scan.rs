@@ -0,0 +1,198 @@
+use anyhow::Result;
+use core::fmt;
+
+use crate::record::rid::RecordId;
+
+use super::{
+    constant::Constant,
+    predicate::Predicate,
+    scan::{Scan, UpdateScan},
+};
+
+#[derive(Debug)]
+enum SelectScanError {
+    NotUpdatable,
+}
+
+impl std::error::Error for SelectScanError {}
+impl fmt::Display for SelectScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SelectScanError::NotUpdatable => write!(f, "underlying scan is not updatable"),
+        }
+    }
+}
+
+/// The scan class corresponding to the select relational algebra operator.
+/// All methods except next delegate their work to the underlying scan.
+/// The scan is updatable whenever its underlying scan is.
+pub struct SelectScan {
+    scan: Box<dyn Scan>,
+    pred: Predicate,
+}
+
+impl SelectScan {
+    pub fn new(scan: Box<dyn Scan>, pred: Predicate) -> Self {
+        Self { scan, pred }
+    }
+
+    fn updatable(&mut self) -> Result<&mut dyn UpdateScan> {
+        self.scan
+            .as_update_scan()
+            .ok_or_else(|| SelectScanError::NotUpdatable.into())
+    }
+}
+
+impl Scan for SelectScan {
+    fn before_first(&mut self) -> Result<()> {
+        self.scan.before_first()
+    }
+
+    /// Move to the next record of the underlying scan that satisfies the predicate.
+    fn next(&mut self) -> Result<bool> {
+        while self.scan.next()? {
+            if self.pred.is_satisfied(self.scan.as_ref())? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn get_int(&self, field_name: &str) -> Result<i32> {
+        self.scan.get_int(field_name)
+    }
+
+    fn get_string(&self, field_name: &str) -> Result<String> {
+        self.scan.get_string(field_name)
+    }
+
+    fn get_val(&self, field_name: &str) -> Result<Constant> {
+        self.scan.get_val(field_name)
+    }
+
+    fn has_field(&self, field_name: &str) -> bool {
+        self.scan.has_field(field_name)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.scan.close()
+    }
+
+    fn as_update_scan(&mut self) -> Option<&mut dyn UpdateScan> {
+        if self.scan.as_update_scan().is_some() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+impl UpdateScan for SelectScan {
+    fn set_val(&mut self, field_name: &str, val: &Constant) -> Result<()> {
+        self.updatable()?.set_val(field_name, val)
+    }
+
+    fn set_int(&mut self, field_name: &str, val: i32) -> Result<()> {
+        self.updatable()?.set_int(field_name, val)
+    }
+
+    fn set_string(&mut self, field_name: &str, val: &str) -> Result<()> {
+        self.updatable()?.set_string(field_name, val)
+    }
+
+    fn insert(&mut self) -> Result<()> {
+        self.updatable()?.insert()
+    }
+
+    fn delete(&mut self) -> Result<()> {
+        self.updatable()?.delete()
+    }
+
+    fn get_rid(&mut self) -> Result<RecordId> {
+        self.updatable()?.get_rid()
+    }
+
+    fn move_to_rid(&mut self, rid: RecordId) -> Result<()> {
+        self.updatable()?.move_to_rid(rid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+
+    use crate::{
+        query::{
+            constant::Constant,
+            expression::Expression,
+            predicate::Predicate,
+            project_scan::ProjectScan,
+            scan::{Scan, UpdateScan},
+            term::Term,
+        },
+        record::{layout::Layout, schema::Schema, table_scan::TableScan},
+        server::simpledb::SimpleDB,
+    };
+
+    use super::SelectScan;
+
+    #[test]
+    fn test_select_scan() {
+        let temp_dir = tempdir().unwrap();
+        let db_dir = temp_dir.path().to_str().unwrap();
+
+        let db = SimpleDB::new(db_dir, 800, 8).unwrap();
+        let tx = db.new_tx().unwrap();
+
+        let mut schema = Schema::new();
+        schema.add_int_field("A");
+        schema.add_string_field("B", 9);
+        let layout = Layout::new(schema);
+
+        let mut scan = TableScan::new(Arc::clone(&tx), "T", layout.clone()).unwrap();
+        for i in 0..50 {
+            scan.insert().unwrap();
+            scan.set_int("A", i % 10).unwrap();
+            scan.set_string("B", &format!("rec{}", i)).unwrap();
+        }
+        scan.close().unwrap();
+
+        // select B from T where A = 7
+        let term = Term::new(
+            Expression::FieldName("A".to_string()),
+            Expression::Constant(Constant::Int(7)),
+        );
+        let table_scan = TableScan::new(Arc::clone(&tx), "T", layout.clone()).unwrap();
+        let select_scan = SelectScan::new(Box::new(table_scan), Predicate::from_term(term.clone()));
+        let mut project_scan = ProjectScan::new(Box::new(select_scan), vec!["B".to_string()]);
+        let mut result = vec![];
+        while project_scan.next().unwrap() {
+            result.push(project_scan.get_string("B").unwrap());
+            assert!(project_scan.get_int("A").is_err());
+        }
+        project_scan.close().unwrap();
+        assert_eq!(result, ["rec7", "rec17", "rec27", "rec37", "rec47"]);
+
+        // delete from T where A = 7, through the select scan
+        let table_scan = TableScan::new(Arc::clone(&tx), "T", layout.clone()).unwrap();
+        let mut select_scan = SelectScan::new(Box::new(table_scan), Predicate::from_term(term));
+        let update_scan = select_scan.as_update_scan().unwrap();
+        while update_scan.next().unwrap() {
+            update_scan.delete().unwrap();
+        }
+        update_scan.close().unwrap();
+
+        let mut scan = TableScan::new(Arc::clone(&tx), "T", layout).unwrap();
+        let mut count = 0;
+        while scan.next().unwrap() {
+            assert_ne!(scan.get_val("A").unwrap(), Constant::Int(7));
+            count += 1;
+        }
+        scan.close().unwrap();
+        assert_eq!(count, 45);
+
+        tx.lock().unwrap().commit().unwrap();
+    }
+}
@@ -0,0 +1,159 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::cmp::Ordering;
+
+use crate::record::{rid::RecordId, table_scan::TableScan, temp_table::TempTable};
+
+use super::{
+    constant::Constant,
+    record_comparator::RecordComparator,
+    scan::{Scan, UpdateScan},
+};
+
+#[derive(Debug)]
+enum SortScanError {
+    NoCurrentRecord,
+}
+
+impl std::error::Error for SortScanError {}
+impl fmt::Display for SortScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SortScanError::NoCurrentRecord => write!(f, "sort scan has no current record"),
+        }
+    }
+}
+
+/// A saved position of a sort scan: the record of each run it was at, and which run was current.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortPosition {
+    rids: Vec<Option<RecordId>>,
+    current: Option<usize>,
+}
+
+/// The scan class for the sort operator.
+/// The scan merges the sorted runs of its input: each run is a sorted temporary table,
+/// and the current record is the smallest of the records the runs are at.
+pub struct SortScan {
+    scans: Vec<TableScan>,
+    // whether each run's scan is positioned at a record
+    has_more: Vec<bool>,
+    current: Option<usize>,
+    comparator: RecordComparator,
+}
+
+impl SortScan {
+    /// Create a sort scan that merges the specified runs.
+    pub fn new(runs: &[TempTable], comparator: RecordComparator) -> Result<Self> {
+        let scans = runs
+            .iter()
+            .map(|run| run.open())
+            .collect::<Result<Vec<_>>>()?;
+        let mut scan = Self {
+            has_more: vec![false; scans.len()],
+            scans,
+            current: None,
+            comparator,
+        };
+        scan.before_first()?;
+        Ok(scan)
+    }
+
+    /// Save the position of the current record, so that it can be restored at a later time.
+    pub fn save_position(&mut self) -> Result<SortPosition> {
+        let mut rids = Vec::with_capacity(self.scans.len());
+        for (scan, has_more) in self.scans.iter_mut().zip(&self.has_more) {
+            rids.push(if *has_more {
+                Some(scan.get_rid()?)
+            } else {
+                None
+            });
+        }
+        Ok(SortPosition {
+            rids,
+            current: self.current,
+        })
+    }
+
+    /// Move the scan to its previously-saved position.
+    pub fn restore_position(&mut self, position: &SortPosition) -> Result<()> {
+        for (i, rid) in position.rids.iter().enumerate() {
+            self.has_more[i] = rid.is_some();
+            if let Some(rid) = rid {
+                self.scans[i].move_to_rid(*rid)?;
+            }
+        }
+        self.current = position.current;
+        Ok(())
+    }
+
+    fn current_scan(&self) -> Result<&TableScan> {
+        self.current
+            .map(|current| &self.scans[current])
+            .ok_or_else(|| SortScanError::NoCurrentRecord.into())
+    }
+}
+
+impl Scan for SortScan {
+    /// Position the scan before the first record in sorted order.
+    /// Each run's scan is positioned at its first record, so that the smallest of them is the next record.
+    fn before_first(&mut self) -> Result<()> {
+        self.current = None;
+        for (scan, has_more) in self.scans.iter_mut().zip(self.has_more.iter_mut()) {
+            scan.before_first()?;
+            *has_more = scan.next()?;
+        }
+        Ok(())
+    }
+
+    /// Move to the next record in sorted order.
+    /// The current run's scan moves to its next record, and the smallest of the runs' records becomes current.
+    fn next(&mut self) -> Result<bool> {
+        if let Some(current) = self.current {
+            self.has_more[current] = self.scans[current].next()?;
+        }
+
+        let mut smallest: Option<usize> = None;
+        for i in 0..self.scans.len() {
+            if !self.has_more[i] {
+                continue;
+            }
+            smallest = match smallest {
+                Some(j)
+                    if self.comparator.compare(&self.scans[j], &self.scans[i])?
+                        != Ordering::Greater =>
+                {
+                    Some(j)
+                }
+                _ => Some(i),
+            };
+        }
+        self.current = smallest;
+        Ok(self.current.is_some())
+    }
+
+    fn get_int(&self, field_name: &str) -> Result<i32> {
+        self.current_scan()?.get_int(field_name)
+    }
+
+    fn get_string(&self, field_name: &str) -> Result<String> {
+        self.current_scan()?.get_string(field_name)
+    }
+
+    fn get_val(&self, field_name: &str) -> Result<Constant> {
+        self.current_scan()?.get_val(field_name)
+    }
+
+    fn has_field(&self, field_name: &str) -> bool {
+        self.scans
+            .first()
+            .is_some_and(|scan| scan.has_field(field_name))
+    }
+
+    fn close(&mut self) -> Result<()> {
+        for scan in self.scans.iter_mut() {
+            scan.close()?;
+        }
+        Ok(())
+    }
+}
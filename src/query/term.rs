@@ -0,0 +1,171 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+
+use crate::{plan::plan::Plan, record::schema::Schema};
+
+use super::{constant::Constant, expression::Expression, scan::Scan};
+
+/// The reduction factor assumed for a range comparison, which is expected to select about a third of the records.
+pub const RANGE_REDUCTION_FACTOR: usize = 3;
+
+/// The comparison operator of a term.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operator::Eq => write!(f, "="),
+            Operator::Lt => write!(f, "<"),
+            Operator::Le => write!(f, "<="),
+            Operator::Gt => write!(f, ">"),
+            Operator::Ge => write!(f, ">="),
+        }
+    }
+}
+
+impl Operator {
+    /// Return the operator that gives the same result when the operands are swapped.
+    fn flip(self) -> Self {
+        match self {
+            Operator::Eq => Operator::Eq,
+            Operator::Lt => Operator::Gt,
+            Operator::Le => Operator::Ge,
+            Operator::Gt => Operator::Lt,
+            Operator::Ge => Operator::Le,
+        }
+    }
+
+    fn compare(self, lhs: &Constant, rhs: &Constant) -> bool {
+        match self {
+            Operator::Eq => lhs == rhs,
+            Operator::Lt => lhs < rhs,
+            Operator::Le => lhs <= rhs,
+            Operator::Gt => lhs > rhs,
+            Operator::Ge => lhs >= rhs,
+        }
+    }
+}
+
+/// A term is a comparison between two expressions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Term {
+    lhs: Expression,
+    op: Operator,
+    rhs: Expression,
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.lhs, self.op, self.rhs)
+    }
+}
+
+impl Term {
+    /// Create a term that equates two expressions.
+    pub fn new(lhs: Expression, rhs: Expression) -> Self {
+        Self::with_operator(lhs, Operator::Eq, rhs)
+    }
+
+    pub fn with_operator(lhs: Expression, op: Operator, rhs: Expression) -> Self {
+        Self { lhs, op, rhs }
+    }
+
+    /// Return true if the term's expressions compare as the operator requires, with respect to the specified scan.
+    pub fn is_satisfied(&self, scan: &dyn Scan) -> Result<bool> {
+        let lhs_val = self.lhs.evaluate(scan)?;
+        let rhs_val = self.rhs.evaluate(scan)?;
+        Ok(self.op.compare(&lhs_val, &rhs_val))
+    }
+
+    /// Calculate the extent to which selecting on the term reduces the number of records output by a query.
+    /// For example if the reduction factor is 2, then the term cuts the size of the output in half.
+    pub fn reduction_factor(&self, plan: &dyn Plan) -> usize {
+        if self.op != Operator::Eq {
+            return match (&self.lhs, &self.rhs) {
+                (Expression::Constant(lhs), Expression::Constant(rhs)) => {
+                    if self.op.compare(lhs, rhs) {
+                        1
+                    } else {
+                        usize::MAX
+                    }
+                }
+                _ => RANGE_REDUCTION_FACTOR,
+            };
+        }
+        match (&self.lhs, &self.rhs) {
+            (Expression::FieldName(lhs), Expression::FieldName(rhs)) => {
+                plan.distinct_values(lhs).max(plan.distinct_values(rhs))
+            }
+            (Expression::FieldName(lhs), Expression::Constant(_)) => plan.distinct_values(lhs),
+            (Expression::Constant(_), Expression::FieldName(rhs)) => plan.distinct_values(rhs),
+            // otherwise, the term equates constants
+            (Expression::Constant(lhs), Expression::Constant(rhs)) => {
+                if lhs == rhs {
+                    1
+                } else {
+                    usize::MAX
+                }
+            }
+        }
+    }
+
+    /// Determine if this term is of the form "F=c" where F is the specified field and c is some constant.
+    /// If so, the method returns that constant.
+    pub fn equates_with_constant(&self, field_name: &str) -> Option<&Constant> {
+        if self.op != Operator::Eq {
+            return None;
+        }
+        match (&self.lhs, &self.rhs) {
+            (Expression::FieldName(lhs), Expression::Constant(rhs)) if lhs == field_name => {
+                Some(rhs)
+            }
+            (Expression::Constant(lhs), Expression::FieldName(rhs)) if rhs == field_name => {
+                Some(lhs)
+            }
+            _ => None,
+        }
+    }
+
+    /// Determine if this term is of the form "F1=F2" where F1 is the specified field and F2 is another field.
+    /// If so, the method returns the name of that field.
+    pub fn equates_with_field(&self, field_name: &str) -> Option<&str> {
+        if self.op != Operator::Eq {
+            return None;
+        }
+        match (&self.lhs, &self.rhs) {
+            (Expression::FieldName(lhs), Expression::FieldName(rhs)) if lhs == field_name => {
+                Some(rhs)
+            }
+            (Expression::FieldName(lhs), Expression::FieldName(rhs)) if rhs == field_name => {
+                Some(lhs)
+            }
+            _ => None,
+        }
+    }
+
+    /// Determine if this term compares the specified field with a constant, as in "F<c" or "c<=F".
+    /// If so, the method returns the operator, oriented so that the field is on its left, and the constant.
+    pub fn compares_with_constant(&self, field_name: &str) -> Option<(Operator, &Constant)> {
+        match (&self.lhs, &self.rhs) {
+            (Expression::FieldName(lhs), Expression::Constant(rhs)) if lhs == field_name => {
+                Some((self.op, rhs))
+            }
+            (Expression::Constant(lhs), Expression::FieldName(rhs)) if rhs == field_name => {
+                Some((self.op.flip(), lhs))
+            }
+            _ => None,
+        }
+    }
+
+    /// Return true if both of the term's expressions apply to the specified schema.
+    pub fn applies_to(&self, schema: &Schema) -> bool {
+        self.lhs.applies_to(schema) && self.rhs.applies_to(schema)
+    }
+}
@@ -0,0 +1,7 @@
+pub mod chunk_scan;
+pub mod layout;
+pub mod record_page;
+pub mod rid;
+pub mod schema;
+pub mod table_scan;
+pub mod temp_table;
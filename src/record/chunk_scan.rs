@@ -0,0 +1,127 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    file::block_id::BlockId,
+    query::{constant::Constant, scan::Scan},
+    tx::transaction::Transaction,
+};
+
+use super::{layout::Layout, record_page::RecordPage, schema::FieldType};
+
+#[derive(Debug)]
+enum ChunkScanError {
+    ScanClosed,
+}
+
+impl std::error::Error for ChunkScanError {}
+impl fmt::Display for ChunkScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChunkScanError::ScanClosed => write!(f, "chunk scan is closed"),
+        }
+    }
+}
+
+/// The class for the chunk operator.
+/// A chunk is a range of consecutive blocks of a table. The scan pins all of the blocks of the chunk when it is
+/// opened, so that the chunk can be scanned repeatedly without reading any block again.
+#[derive(Debug)]
+pub struct ChunkScan {
+    tx: Arc<Mutex<Transaction>>,
+    layout: Layout,
+    pages: Vec<RecordPage>,
+    current_page: usize,
+    current_slot: i32,
+}
+
+impl ChunkScan {
+    /// Create a chunk consisting of the specified blocks of the table, from the start block to the end block inclusive.
+    pub fn new(
+        tx: Arc<Mutex<Transaction>>,
+        table_name: &str,
+        layout: Layout,
+        start_block: usize,
+        end_block: usize,
+    ) -> Result<Self> {
+        let filename = format!("{}.tbl", table_name);
+        let mut scan = Self {
+            tx,
+            layout,
+            pages: vec![],
+            current_page: 0,
+            current_slot: -1,
+        };
+        for block_number in start_block..=end_block {
+            let block = BlockId::new(&filename, block_number);
+            // unpin the blocks pinned so far if a block cannot be pinned
+            let page = RecordPage::new(Arc::clone(&scan.tx), block, scan.layout.clone())
+                .or_else(|e| scan.close().and(Err(e)))?;
+            scan.pages.push(page);
+        }
+        Ok(scan)
+    }
+
+    fn current_page(&self) -> Result<&RecordPage> {
+        // the pages are only absent after the scan has been closed
+        self.pages
+            .get(self.current_page)
+            .ok_or_else(|| ChunkScanError::ScanClosed.into())
+    }
+}
+
+impl Scan for ChunkScan {
+    /// Position the scan before the first record of the chunk's first block.
+    fn before_first(&mut self) -> Result<()> {
+        self.current_page = 0;
+        self.current_slot = -1;
+        Ok(())
+    }
+
+    /// Move to the next record in the current block of the chunk.
+    /// If there are no more records, then make the next block be current.
+    /// If there are no more blocks in the chunk, then return false.
+    fn next(&mut self) -> Result<bool> {
+        loop {
+            if let Some(slot) = self.current_page()?.next_after(self.current_slot)? {
+                self.current_slot = slot;
+                return Ok(true);
+            }
+            if self.current_page + 1 >= self.pages.len() {
+                return Ok(false);
+            }
+            self.current_page += 1;
+            self.current_slot = -1;
+        }
+    }
+
+    fn get_int(&self, field_name: &str) -> Result<i32> {
+        self.current_page()?.get_int(self.current_slot, field_name)
+    }
+
+    fn get_string(&self, field_name: &str) -> Result<String> {
+        self.current_page()?
+            .get_string(self.current_slot, field_name)
+    }
+
+    fn get_val(&self, field_name: &str) -> Result<Constant> {
+        match self.layout.schema().field_type(field_name) {
+            Some(FieldType::Varchar) => Ok(Constant::String(self.get_string(field_name)?)),
+            _ => Ok(Constant::Int(self.get_int(field_name)?)),
+        }
+    }
+
+    fn has_field(&self, field_name: &str) -> bool {
+        self.layout.schema().has_field(field_name)
+    }
+
+    /// Unpin the blocks of the chunk.
+    fn close(&mut self) -> Result<()> {
+        let mut tx = self.tx.lock().unwrap();
+        for page in self.pages.drain(..) {
+            tx.unpin(page.block())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use crate::file::page::Page;
+
+use super::schema::{FieldType, Schema};
+
+/// Description of the structure of a record.
+/// It contains the name, type, length and offset of each field of the table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    schema: Schema,
+    offsets: HashMap<String, usize>,
+    slot_size: usize,
+}
+
+impl Layout {
+    /// Create a layout object from a schema.
+    /// This constructor is used when a table is created. It determines the physical offset of each field within the record.
+    /// Every slot starts with a 4-byte empty/inuse flag, followed by the fields in schema order.
+    pub fn new(schema: Schema) -> Self {
+        let mut offsets = HashMap::new();
+        let mut pos = std::mem::size_of::<i32>(); // leave space for the empty/inuse flag
+        for field_name in schema.fields() {
+            offsets.insert(field_name.clone(), pos);
+            pos += Self::length_in_bytes(&schema, field_name);
+        }
+
+        Self {
+            schema,
+            offsets,
+            slot_size: pos,
+        }
+    }
+
+    /// Create a layout object from the specified metadata.
+    /// This constructor is used when the metadata is retrieved from the catalog.
+    pub fn from_metadata(
+        schema: Schema,
+        offsets: HashMap<String, usize>,
+        slot_size: usize,
+    ) -> Self {
+        Self {
+            schema,
+            offsets,
+            slot_size,
+        }
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Return the offset of a specified field within a record.
+    pub fn offset(&self, field_name: &str) -> Option<usize> {
+        self.offsets.get(field_name).copied()
+    }
+
+    /// Return the size of a slot, in bytes.
+    pub fn slot_size(&self) -> usize {
+        self.slot_size
+    }
+
+    fn length_in_bytes(schema: &Schema, field_name: &str) -> usize {
+        match schema.field_type(field_name) {
+            Some(FieldType::Varchar) => Page::max_length(schema.length(field_name).unwrap_or(0)),
+            _ => std::mem::size_of::<i32>(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::record::schema::Schema;
+
+    use super::Layout;
+
+    #[test]
+    fn test_layout() {
+        let mut schema = Schema::new();
+        schema.add_int_field("A");
+        schema.add_string_field("B", 9);
+        let layout = Layout::new(schema);
+
+        // the flag takes the first 4 bytes, the int 4 bytes and the string a length prefix plus 9 chars
+        assert_eq!(layout.offset("A"), Some(4));
+        assert_eq!(layout.offset("B"), Some(8));
+        assert_eq!(layout.offset("C"), None);
+        assert_eq!(layout.slot_size(), 8 + 4 + 9 * 4);
+    }
+}
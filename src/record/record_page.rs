@@ -0,0 +1,231 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    file::{block_id::BlockId, page::Page},
+    tx::transaction::Transaction,
+};
+
+use super::{layout::Layout, schema::FieldType};
+
+const EMPTY: i32 = 0;
+const USED: i32 = 1;
+
+#[derive(Debug)]
+enum RecordPageError {
+    UnknownField(String),
+}
+
+impl std::error::Error for RecordPageError {}
+impl fmt::Display for RecordPageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordPageError::UnknownField(field_name) => write!(f, "unknown field: {}", field_name),
+        }
+    }
+}
+
+/// Store a record at a given location in a block.
+/// A record page is a block of slots, each slot holding an empty/inuse flag followed by the record's fields.
+/// Slots are numbered from 0; the slot number -1 denotes the position before the first slot.
+#[derive(Debug)]
+pub struct RecordPage {
+    tx: Arc<Mutex<Transaction>>,
+    block: BlockId,
+    layout: Layout,
+}
+
+impl RecordPage {
+    /// Create a record page for the specified block, and pin the block for the lifetime of the record page.
+    pub fn new(tx: Arc<Mutex<Transaction>>, block: BlockId, layout: Layout) -> Result<Self> {
+        tx.lock().unwrap().pin(&block)?;
+        Ok(Self { tx, block, layout })
+    }
+
+    /// Return the integer value stored for the specified field of a specified slot.
+    pub fn get_int(&self, slot: i32, field_name: &str) -> Result<i32> {
+        let field_pos = self.offset(slot) + self.field_offset(field_name)?;
+        self.tx.lock().unwrap().get_int(&self.block, field_pos)
+    }
+
+    /// Return the string value stored for the specified field of a specified slot.
+    pub fn get_string(&self, slot: i32, field_name: &str) -> Result<String> {
+        let field_pos = self.offset(slot) + self.field_offset(field_name)?;
+        self.tx.lock().unwrap().get_string(&self.block, field_pos)
+    }
+
+    /// Store an integer at the specified field of the specified slot.
+    pub fn set_int(&self, slot: i32, field_name: &str, val: i32) -> Result<()> {
+        let field_pos = self.offset(slot) + self.field_offset(field_name)?;
+        self.tx
+            .lock()
+            .unwrap()
+            .set_int(&self.block, field_pos, val, true)
+    }
+
+    /// Store a string at the specified field of the specified slot.
+    /// It is an error if the string is longer than the field's declared length.
+    pub fn set_string(&self, slot: i32, field_name: &str, val: &str) -> Result<()> {
+        let field_pos = self.offset(slot) + self.field_offset(field_name)?;
+        Page::check_varchar(val, self.layout.schema().length(field_name).unwrap_or(0))?;
+        self.tx
+            .lock()
+            .unwrap()
+            .set_string(&self.block, field_pos, val, true)
+    }
+
+    /// Mark the specified slot as empty.
+    pub fn delete(&self, slot: i32) -> Result<()> {
+        self.set_flag(slot, EMPTY)
+    }
+
+    /// Use the layout to format a new block of records.
+    /// These values are not logged, because the old values are meaningless.
+    pub fn format(&self) -> Result<()> {
+        let mut slot = 0;
+        let mut tx = self.tx.lock().unwrap();
+        while self.is_valid_slot(slot, tx.block_size()) {
+            tx.set_int(&self.block, self.offset(slot), EMPTY, false)?;
+            let schema = self.layout.schema();
+            for field_name in schema.fields() {
+                let field_pos = self.offset(slot) + self.field_offset(field_name)?;
+                if schema.field_type(field_name) == Some(FieldType::Integer) {
+                    tx.set_int(&self.block, field_pos, 0, false)?;
+                } else {
+                    tx.set_string(&self.block, field_pos, "", false)?;
+                }
+            }
+            slot += 1;
+        }
+        Ok(())
+    }
+
+    /// Return the first used slot following the specified slot, if any.
+    pub fn next_after(&self, slot: i32) -> Result<Option<i32>> {
+        self.search_after(slot, USED)
+    }
+
+    /// Look for the first empty slot following the specified slot.
+    /// If found, mark it as used and return its number.
+    pub fn insert_after(&self, slot: i32) -> Result<Option<i32>> {
+        let new_slot = self.search_after(slot, EMPTY)?;
+        if let Some(new_slot) = new_slot {
+            self.set_flag(new_slot, USED)?;
+        }
+        Ok(new_slot)
+    }
+
+    pub fn block(&self) -> &BlockId {
+        &self.block
+    }
+
+    /// Set the record's empty/inuse flag.
+    fn set_flag(&self, slot: i32, flag: i32) -> Result<()> {
+        self.tx
+            .lock()
+            .unwrap()
+            .set_int(&self.block, self.offset(slot), flag, true)
+    }
+
+    fn search_after(&self, slot: i32, flag: i32) -> Result<Option<i32>> {
+        let mut slot = slot + 1;
+        let mut tx = self.tx.lock().unwrap();
+        while self.is_valid_slot(slot, tx.block_size()) {
+            if tx.get_int(&self.block, self.offset(slot))? == flag {
+                return Ok(Some(slot));
+            }
+            slot += 1;
+        }
+        Ok(None)
+    }
+
+    fn is_valid_slot(&self, slot: i32, block_size: usize) -> bool {
+        self.offset(slot + 1) <= block_size
+    }
+
+    fn offset(&self, slot: i32) -> usize {
+        slot as usize * self.layout.slot_size()
+    }
+
+    fn field_offset(&self, field_name: &str) -> Result<usize> {
+        self.layout
+            .offset(field_name)
+            .ok_or_else(|| RecordPageError::UnknownField(field_name.to_string()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+
+    use crate::{
+        record::{layout::Layout, schema::Schema},
+        server::simpledb::SimpleDB,
+    };
+
+    use super::RecordPage;
+
+    #[test]
+    fn test_record_page() {
+        let temp_dir = tempdir().unwrap();
+        let db_dir = temp_dir.path().to_str().unwrap();
+
+        let db = SimpleDB::new(db_dir, 800, 8).unwrap();
+        let tx = db.new_tx().unwrap();
+
+        let mut schema = Schema::new();
+        schema.add_int_field("A");
+        schema.add_string_field("B", 9);
+        let layout = Layout::new(schema);
+
+        let block = tx.lock().unwrap().append("testfile").unwrap();
+        let record_page = RecordPage::new(Arc::clone(&tx), block.clone(), layout).unwrap();
+        record_page.format().unwrap();
+
+        // fill the page with records
+        let mut inserted = vec![];
+        let mut slot = record_page.insert_after(-1).unwrap();
+        while let Some(s) = slot {
+            record_page.set_int(s, "A", s * 10).unwrap();
+            record_page
+                .set_string(s, "B", &format!("rec{}", s))
+                .unwrap();
+            inserted.push(s);
+            slot = record_page.insert_after(s).unwrap();
+        }
+        // 800 bytes / 48-byte slots
+        assert_eq!(inserted.len(), 16);
+
+        // delete the records whose A-value is less than 25
+        let mut slot = record_page.next_after(-1).unwrap();
+        while let Some(s) = slot {
+            if record_page.get_int(s, "A").unwrap() < 25 {
+                record_page.delete(s).unwrap();
+            }
+            slot = record_page.next_after(s).unwrap();
+        }
+
+        let mut remaining = vec![];
+        let mut slot = record_page.next_after(-1).unwrap();
+        while let Some(s) = slot {
+            assert_eq!(record_page.get_string(s, "B").unwrap(), format!("rec{}", s));
+            remaining.push(record_page.get_int(s, "A").unwrap());
+            slot = record_page.next_after(s).unwrap();
+        }
+        assert_eq!(remaining, (3..16).map(|s| s * 10).collect::<Vec<_>>());
+
+        // a freed slot is reused by the next insert
+        assert_eq!(record_page.insert_after(-1).unwrap(), Some(0));
+        assert!(record_page.get_string(0, "C").is_err());
+
+        // a string longer than the field is rejected without touching the slot
+        assert!(record_page.set_string(1, "B", "0123456789").is_err());
+        assert_eq!(record_page.get_string(1, "B").unwrap(), "rec1");
+
+        tx.lock().unwrap().unpin(&block).unwrap();
+        tx.lock().unwrap().commit().unwrap();
+    }
+}
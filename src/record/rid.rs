@@ -0,0 +1,29 @@
+use core::fmt;
+
+/// An identifier for a record within a file.
+/// A record id consists of the block number in the file, and the location of the record in that block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RecordId {
+    block_number: usize,
+    slot: i32,
+}
+
+impl fmt::Display for RecordId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}, {}]", self.block_number, self.slot)
+    }
+}
+
+impl RecordId {
+    pub fn new(block_number: usize, slot: i32) -> Self {
+        Self { block_number, slot }
+    }
+
+    pub fn block_number(&self) -> usize {
+        self.block_number
+    }
+
+    pub fn slot(&self) -> i32 {
+        self.slot
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use num_enum::TryFromPrimitive;
+
+/// The type of a field, encoded with the same values as the JDBC type codes used by the original SimpleDB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
+#[repr(i32)]
+pub enum FieldType {
+    Integer = 4,
+    Varchar = 12,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FieldInfo {
+    field_type: FieldType,
+    length: usize,
+}
+
+/// The record schema of a table.
+/// A schema contains the name and type of each field of the table, as well as the length of each varchar field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schema {
+    fields: Vec<String>,
+    info: HashMap<String, FieldInfo>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a field to the schema having a specified name, type, and length.
+    /// If the field type is an integer, then the length value is irrelevant.
+    pub fn add_field(&mut self, field_name: &str, field_type: FieldType, length: usize) {
+        if !self.info.contains_key(field_name) {
+            self.fields.push(field_name.to_string());
+        }
+        self.info
+            .insert(field_name.to_string(), FieldInfo { field_type, length });
+    }
+
+    pub fn add_int_field(&mut self, field_name: &str) {
+        self.add_field(field_name, FieldType::Integer, 0);
+    }
+
+    /// Add a string field to the schema. The length is the conceptual length of the field,
+    /// e.g. if the field is defined as varchar(8), then its length is 8.
+    pub fn add_string_field(&mut self, field_name: &str, length: usize) {
+        self.add_field(field_name, FieldType::Varchar, length);
+    }
+
+    /// Add a field to the schema having the same type and length as the corresponding field in another schema.
+    pub fn add(&mut self, field_name: &str, schema: &Schema) {
+        if let Some(info) = schema.info.get(field_name) {
+            self.add_field(field_name, info.field_type, info.length);
+        }
+    }
+
+    /// Add all of the fields in the specified schema to the current schema.
+    pub fn add_all(&mut self, schema: &Schema) {
+        for field_name in schema.fields() {
+            self.add(field_name, schema);
+        }
+    }
+
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    pub fn has_field(&self, field_name: &str) -> bool {
+        self.info.contains_key(field_name)
+    }
+
+    pub fn field_type(&self, field_name: &str) -> Option<FieldType> {
+        self.info.get(field_name).map(|info| info.field_type)
+    }
+
+    pub fn length(&self, field_name: &str) -> Option<usize> {
+        self.info.get(field_name).map(|info| info.length)
+    }
+}
@@ -0,0 +1,303 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    file::block_id::BlockId,
+    query::{
+        constant::Constant,
+        scan::{Scan, UpdateScan},
+    },
+    tx::transaction::Transaction,
+};
+
+use super::{layout::Layout, record_page::RecordPage, rid::RecordId, schema::FieldType};
+
+#[derive(Debug)]
+enum TableScanError {
+    ScanClosed,
+    SlotTooLarge(usize, usize),
+}
+
+impl std::error::Error for TableScanError {}
+impl fmt::Display for TableScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TableScanError::ScanClosed => write!(f, "table scan is closed"),
+            TableScanError::SlotTooLarge(slot_size, block_size) => write!(
+                f,
+                "slot size {} exceeds block size {}",
+                slot_size, block_size
+            ),
+        }
+    }
+}
+
+/// Provides the abstraction of an arbitrarily large array of records.
+/// The records of a table are stored in the file `<table>.tbl`, one record page per block.
+#[derive(Debug)]
+pub struct TableScan {
+    tx: Arc<Mutex<Transaction>>,
+    layout: Layout,
+    record_page: Option<RecordPage>,
+    filename: String,
+    current_slot: i32,
+}
+
+impl TableScan {
+    pub fn new(tx: Arc<Mutex<Transaction>>, table_name: &str, layout: Layout) -> Result<Self> {
+        // a record that does not fit in a block could never be inserted
+        let block_size = tx.lock().unwrap().block_size();
+        if layout.slot_size() > block_size {
+            return Err(TableScanError::SlotTooLarge(layout.slot_size(), block_size).into());
+        }
+
+        let mut scan = Self {
+            tx,
+            layout,
+            record_page: None,
+            filename: format!("{}.tbl", table_name),
+            current_slot: -1,
+        };
+
+        if scan.tx.lock().unwrap().size(&scan.filename)? == 0 {
+            scan.move_to_new_block()?;
+        } else {
+            scan.move_to_block(0)?;
+        }
+
+        Ok(scan)
+    }
+
+    fn move_to_block(&mut self, block_number: usize) -> Result<()> {
+        self.close()?;
+        let block = BlockId::new(&self.filename, block_number);
+        self.record_page = Some(RecordPage::new(
+            Arc::clone(&self.tx),
+            block,
+            self.layout.clone(),
+        )?);
+        self.current_slot = -1;
+        Ok(())
+    }
+
+    fn move_to_new_block(&mut self) -> Result<()> {
+        self.close()?;
+        let block = self.tx.lock().unwrap().append(&self.filename)?;
+        let record_page = RecordPage::new(Arc::clone(&self.tx), block, self.layout.clone())?;
+        record_page.format()?;
+        self.record_page = Some(record_page);
+        self.current_slot = -1;
+        Ok(())
+    }
+
+    fn at_last_block(&self) -> Result<bool> {
+        let size = self.tx.lock().unwrap().size(&self.filename)?;
+        Ok(self.current_page()?.block().block_number() == size - 1)
+    }
+
+    fn current_page(&self) -> Result<&RecordPage> {
+        // the record page is only absent after the scan has been closed
+        self.record_page
+            .as_ref()
+            .ok_or_else(|| TableScanError::ScanClosed.into())
+    }
+}
+
+impl Scan for TableScan {
+    /// Position the scan before the first record of the file.
+    fn before_first(&mut self) -> Result<()> {
+        self.move_to_block(0)
+    }
+
+    /// Move to the next used slot, moving on to the following blocks as needed.
+    /// Return false when there are no more records in the file.
+    fn next(&mut self) -> Result<bool> {
+        loop {
+            let record_page = self.current_page()?;
+            if let Some(slot) = record_page.next_after(self.current_slot)? {
+                self.current_slot = slot;
+                return Ok(true);
+            }
+
+            let block_number = record_page.block().block_number();
+            if self.at_last_block()? {
+                return Ok(false);
+            }
+            self.move_to_block(block_number + 1)?;
+        }
+    }
+
+    fn get_int(&self, field_name: &str) -> Result<i32> {
+        self.current_page()?.get_int(self.current_slot, field_name)
+    }
+
+    fn get_string(&self, field_name: &str) -> Result<String> {
+        self.current_page()?
+            .get_string(self.current_slot, field_name)
+    }
+
+    fn get_val(&self, field_name: &str) -> Result<Constant> {
+        match self.layout.schema().field_type(field_name) {
+            Some(FieldType::Varchar) => Ok(Constant::String(self.get_string(field_name)?)),
+            _ => Ok(Constant::Int(self.get_int(field_name)?)),
+        }
+    }
+
+    fn has_field(&self, field_name: &str) -> bool {
+        self.layout.schema().has_field(field_name)
+    }
+
+    /// Unpin the block of the current record page.
+    fn close(&mut self) -> Result<()> {
+        if let Some(record_page) = self.record_page.take() {
+            self.tx.lock().unwrap().unpin(record_page.block())?;
+        }
+        Ok(())
+    }
+
+    fn as_update_scan(&mut self) -> Option<&mut dyn UpdateScan> {
+        Some(self)
+    }
+}
+
+impl UpdateScan for TableScan {
+    fn set_val(&mut self, field_name: &str, val: &Constant) -> Result<()> {
+        match val {
+            Constant::Int(n) => self.set_int(field_name, *n),
+            Constant::String(s) => self.set_string(field_name, s),
+        }
+    }
+
+    fn set_int(&mut self, field_name: &str, val: i32) -> Result<()> {
+        self.current_page()?
+            .set_int(self.current_slot, field_name, val)
+    }
+
+    fn set_string(&mut self, field_name: &str, val: &str) -> Result<()> {
+        self.current_page()?
+            .set_string(self.current_slot, field_name, val)
+    }
+
+    /// Insert a new record somewhere in the file, starting the search from the current record.
+    /// A new block is appended to the file if all the existing blocks are full.
+    fn insert(&mut self) -> Result<()> {
+        loop {
+            let record_page = self.current_page()?;
+            if let Some(slot) = record_page.insert_after(self.current_slot)? {
+                self.current_slot = slot;
+                return Ok(());
+            }
+
+            let block_number = record_page.block().block_number();
+            if self.at_last_block()? {
+                self.move_to_new_block()?;
+            } else {
+                self.move_to_block(block_number + 1)?;
+            }
+        }
+    }
+
+    fn delete(&mut self) -> Result<()> {
+        self.current_page()?.delete(self.current_slot)
+    }
+
+    /// Return the id of the current record.
+    fn get_rid(&mut self) -> Result<RecordId> {
+        Ok(RecordId::new(
+            self.current_page()?.block().block_number(),
+            self.current_slot,
+        ))
+    }
+
+    /// Position the scan at the record having the specified id.
+    fn move_to_rid(&mut self, rid: RecordId) -> Result<()> {
+        self.move_to_block(rid.block_number())?;
+        self.current_slot = rid.slot();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+
+    use crate::{
+        query::scan::{Scan, UpdateScan},
+        record::{layout::Layout, schema::Schema},
+        server::simpledb::SimpleDB,
+    };
+
+    use super::TableScan;
+
+    #[test]
+    fn test_table_scan() {
+        let temp_dir = tempdir().unwrap();
+        let db_dir = temp_dir.path().to_str().unwrap();
+
+        let db = SimpleDB::new(db_dir, 800, 8).unwrap();
+        let tx = db.new_tx().unwrap();
+
+        let mut schema = Schema::new();
+        schema.add_int_field("A");
+        schema.add_string_field("B", 9);
+        let layout = Layout::new(schema);
+
+        // 50 records of 48 bytes need several 800-byte blocks
+        let mut scan = TableScan::new(Arc::clone(&tx), "T", layout.clone()).unwrap();
+        for i in 0..50 {
+            scan.insert().unwrap();
+            scan.set_int("A", i).unwrap();
+            scan.set_string("B", &format!("rec{}", i)).unwrap();
+        }
+        assert_eq!(tx.lock().unwrap().size("T.tbl").unwrap(), 4);
+
+        // delete the records whose A-value is less than 25
+        let mut deleted = 0;
+        scan.before_first().unwrap();
+        while scan.next().unwrap() {
+            if scan.get_int("A").unwrap() < 25 {
+                scan.delete().unwrap();
+                deleted += 1;
+            }
+        }
+        assert_eq!(deleted, 25);
+
+        let mut remaining = vec![];
+        let mut rid = None;
+        scan.before_first().unwrap();
+        while scan.next().unwrap() {
+            let a = scan.get_int("A").unwrap();
+            assert_eq!(scan.get_string("B").unwrap(), format!("rec{}", a));
+            if a == 42 {
+                rid = Some(scan.get_rid().unwrap());
+            }
+            remaining.push(a);
+        }
+        assert_eq!(remaining, (25..50).collect::<Vec<_>>());
+
+        scan.move_to_rid(rid.unwrap()).unwrap();
+        assert_eq!(scan.get_int("A").unwrap(), 42);
+
+        scan.close().unwrap();
+        assert!(scan.get_int("A").is_err());
+
+        // a new scan over the same table sees the same records
+        let mut scan = TableScan::new(Arc::clone(&tx), "T", layout).unwrap();
+        let mut count = 0;
+        while scan.next().unwrap() {
+            count += 1;
+        }
+        assert_eq!(count, 25);
+        scan.close().unwrap();
+
+        // a record larger than a block is rejected up front
+        let mut schema = Schema::new();
+        schema.add_string_field("C", 200);
+        assert!(TableScan::new(Arc::clone(&tx), "U", Layout::new(schema)).is_err());
+
+        tx.lock().unwrap().commit().unwrap();
+    }
+}
@@ -0,0 +1,59 @@
+use anyhow::Result;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use crate::tx::transaction::Transaction;
+
+use super::{layout::Layout, schema::Schema, table_scan::TableScan};
+
+/// The prefix of the names of temporary tables, whose files are removed when the database is opened.
+/// The prefix is followed by a number, so a temporary table cannot collide with a user table.
+pub const TEMP_TABLE_PREFIX: &str = "temp";
+
+/// Whether the file is one of the table files generated by `TempTable`, named `tempN.tbl`.
+/// User tables whose names merely start with the prefix, such as `temperature`, are not temporary.
+pub fn is_temp_table_file(filename: &str) -> bool {
+    filename
+        .strip_prefix(TEMP_TABLE_PREFIX)
+        .and_then(|name| name.strip_suffix(".tbl"))
+        .is_some_and(|num| !num.is_empty() && num.bytes().all(|b| b.is_ascii_digit()))
+}
+
+static NEXT_TABLE_NUM: AtomicUsize = AtomicUsize::new(1);
+
+/// A class that creates temporary tables.
+/// A temporary table is not registered in the catalog. Each one gets a unique name of the form `tempN`.
+#[derive(Debug, Clone)]
+pub struct TempTable {
+    tx: Arc<Mutex<Transaction>>,
+    table_name: String,
+    layout: Layout,
+}
+
+impl TempTable {
+    /// Allocate a name for a new temporary table having the specified schema.
+    pub fn new(tx: Arc<Mutex<Transaction>>, schema: Schema) -> Self {
+        let table_num = NEXT_TABLE_NUM.fetch_add(1, Ordering::Relaxed);
+        Self {
+            tx,
+            table_name: format!("{}{}", TEMP_TABLE_PREFIX, table_num),
+            layout: Layout::new(schema),
+        }
+    }
+
+    /// Open a table scan for the temporary table.
+    pub fn open(&self) -> Result<TableScan> {
+        TableScan::new(Arc::clone(&self.tx), &self.table_name, self.layout.clone())
+    }
+
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    /// Return the table's layout.
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+}
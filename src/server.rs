@@ -0,0 +1,3 @@
+pub mod network_server;
+pub mod protocol;
+pub mod simpledb;
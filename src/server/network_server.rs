@@ -0,0 +1,373 @@
+use anyhow::{Ok, Result};
+use core::fmt;
+use std::{
+    collections::HashMap,
+    io::{BufReader, BufWriter},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use crate::{query::scan::Scan, tx::transaction::Transaction};
+
+use super::{
+    protocol::{read_frame, write_frame, Request, Response, PROTOCOL_VERSION},
+    simpledb::SimpleDB,
+};
+
+#[derive(Debug)]
+enum NetworkServerError {
+    NotConnected,
+    VersionMismatch(u32),
+    UnknownResultSet(u32),
+}
+
+impl std::error::Error for NetworkServerError {}
+impl fmt::Display for NetworkServerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetworkServerError::NotConnected => write!(f, "connect must be the first request"),
+            NetworkServerError::VersionMismatch(version) => write!(
+                f,
+                "protocol version {} is not supported, expected {}",
+                version, PROTOCOL_VERSION
+            ),
+            NetworkServerError::UnknownResultSet(id) => write!(f, "unknown result set {}", id),
+        }
+    }
+}
+
+/// Serves a database to remote clients.
+/// Each connection is handled by its own thread and has its own transaction.
+pub struct NetworkServer {
+    db: Arc<SimpleDB>,
+}
+
+impl NetworkServer {
+    pub fn new(db: SimpleDB) -> Self {
+        Self { db: Arc::new(db) }
+    }
+
+    /// Accept connections until the listener fails.
+    pub fn serve(&self, listener: TcpListener) -> Result<()> {
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let db = Arc::clone(&self.db);
+            thread::spawn(move || {
+                if let Err(err) = handle_connection(db, stream) {
+                    eprintln!("connection failed: {}", err);
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Serve the connection's requests.
+/// However the connection ends, whether the client goes away without closing it or reading, decoding or writing a
+/// frame fails, a session that was not closed is rolled back, so that its locks and buffers are released.
+fn handle_connection(db: Arc<SimpleDB>, stream: TcpStream) -> Result<()> {
+    let mut session = None;
+    let result = handle_requests(db, stream, &mut session);
+    if let Some(session) = session {
+        if let Err(rollback_err) = session.abort() {
+            return Err(match result {
+                Err(err) => err.context(rollback_err.to_string()),
+                _ => rollback_err,
+            });
+        }
+    }
+    result
+}
+
+fn handle_requests(
+    db: Arc<SimpleDB>,
+    stream: TcpStream,
+    session: &mut Option<Session>,
+) -> Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = BufWriter::new(stream);
+
+    while let Some(frame) = read_frame(&mut reader)? {
+        let request = Request::decode(&frame)?;
+        let response = match (session.as_mut(), request) {
+            (None, Request::Connect { version }) if version == PROTOCOL_VERSION => {
+                *session = Some(Session::new(Arc::clone(&db))?);
+                Response::Ok
+            }
+            (None, Request::Connect { version }) => {
+                Response::Error(NetworkServerError::VersionMismatch(version).to_string())
+            }
+            (None, _) => Response::Error(NetworkServerError::NotConnected.to_string()),
+            (Some(open_session), Request::Close) => {
+                open_session.close()?;
+                *session = None;
+                write_frame(&mut writer, &Response::Ok.encode())?;
+                return Ok(());
+            }
+            (Some(session), request) => session.handle(request),
+        };
+        write_frame(&mut writer, &response.encode())?;
+    }
+    Ok(())
+}
+
+/// The server side of a connection: its current transaction and open result sets.
+struct Session {
+    db: Arc<SimpleDB>,
+    tx: Arc<Mutex<Transaction>>,
+    result_sets: HashMap<u32, (Box<dyn Scan>, Vec<String>)>,
+    next_id: u32,
+}
+
+impl Session {
+    fn new(db: Arc<SimpleDB>) -> Result<Self> {
+        let tx = db.new_tx()?;
+        Ok(Self {
+            db,
+            tx,
+            result_sets: HashMap::new(),
+            next_id: 0,
+        })
+    }
+
+    /// Execute a request and return its response.
+    /// A failed statement rolls back the current transaction, as the embedded shell does.
+    fn handle(&mut self, request: Request) -> Response {
+        let result = match request {
+            Request::ExecuteQuery(sql) => self.execute_query(&sql),
+            Request::Fetch { id, max_rows } => self.fetch(id, max_rows),
+            Request::CloseResultSet(id) => self.close_result_set(id),
+            Request::ExecuteUpdate(sql) => self.execute_update(&sql),
+            Request::Commit => self.commit().map(|_| Response::Ok),
+            Request::Rollback => self.rollback().map(|_| Response::Ok),
+            Request::Connect { .. } | Request::Close => Ok(Response::Ok),
+        };
+
+        result.unwrap_or_else(|err| match self.rollback() {
+            std::result::Result::Ok(()) => Response::Error(err.to_string()),
+            Err(rollback_err) => Response::Error(format!("{}; {}", err, rollback_err)),
+        })
+    }
+
+    fn execute_query(&mut self, sql: &str) -> Result<Response> {
+        let plan = self
+            .db
+            .planner()
+            .create_query_plan(sql, Arc::clone(&self.tx))?;
+        let schema = plan.schema().clone();
+        let scan = plan.open()?;
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.result_sets
+            .insert(id, (scan, schema.fields().to_vec()));
+        Ok(Response::ResultSet { id, schema })
+    }
+
+    /// Return up to `max_rows` of the next rows of a result set.
+    /// The result set is closed once it has no more rows.
+    fn fetch(&mut self, id: u32, max_rows: u32) -> Result<Response> {
+        let (scan, fields) = self
+            .result_sets
+            .get_mut(&id)
+            .ok_or(NetworkServerError::UnknownResultSet(id))?;
+
+        let mut rows = vec![];
+        let mut done = false;
+        while rows.len() < max_rows as usize {
+            if !scan.next()? {
+                done = true;
+                break;
+            }
+            rows.push(
+                fields
+                    .iter()
+                    .map(|field_name| scan.get_val(field_name))
+                    .collect::<Result<Vec<_>>>()?,
+            );
+        }
+
+        if done {
+            self.close_result_set(id)?;
+        }
+        Ok(Response::Rows { rows, done })
+    }
+
+    fn close_result_set(&mut self, id: u32) -> Result<Response> {
+        if let Some((mut scan, _)) = self.result_sets.remove(&id) {
+            scan.close()?;
+        }
+        Ok(Response::Ok)
+    }
+
+    fn execute_update(&mut self, sql: &str) -> Result<Response> {
+        let count = self
+            .db
+            .planner()
+            .execute_update(sql, Arc::clone(&self.tx))?;
+        Ok(Response::UpdateCount(count as u32))
+    }
+
+    /// Commit the current transaction and start a new one.
+    /// Open result sets do not survive the end of their transaction.
+    fn commit(&mut self) -> Result<()> {
+        self.close_result_sets()?;
+        self.tx.lock().unwrap().commit()?;
+        self.tx = self.db.new_tx()?;
+        Ok(())
+    }
+
+    /// Roll back the current transaction and start a new one.
+    fn rollback(&mut self) -> Result<()> {
+        // the rollback unpins whatever the open scans still hold
+        self.result_sets.clear();
+        self.tx.lock().unwrap().rollback()?;
+        self.tx = self.db.new_tx()?;
+        Ok(())
+    }
+
+    /// Commit the current transaction, which ends the session.
+    fn close(&mut self) -> Result<()> {
+        self.close_result_sets()?;
+        self.tx.lock().unwrap().commit()
+    }
+
+    /// Roll back the current transaction and end the session.
+    fn abort(mut self) -> Result<()> {
+        self.result_sets.clear();
+        self.tx.lock().unwrap().rollback()
+    }
+
+    fn close_result_sets(&mut self) -> Result<()> {
+        for (_, (mut scan, _)) in self.result_sets.drain() {
+            scan.close()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        net::{TcpListener, TcpStream},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use tempfile::tempdir;
+
+    use crate::{
+        client::driver::Driver,
+        record::schema::FieldType,
+        server::{
+            protocol::{read_frame, write_frame, Request, Response, PROTOCOL_VERSION},
+            simpledb::SimpleDB,
+        },
+    };
+
+    use super::NetworkServer;
+
+    #[test]
+    fn test_client_server() {
+        let temp_dir = tempdir().unwrap();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || NetworkServer::new(db).serve(listener));
+
+        let driver = Driver::new();
+        let conn = driver.connect(addr).unwrap();
+        let stmt = conn.create_statement();
+        stmt.execute_update("create table T(A int, B varchar(9))")
+            .unwrap();
+        for i in 0..250 {
+            let sql = format!("insert into T(A, B) values ({}, 'rec{}')", i, i);
+            assert_eq!(stmt.execute_update(&sql).unwrap(), 1);
+        }
+        conn.commit().unwrap();
+
+        // more rows than one fetch returns
+        let mut rs = stmt.execute_query("select A, B from T").unwrap();
+        assert_eq!(rs.schema().field_type("b"), Some(FieldType::Varchar));
+        assert!(rs.get_int("A").is_err());
+        let mut count = 0;
+        while rs.next().unwrap() {
+            let a = rs.get_int("A").unwrap();
+            assert_eq!(rs.get_string("B").unwrap(), format!("rec{}", a));
+            assert!(rs.get_string("A").is_err());
+            count += 1;
+        }
+        assert_eq!(count, 250);
+        rs.close().unwrap();
+
+        // a rolled back delete is not seen by another connection
+        let other = driver.connect(addr).unwrap();
+        assert_eq!(stmt.execute_update("delete from T where A = 7").unwrap(), 1);
+        conn.rollback().unwrap();
+        let mut rs = other
+            .create_statement()
+            .execute_query("select B from T where A = 7")
+            .unwrap();
+        assert!(rs.next().unwrap());
+        assert_eq!(rs.get_string("B").unwrap(), "rec7");
+        rs.close().unwrap();
+        other.close().unwrap();
+
+        // a failed statement reports the server error and keeps the connection usable
+        let err = stmt.execute_query("select C from T").unwrap_err();
+        assert!(err.to_string().contains("field c not found"));
+        let mut rs = stmt.execute_query("select A from T where A = 249").unwrap();
+        assert!(rs.next().unwrap());
+        assert!(!rs.next().unwrap());
+        rs.close().unwrap();
+
+        conn.close().unwrap();
+    }
+
+    #[test]
+    fn test_broken_connection_rolls_back() {
+        let temp_dir = tempdir().unwrap();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || NetworkServer::new(db).serve(listener));
+
+        let driver = Driver::new();
+        let conn = driver.connect(addr).unwrap();
+        let stmt = conn.create_statement();
+        stmt.execute_update("create table T(A int)").unwrap();
+        stmt.execute_update("insert into T(A) values (1)").unwrap();
+        conn.commit().unwrap();
+
+        // a malformed frame, and a connection dropped in the middle of a frame
+        let breaks: [&[u8]; 2] = [&[0, 0, 0, 1, 0xff], &[0, 0, 0, 9, 4]];
+        for bytes in breaks {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            let mut call = |request: Request| {
+                write_frame(&mut stream, &request.encode()).unwrap();
+                Response::decode(&read_frame(&mut stream).unwrap().unwrap()).unwrap()
+            };
+            call(Request::Connect {
+                version: PROTOCOL_VERSION,
+            });
+            // the update holds an xlock on the table's block
+            let response = call(Request::ExecuteUpdate("update T set A = 2".to_string()));
+            assert!(matches!(response, Response::UpdateCount(1)));
+            stream.write_all(bytes).unwrap();
+            drop(stream);
+
+            // the session is rolled back, so its locks are released well before a lock wait times out
+            let start = Instant::now();
+            let mut rs = stmt.execute_query("select A from T").unwrap();
+            assert!(rs.next().unwrap());
+            assert_eq!(rs.get_int("A").unwrap(), 1);
+            rs.close().unwrap();
+            conn.commit().unwrap();
+            assert!(start.elapsed() < Duration::from_secs(5));
+        }
+
+        conn.close().unwrap();
+    }
+}
@@ -0,0 +1,321 @@
+use anyhow::{bail, Result};
+use bytebuffer::ByteBuffer;
+use core::fmt;
+use std::io::{ErrorKind, Read, Write};
+
+use crate::{
+    query::constant::Constant,
+    record::schema::{FieldType, Schema},
+};
+
+pub const PROTOCOL_VERSION: u32 = 1;
+const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+#[derive(Debug)]
+enum ProtocolError {
+    FrameTooLarge(usize),
+    UnknownTag(&'static str, u8),
+    TrailingBytes(usize),
+}
+
+impl std::error::Error for ProtocolError {}
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProtocolError::FrameTooLarge(len) => write!(
+                f,
+                "frame of {} bytes exceeds the maximum of {} bytes",
+                len, MAX_FRAME_SIZE
+            ),
+            ProtocolError::UnknownTag(kind, tag) => write!(f, "unknown {} tag {}", kind, tag),
+            ProtocolError::TrailingBytes(len) => {
+                write!(f, "{} unexpected bytes at the end of a message", len)
+            }
+        }
+    }
+}
+
+/// A message sent by a client to the server.
+/// Each connection has one current transaction, which the statements run in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request {
+    Connect { version: u32 },
+    ExecuteQuery(String),
+    Fetch { id: u32, max_rows: u32 },
+    CloseResultSet(u32),
+    ExecuteUpdate(String),
+    Commit,
+    Rollback,
+    Close,
+}
+
+/// A message sent by the server in reply to a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Response {
+    Ok,
+    ResultSet {
+        id: u32,
+        schema: Schema,
+    },
+    Rows {
+        rows: Vec<Vec<Constant>>,
+        done: bool,
+    },
+    UpdateCount(u32),
+    Error(String),
+}
+
+impl Request {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = ByteBuffer::new();
+        match self {
+            Request::Connect { version } => {
+                buf.write_u8(0);
+                buf.write_u32(*version);
+            }
+            Request::ExecuteQuery(sql) => {
+                buf.write_u8(1);
+                buf.write_string(sql);
+            }
+            Request::Fetch { id, max_rows } => {
+                buf.write_u8(2);
+                buf.write_u32(*id);
+                buf.write_u32(*max_rows);
+            }
+            Request::CloseResultSet(id) => {
+                buf.write_u8(3);
+                buf.write_u32(*id);
+            }
+            Request::ExecuteUpdate(sql) => {
+                buf.write_u8(4);
+                buf.write_string(sql);
+            }
+            Request::Commit => buf.write_u8(5),
+            Request::Rollback => buf.write_u8(6),
+            Request::Close => buf.write_u8(7),
+        }
+        buf.into_vec()
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut buf = ByteBuffer::from_bytes(bytes);
+        let request = match buf.read_u8()? {
+            0 => Request::Connect {
+                version: buf.read_u32()?,
+            },
+            1 => Request::ExecuteQuery(buf.read_string()?),
+            2 => Request::Fetch {
+                id: buf.read_u32()?,
+                max_rows: buf.read_u32()?,
+            },
+            3 => Request::CloseResultSet(buf.read_u32()?),
+            4 => Request::ExecuteUpdate(buf.read_string()?),
+            5 => Request::Commit,
+            6 => Request::Rollback,
+            7 => Request::Close,
+            tag => bail!(ProtocolError::UnknownTag("request", tag)),
+        };
+        check_consumed(&buf)?;
+        Ok(request)
+    }
+}
+
+impl Response {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = ByteBuffer::new();
+        match self {
+            Response::Ok => buf.write_u8(0),
+            Response::ResultSet { id, schema } => {
+                buf.write_u8(1);
+                buf.write_u32(*id);
+                buf.write_u32(schema.fields().len() as u32);
+                for field_name in schema.fields() {
+                    buf.write_string(field_name);
+                    buf.write_i32(schema.field_type(field_name).unwrap() as i32);
+                    buf.write_u32(schema.length(field_name).unwrap() as u32);
+                }
+            }
+            Response::Rows { rows, done } => {
+                buf.write_u8(2);
+                buf.write_u32(rows.len() as u32);
+                for row in rows {
+                    buf.write_u32(row.len() as u32);
+                    for val in row {
+                        match val {
+                            Constant::Int(n) => {
+                                buf.write_u8(0);
+                                buf.write_i32(*n);
+                            }
+                            Constant::String(s) => {
+                                buf.write_u8(1);
+                                buf.write_string(s);
+                            }
+                        }
+                    }
+                }
+                buf.write_u8(*done as u8);
+            }
+            Response::UpdateCount(count) => {
+                buf.write_u8(3);
+                buf.write_u32(*count);
+            }
+            Response::Error(msg) => {
+                buf.write_u8(4);
+                buf.write_string(msg);
+            }
+        }
+        buf.into_vec()
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut buf = ByteBuffer::from_bytes(bytes);
+        let response = match buf.read_u8()? {
+            0 => Response::Ok,
+            1 => {
+                let id = buf.read_u32()?;
+                let mut schema = Schema::new();
+                for _ in 0..buf.read_u32()? {
+                    let field_name = buf.read_string()?;
+                    let field_type = FieldType::try_from(buf.read_i32()?)?;
+                    let length = buf.read_u32()? as usize;
+                    schema.add_field(&field_name, field_type, length);
+                }
+                Response::ResultSet { id, schema }
+            }
+            2 => {
+                // the counts come from the peer, so rows are not preallocated from them
+                let mut rows = vec![];
+                for _ in 0..buf.read_u32()? {
+                    let mut row = vec![];
+                    for _ in 0..buf.read_u32()? {
+                        row.push(match buf.read_u8()? {
+                            0 => Constant::Int(buf.read_i32()?),
+                            1 => Constant::String(buf.read_string()?),
+                            tag => bail!(ProtocolError::UnknownTag("constant", tag)),
+                        });
+                    }
+                    rows.push(row);
+                }
+                let done = buf.read_u8()? != 0;
+                Response::Rows { rows, done }
+            }
+            3 => Response::UpdateCount(buf.read_u32()?),
+            4 => Response::Error(buf.read_string()?),
+            tag => bail!(ProtocolError::UnknownTag("response", tag)),
+        };
+        check_consumed(&buf)?;
+        Ok(response)
+    }
+}
+
+fn check_consumed(buf: &ByteBuffer) -> Result<()> {
+    let remaining = buf.len() - buf.get_rpos();
+    if remaining > 0 {
+        bail!(ProtocolError::TrailingBytes(remaining));
+    }
+    Ok(())
+}
+
+/// Write a message as a frame: a 4-byte big-endian length followed by the message bytes.
+pub fn write_frame(writer: &mut impl Write, bytes: &[u8]) -> Result<()> {
+    if bytes.len() > MAX_FRAME_SIZE {
+        bail!(ProtocolError::FrameTooLarge(bytes.len()));
+    }
+    writer.write_all(&(bytes.len() as u32).to_be_bytes())?;
+    writer.write_all(bytes)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Read the next frame.
+/// Return None if the peer closed the stream between two frames.
+pub fn read_frame(reader: &mut impl Read) -> Result<Option<Vec<u8>>> {
+    let mut len = [0u8; 4];
+    let mut read = 0;
+    while read < len.len() {
+        match reader.read(&mut len[read..]) {
+            Ok(0) if read == 0 => return Ok(None),
+            Ok(0) => bail!(std::io::Error::from(ErrorKind::UnexpectedEof)),
+            Ok(n) => read += n,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_SIZE {
+        bail!(ProtocolError::FrameTooLarge(len));
+    }
+    let mut bytes = vec![0u8; len];
+    reader.read_exact(&mut bytes)?;
+    Ok(Some(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{query::constant::Constant, record::schema::Schema};
+
+    use super::{read_frame, write_frame, Request, Response};
+
+    #[test]
+    fn test_round_trip() {
+        let requests = vec![
+            Request::Connect { version: 1 },
+            Request::ExecuteQuery("select a from t".to_string()),
+            Request::Fetch {
+                id: 3,
+                max_rows: 100,
+            },
+            Request::CloseResultSet(3),
+            Request::ExecuteUpdate("delete from t".to_string()),
+            Request::Commit,
+            Request::Rollback,
+            Request::Close,
+        ];
+        for request in requests {
+            assert_eq!(Request::decode(&request.encode()).unwrap(), request);
+        }
+
+        let mut schema = Schema::new();
+        schema.add_int_field("a");
+        schema.add_string_field("b", 9);
+        let responses = vec![
+            Response::Ok,
+            Response::ResultSet { id: 1, schema },
+            Response::Rows {
+                rows: vec![
+                    vec![Constant::Int(-1), Constant::String("héllo".to_string())],
+                    vec![Constant::Int(2), Constant::String(String::new())],
+                ],
+                done: true,
+            },
+            Response::UpdateCount(7),
+            Response::Error("table not found: t".to_string()),
+        ];
+        for response in responses {
+            assert_eq!(Response::decode(&response.encode()).unwrap(), response);
+        }
+
+        assert!(Request::decode(&[42]).is_err());
+        assert!(Request::decode(&[5, 0]).is_err());
+        assert!(Response::decode(&[3, 0]).is_err());
+    }
+
+    #[test]
+    fn test_frames() {
+        let mut stream = vec![];
+        write_frame(&mut stream, &Request::Commit.encode()).unwrap();
+        write_frame(&mut stream, &[]).unwrap();
+
+        let mut reader = stream.as_slice();
+        let frame = read_frame(&mut reader).unwrap().unwrap();
+        assert_eq!(Request::decode(&frame).unwrap(), Request::Commit);
+        assert_eq!(read_frame(&mut reader).unwrap().unwrap(), Vec::<u8>::new());
+        assert!(read_frame(&mut reader).unwrap().is_none());
+
+        // a frame cut short is an error rather than the end of the stream
+        let mut reader = &stream[..6];
+        read_frame(&mut reader).unwrap();
+        assert!(read_frame(&mut reader).is_err());
+    }
+}
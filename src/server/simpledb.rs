@@ -0,0 +1,254 @@
+use anyhow::{Ok, Result};
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    buffer::manager::BufferManager,
+    file::manager::FileManager,
+    log::manager::LogManager,
+    metadata::manager::MetadataManager,
+    plan::{
+        basic_query_planner::BasicQueryPlanner, basic_update_planner::BasicUpdatePlanner,
+        planner::Planner, query_planner::QueryPlanner,
+    },
+    tx::{concurrency::lock_table::LockTable, transaction::Transaction},
+};
+
+pub const BLOCK_SIZE: usize = 1024;
+pub const BUFFER_SIZE: usize = 8;
+pub const LOG_FILE: &str = "simpledb.log";
+
+/// The class that configures the system.
+/// Opening a database creates the file, log, buffer and metadata managers and the planner.
+/// An existing database is recovered before any client transaction starts;
+/// a new database gets its catalog tables.
+pub struct SimpleDB {
+    file_manager: Arc<FileManager>,
+    log_manager: Arc<Mutex<LogManager>>,
+    buffer_manager: Arc<BufferManager>,
+    lock_table: Arc<Mutex<LockTable>>,
+    metadata_manager: Arc<MetadataManager>,
+    planner: Arc<Planner>,
+}
+
+impl SimpleDB {
+    pub fn new(db_dir: &str, block_size: usize, num_buffers: usize) -> Result<Self> {
+        Self::with_file_manager(FileManager::new(db_dir, block_size)?, num_buffers)
+    }
+
+    /// Open a database using a file manager that has already been configured, for example with checksums enabled.
+    /// Blocks torn by a crash are restored from the double-write area before the log is opened.
+    pub fn with_file_manager(file_manager: FileManager, num_buffers: usize) -> Result<Self> {
+        file_manager.restore_torn_blocks()?;
+        let file_manager = Arc::new(file_manager);
+        let is_new = file_manager.is_new();
+        let log_manager = Arc::new(Mutex::new(LogManager::new(
+            Arc::clone(&file_manager),
+            LOG_FILE,
+        )?));
+        let buffer_manager = Arc::new(BufferManager::new(
+            Arc::clone(&file_manager),
+            Arc::clone(&log_manager),
+            num_buffers,
+        ));
+        let lock_table = Arc::new(Mutex::new(LockTable::new()));
+
+        let tx = Arc::new(Mutex::new(Transaction::new(
+            Arc::clone(&file_manager),
+            Arc::clone(&log_manager),
+            Arc::clone(&buffer_manager),
+            Arc::clone(&lock_table),
+        )?));
+        if !is_new {
+            tx.lock().unwrap().recover()?;
+        }
+        let metadata_manager = Arc::new(MetadataManager::new(is_new, Arc::clone(&tx))?);
+        tx.lock().unwrap().commit()?;
+
+        let planner = Arc::new(Planner::new(
+            Box::new(BasicQueryPlanner::new(Arc::clone(&metadata_manager))),
+            Box::new(BasicUpdatePlanner::new(Arc::clone(&metadata_manager))),
+        ));
+
+        Ok(Self {
+            file_manager,
+            log_manager,
+            buffer_manager,
+            lock_table,
+            metadata_manager,
+            planner,
+        })
+    }
+
+    /// Open a database with the default block size and number of buffers.
+    pub fn open(db_dir: &str) -> Result<Self> {
+        Self::new(db_dir, BLOCK_SIZE, BUFFER_SIZE)
+    }
+
+    /// Replace the query planner, for example with a `HeuristicQueryPlanner` in place of the default basic planner.
+    /// Update statements are still executed by the basic update planner.
+    pub fn with_query_planner(mut self, query_planner: Box<dyn QueryPlanner>) -> Self {
+        self.planner = Arc::new(Planner::new(
+            query_planner,
+            Box::new(BasicUpdatePlanner::new(Arc::clone(&self.metadata_manager))),
+        ));
+        self
+    }
+
+    /// Start a new transaction on the database.
+    pub fn new_tx(&self) -> Result<Arc<Mutex<Transaction>>> {
+        Ok(Arc::new(Mutex::new(Transaction::new(
+            Arc::clone(&self.file_manager),
+            Arc::clone(&self.log_manager),
+            Arc::clone(&self.buffer_manager),
+            Arc::clone(&self.lock_table),
+        )?)))
+    }
+
+    pub fn file_manager(&self) -> Arc<FileManager> {
+        Arc::clone(&self.file_manager)
+    }
+
+    pub fn log_manager(&self) -> Arc<Mutex<LogManager>> {
+        Arc::clone(&self.log_manager)
+    }
+
+    pub fn buffer_manager(&self) -> Arc<BufferManager> {
+        Arc::clone(&self.buffer_manager)
+    }
+
+    pub fn metadata_manager(&self) -> Arc<MetadataManager> {
+        Arc::clone(&self.metadata_manager)
+    }
+
+    pub fn planner(&self) -> Arc<Planner> {
+        Arc::clone(&self.planner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+
+    use crate::file::manager::{Durability, FileManager};
+
+    use super::SimpleDB;
+
+    #[test]
+    fn test_recovery_on_reopen() {
+        let temp_dir = tempdir().unwrap();
+        let configs = [
+            (false, false, Durability::Always),
+            (true, false, Durability::Always),
+            (true, true, Durability::Always),
+            (true, true, Durability::OnCommit),
+        ];
+        for (i, (checksums, double_write, durability)) in configs.into_iter().enumerate() {
+            let db_dir = temp_dir.path().join(format!("recoverytest{}", i));
+            recover_on_reopen(
+                db_dir.to_str().unwrap(),
+                checksums,
+                double_write,
+                durability,
+            );
+        }
+    }
+
+    #[test]
+    fn test_user_table_with_temp_prefix_survives_reopen() {
+        let temp_dir = tempdir().unwrap();
+        let db_dir = temp_dir.path().to_str().unwrap();
+
+        {
+            let db = SimpleDB::new(db_dir, 800, 8).unwrap();
+            let planner = db.planner();
+            let tx = db.new_tx().unwrap();
+            planner
+                .execute_update("create table temperature(A int)", Arc::clone(&tx))
+                .unwrap();
+            planner
+                .execute_update("insert into temperature(A) values (21)", Arc::clone(&tx))
+                .unwrap();
+            // names that temporary tables use are reserved
+            assert!(planner
+                .execute_update("create table temp7(A int)", Arc::clone(&tx))
+                .is_err());
+            tx.lock().unwrap().commit().unwrap();
+        }
+
+        let db = SimpleDB::new(db_dir, 800, 8).unwrap();
+        let tx = db.new_tx().unwrap();
+        let plan = db
+            .planner()
+            .create_query_plan("select A from temperature", Arc::clone(&tx))
+            .unwrap();
+        let mut scan = plan.open().unwrap();
+        assert!(scan.next().unwrap());
+        assert_eq!(scan.get_int("a").unwrap(), 21);
+        assert!(!scan.next().unwrap());
+        scan.close().unwrap();
+        tx.lock().unwrap().commit().unwrap();
+    }
+
+    fn recover_on_reopen(
+        db_dir: &str,
+        checksums: bool,
+        double_write: bool,
+        durability: Durability,
+    ) {
+        let open = || {
+            let file_manager = FileManager::new(db_dir, 800)
+                .unwrap()
+                .with_checksums(checksums)
+                .with_double_write(double_write)
+                .with_durability(durability);
+            SimpleDB::with_file_manager(file_manager, 8).unwrap()
+        };
+
+        {
+            let db = open();
+            let planner = db.planner();
+
+            let tx = db.new_tx().unwrap();
+            planner
+                .execute_update("create table T(A int, B varchar(9))", Arc::clone(&tx))
+                .unwrap();
+            planner
+                .execute_update("insert into T(A, B) values (1, 'one')", Arc::clone(&tx))
+                .unwrap();
+            tx.lock().unwrap().commit().unwrap();
+
+            // an uncommitted change whose page reaches the disk
+            let tx = db.new_tx().unwrap();
+            planner
+                .execute_update("update T set B = 'dirty'", Arc::clone(&tx))
+                .unwrap();
+            db.buffer_manager()
+                .flush_all(tx.lock().unwrap().txnum())
+                .unwrap();
+            // the process "crashes" without committing or rolling back
+        }
+        if double_write {
+            // the crash also tore the in-place write of the table's block
+            let path = std::path::Path::new(db_dir).join("t.tbl");
+            let bytes = std::fs::read(&path).unwrap();
+            std::fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+        }
+
+        let db = open();
+        let tx = db.new_tx().unwrap();
+        let plan = db
+            .planner()
+            .create_query_plan("select A, B from T", Arc::clone(&tx))
+            .unwrap();
+        let mut scan = plan.open().unwrap();
+        assert!(scan.next().unwrap());
+        assert_eq!(scan.get_int("a").unwrap(), 1);
+        assert_eq!(scan.get_string("b").unwrap(), "one");
+        assert!(!scan.next().unwrap());
+        scan.close().unwrap();
+
+        tx.lock().unwrap().commit().unwrap();
+    }
+}
@@ -1,21 +1,18 @@
 use anyhow::Result;
-use std::{
-    collections::HashMap,
-    sync::{Arc, Mutex},
-};
+use std::{collections::HashMap, sync::Arc};
 
 use crate::{buffer::manager::BufferManager, file::block_id::BlockId};
 
 #[derive(Debug)]
 pub struct BufferList {
-    buffer_manager: Arc<Mutex<BufferManager>>,
+    buffer_manager: Arc<BufferManager>,
     buffers: HashMap<BlockId, usize>,
     pins: Vec<BlockId>,
 }
 
 /// Manages the transaction's currently-pinned buffers.
 impl BufferList {
-    pub fn new(buffer_manager: Arc<Mutex<BufferManager>>) -> Self {
+    pub fn new(buffer_manager: Arc<BufferManager>) -> Self {
         Self {
             buffer_manager,
             buffers: HashMap::new(),
@@ -28,7 +25,7 @@ impl BufferList {
     }
 
     pub fn pin(&mut self, block: &BlockId) -> Result<()> {
-        let buffer = self.buffer_manager.lock().unwrap().pin(block)?;
+        let buffer = self.buffer_manager.pin(block)?;
 
         self.buffers.insert(block.clone(), buffer);
         self.pins.push(block.clone());
@@ -37,9 +34,12 @@ impl BufferList {
 
     pub fn unpin(&mut self, block: &BlockId) -> Result<()> {
         if let Some(idx) = self.buffers.get(block) {
-            self.buffer_manager.lock().unwrap().unpin(*idx)?;
+            self.buffer_manager.unpin(*idx)?;
 
-            self.pins.retain(|e| e != block);
+            // the block may be pinned more than once, so only forget a single pin
+            if let Some(pos) = self.pins.iter().position(|e| e == block) {
+                self.pins.remove(pos);
+            }
 
             if !self.pins.contains(block) {
                 self.buffers.remove(block);
@@ -51,7 +51,7 @@ impl BufferList {
     pub fn unpin_all(&mut self) -> Result<()> {
         for block in &self.pins {
             if let Some(idx) = self.buffers.get(block) {
-                self.buffer_manager.lock().unwrap().unpin(*idx)?
+                self.buffer_manager.unpin(*idx)?
             }
         }
 
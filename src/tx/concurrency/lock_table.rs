@@ -33,6 +33,12 @@ pub struct LockTable {
     state: Arc<(Mutex<HashMap<BlockId, Lock>>, Condvar)>,
 }
 
+impl Default for LockTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl LockTable {
     pub fn new() -> Self {
         Self {
@@ -47,7 +53,7 @@ impl LockTable {
         let mut locks = lock.lock().unwrap();
 
         loop {
-            match locks.get(&block) {
+            match locks.get(block) {
                 Some(Lock::Exclusive) => {
                     let (new_locks, timeout) = cvar
                         .wait_timeout(locks, Duration::from_millis(MAX_TIME as u64))
@@ -79,7 +85,7 @@ impl LockTable {
         let mut locks = lock.lock().unwrap();
 
         loop {
-            match locks.get(&block) {
+            match locks.get(block) {
                 Some(Lock::Shared(count)) if *count > 1 => {
                     let (new_locks, timeout) = cvar
                         .wait_timeout(locks, Duration::from_millis(MAX_TIME as u64))
@@ -185,7 +191,7 @@ mod tests {
         assert!(lock_table.slock(&block).is_err());
     }
 
-    fn get_locks(lock_table: &LockTable) -> MutexGuard<HashMap<BlockId, Lock>> {
+    fn get_locks(lock_table: &LockTable) -> MutexGuard<'_, HashMap<BlockId, Lock>> {
         let (lock, _) = &*lock_table.state;
         lock.lock().unwrap()
     }
@@ -40,7 +40,7 @@ impl ConcurrencyManager {
             Some(Lock::Exclusive) => Ok(()),
             Some(Lock::Shared) => Ok(()),
             _ => {
-                self.lock_table.lock().unwrap().slock(block)?;
+                self.lock_table().slock(block)?;
                 self.locks.insert(block.clone(), Lock::Shared);
                 Ok(())
             }
@@ -52,7 +52,7 @@ impl ConcurrencyManager {
             Some(Lock::Exclusive) => Ok(()),
             _ => {
                 self.slock(block)?;
-                self.lock_table.lock().unwrap().xlock(block)?;
+                self.lock_table().xlock(block)?;
                 self.locks.insert(block.clone(), Lock::Exclusive);
                 Ok(())
             }
@@ -61,11 +61,17 @@ impl ConcurrencyManager {
 
     pub fn release(&mut self) -> Result<()> {
         for block in self.locks.keys() {
-            self.lock_table.lock().unwrap().unlock(block)?;
+            self.lock_table().unlock(block)?;
         }
         self.locks.clear();
         Ok(())
     }
+
+    /// The lock table shares its state, so waiting on a clone does not keep the
+    /// global mutex held and block other transactions from releasing their locks.
+    fn lock_table(&self) -> LockTable {
+        self.lock_table.lock().unwrap().clone()
+    }
 }
 
 #[cfg(test)]
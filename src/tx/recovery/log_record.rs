@@ -47,17 +47,17 @@ pub trait LogRecord {
 }
 
 pub fn create_log_record(bytes: Vec<u8>) -> Result<Box<dyn LogRecord>> {
-    let mut p = Page::from_bytes(bytes);
+    let p = Page::from_bytes(bytes);
     let value = p.get_int(0)?;
     match LogOperation::try_from(value)
         .map_err(|err| LogRecordError::UnkownLogOperation(err.number))?
     {
         LogOperation::Checkpoint => Ok(Box::new(CheckpointRecord::new()?)),
-        LogOperation::Start => Ok(Box::new(StartRecord::new(&mut p)?)),
-        LogOperation::Commit => Ok(Box::new(CommitRecord::new(&mut p)?)),
-        LogOperation::Rollback => Ok(Box::new(RollbackRecord::new(&mut p)?)),
-        LogOperation::SetInt => Ok(Box::new(SetIntRecord::new(&mut p)?)),
-        LogOperation::SetString => Ok(Box::new(SetStringRecord::new(&mut p)?)),
+        LogOperation::Start => Ok(Box::new(StartRecord::new(&p)?)),
+        LogOperation::Commit => Ok(Box::new(CommitRecord::new(&p)?)),
+        LogOperation::Rollback => Ok(Box::new(RollbackRecord::new(&p)?)),
+        LogOperation::SetInt => Ok(Box::new(SetIntRecord::new(&p)?)),
+        LogOperation::SetString => Ok(Box::new(SetStringRecord::new(&p)?)),
     }
 }
 
@@ -78,7 +78,7 @@ impl CheckpointRecord {
         let mut p = Page::new(std::mem::size_of::<i32>());
         p.set_int(0, LogOperation::Checkpoint as i32)?;
 
-        log_manager.lock().unwrap().append(p.contents().as_bytes())
+        log_manager.lock().unwrap().append(p.contents())
     }
 }
 
@@ -106,7 +106,7 @@ impl fmt::Display for StartRecord {
 }
 
 impl StartRecord {
-    pub fn new(p: &mut Page) -> Result<Self> {
+    pub fn new(p: &Page) -> Result<Self> {
         let tpos = std::mem::size_of::<i32>();
         Ok(Self {
             txnum: p.get_int(tpos)?,
@@ -119,7 +119,7 @@ impl StartRecord {
         p.set_int(0, LogOperation::Start as i32)?;
         p.set_int(tpos, txnum)?;
 
-        log_manager.lock().unwrap().append(p.contents().as_bytes())
+        log_manager.lock().unwrap().append(p.contents())
     }
 }
 
@@ -128,7 +128,7 @@ impl LogRecord for StartRecord {
         LogOperation::Start
     }
     fn tx_number(&self) -> i32 {
-        -1
+        self.txnum
     }
 
     fn undo(&self, _: &mut Transaction) -> Result<()> {
@@ -147,7 +147,7 @@ impl fmt::Display for CommitRecord {
 }
 
 impl CommitRecord {
-    pub fn new(p: &mut Page) -> Result<Self> {
+    pub fn new(p: &Page) -> Result<Self> {
         let tpos = std::mem::size_of::<i32>();
         Ok(Self {
             txnum: p.get_int(tpos)?,
@@ -160,7 +160,7 @@ impl CommitRecord {
         p.set_int(0, LogOperation::Commit as i32)?;
         p.set_int(tpos, txnum)?;
 
-        log_manager.lock().unwrap().append(p.contents().as_bytes())
+        log_manager.lock().unwrap().append(p.contents())
     }
 }
 
@@ -169,7 +169,7 @@ impl LogRecord for CommitRecord {
         LogOperation::Commit
     }
     fn tx_number(&self) -> i32 {
-        -1
+        self.txnum
     }
 
     fn undo(&self, _: &mut Transaction) -> Result<()> {
@@ -188,7 +188,7 @@ impl fmt::Display for RollbackRecord {
 }
 
 impl RollbackRecord {
-    pub fn new(p: &mut Page) -> Result<Self> {
+    pub fn new(p: &Page) -> Result<Self> {
         let tpos = std::mem::size_of::<i32>();
         Ok(Self {
             txnum: p.get_int(tpos)?,
@@ -201,7 +201,7 @@ impl RollbackRecord {
         p.set_int(0, LogOperation::Rollback as i32)?;
         p.set_int(tpos, txnum)?;
 
-        log_manager.lock().unwrap().append(p.contents().as_bytes())
+        log_manager.lock().unwrap().append(p.contents())
     }
 }
 
@@ -210,7 +210,7 @@ impl LogRecord for RollbackRecord {
         LogOperation::Rollback
     }
     fn tx_number(&self) -> i32 {
-        -1
+        self.txnum
     }
 
     fn undo(&self, _: &mut Transaction) -> Result<()> {
@@ -236,14 +236,14 @@ impl fmt::Display for SetIntRecord {
 }
 
 impl SetIntRecord {
-    pub fn new(p: &mut Page) -> Result<Self> {
+    pub fn new(p: &Page) -> Result<Self> {
         let tpos = std::mem::size_of::<i64>();
         let txnum = p.get_int(tpos)?;
         let fpos = tpos + std::mem::size_of::<i32>();
         let filename = p.get_string(fpos)?;
         let bpos = fpos + Page::max_length(filename.len());
         let block_number = p.get_int(bpos)? as usize;
-        let block = BlockId::new(&filename, block_number);
+        let block = BlockId::new(filename, block_number);
         let opos = bpos + std::mem::size_of::<i32>();
         let offset = p.get_int(opos)? as usize;
         let vpos = opos + std::mem::size_of::<i32>();
@@ -280,7 +280,7 @@ impl SetIntRecord {
         p.set_int(opos, offset as i32)?;
         p.set_int(vpos, val)?;
 
-        log_manager.lock().unwrap().append(p.contents().as_bytes())
+        log_manager.lock().unwrap().append(p.contents())
     }
 }
 
@@ -320,18 +320,18 @@ impl fmt::Display for SetStringRecord {
 }
 
 impl SetStringRecord {
-    pub fn new(p: &mut Page) -> Result<Self> {
+    pub fn new(p: &Page) -> Result<Self> {
         let tpos = std::mem::size_of::<i64>();
         let txnum = p.get_int(tpos)?;
         let fpos = tpos + std::mem::size_of::<i32>();
         let filename = p.get_string(fpos)?;
         let bpos = fpos + Page::max_length(filename.len());
         let block_number = p.get_int(bpos)?;
-        let block = BlockId::new(&filename, block_number as usize);
+        let block = BlockId::new(filename, block_number as usize);
         let opos = bpos + std::mem::size_of::<i32>();
         let offset = p.get_int(opos)? as usize;
         let vpos = opos + std::mem::size_of::<i32>();
-        let val = p.get_string(vpos)?;
+        let val = p.get_string(vpos)?.to_string();
 
         Ok(Self {
             txnum,
@@ -364,7 +364,7 @@ impl SetStringRecord {
         p.set_int(opos, offset as i32)?;
         p.set_string(vpos, val)?;
 
-        log_manager.lock().unwrap().append(p.contents().as_bytes())
+        log_manager.lock().unwrap().append(p.contents())
     }
 }
 
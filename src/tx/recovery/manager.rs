@@ -31,7 +31,7 @@ impl fmt::Display for RecoveryManagerError {
 #[derive(Debug, Clone)]
 pub struct RecoveryManager {
     log_manager: Arc<Mutex<LogManager>>,
-    buffer_manager: Arc<Mutex<BufferManager>>,
+    buffer_manager: Arc<BufferManager>,
     txnum: i32,
 }
 
@@ -39,7 +39,7 @@ pub struct RecoveryManager {
 impl RecoveryManager {
     pub fn new(
         log_manager: Arc<Mutex<LogManager>>,
-        buffer_manager: Arc<Mutex<BufferManager>>,
+        buffer_manager: Arc<BufferManager>,
         txnum: i32,
     ) -> Result<Self> {
         StartRecord::write_to_log(Arc::clone(&log_manager), txnum)?;
@@ -52,7 +52,7 @@ impl RecoveryManager {
 
     /// Write a commit record to the log, and flushes it to disk.
     pub fn commit(&self) -> Result<()> {
-        self.buffer_manager.lock().unwrap().flush_all(self.txnum)?;
+        self.buffer_manager.flush_all(self.txnum)?;
         let lsn = CommitRecord::write_to_log(Arc::clone(&self.log_manager), self.txnum)?;
         self.log_manager.lock().unwrap().flush(lsn)?;
         Ok(())
@@ -61,7 +61,7 @@ impl RecoveryManager {
     /// Write a rollback record to the log and flush it to disk.
     pub fn rollback(&self, tx: &mut Transaction) -> Result<()> {
         self.do_rollback(tx)?;
-        self.buffer_manager.lock().unwrap().flush_all(self.txnum)?;
+        self.buffer_manager.flush_all(self.txnum)?;
         let lsn = RollbackRecord::write_to_log(Arc::clone(&self.log_manager), self.txnum)?;
         self.log_manager.lock().unwrap().flush(lsn)?;
         Ok(())
@@ -70,7 +70,7 @@ impl RecoveryManager {
     /// Recover uncompleted transactions from the log and then write a quiescent checkpoint record to the log and flush it to disk.
     pub fn recover(&self, tx: &mut Transaction) -> Result<()> {
         self.do_recover(tx)?;
-        self.buffer_manager.lock().unwrap().flush_all(self.txnum)?;
+        self.buffer_manager.flush_all(self.txnum)?;
         let lsn = CheckpointRecord::write_to_log(Arc::clone(&self.log_manager))?;
         self.log_manager.lock().unwrap().flush(lsn)?;
         Ok(())
@@ -93,7 +93,7 @@ impl RecoveryManager {
 
     /// Write a setstring record to the log, flushes it to disk and return its lsn.
     pub fn set_string(&self, buf: &mut Buffer, offset: usize, _: &str) -> Result<Lsn> {
-        let old_val = buf.contents().get_string(offset)?;
+        let old_val = buf.contents().get_string(offset)?.to_string();
         if let Some(block) = buf.block() {
             return SetStringRecord::write_to_log(
                 Arc::clone(&self.log_manager),
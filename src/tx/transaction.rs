@@ -1,6 +1,9 @@
 use anyhow::{Ok, Result};
 use core::fmt;
-use std::sync::{atomic::{AtomicI32, Ordering}, Arc, Mutex};
+use std::sync::{
+    atomic::{AtomicI32, Ordering},
+    Arc, Mutex,
+};
 
 use crate::{
     buffer::manager::BufferManager,
@@ -35,8 +38,8 @@ impl fmt::Display for TransactionError {
 pub struct Transaction {
     recovery_manager: RecoveryManager,
     concurrency_manager: ConcurrencyManager,
-    buffer_manager: Arc<Mutex<BufferManager>>,
-    file_manager: Arc<Mutex<FileManager>>,
+    buffer_manager: Arc<BufferManager>,
+    file_manager: Arc<FileManager>,
     buffers: BufferList,
     txnum: i32,
 }
@@ -44,17 +47,14 @@ pub struct Transaction {
 /// Provides transaction management for clients, ensuring that all transactions are serializable, recoverable, and in general satisfy the ACID properties.
 impl Transaction {
     pub fn new(
-        file_manager: Arc<Mutex<FileManager>>,
+        file_manager: Arc<FileManager>,
         log_manager: Arc<Mutex<LogManager>>,
-        buffer_manager: Arc<Mutex<BufferManager>>,
+        buffer_manager: Arc<BufferManager>,
         lock_table: Arc<Mutex<LockTable>>,
     ) -> Result<Self> {
         let txnum = NEXT_TX_NUM.fetch_add(1, Ordering::SeqCst);
-        let recovery_manager = RecoveryManager::new(
-            Arc::clone(&log_manager),
-            Arc::clone(&buffer_manager),
-            txnum,
-        )?;
+        let recovery_manager =
+            RecoveryManager::new(Arc::clone(&log_manager), Arc::clone(&buffer_manager), txnum)?;
         let concurrency_manager = ConcurrencyManager::new(Arc::clone(&lock_table));
         let tx_buffers = BufferList::new(Arc::clone(&buffer_manager));
 
@@ -92,7 +92,7 @@ impl Transaction {
     /// Finally, write a quiescent checkpoint record to the log.
     /// This method is called during system startup, before user transactions begin.
     pub fn recover(&mut self) -> Result<()> {
-        self.buffer_manager.lock().unwrap().flush_all(self.txnum)?;
+        self.buffer_manager.flush_all(self.txnum)?;
         let recovery_manager = self.recovery_manager.clone();
         recovery_manager.recover(self)?; // think about redesigning to not need this
         Ok(())
@@ -112,13 +112,13 @@ impl Transaction {
 
     /// Return the integer value stored at the specified offset of the specified block.
     /// The method first obtains an slock on the block, then it calls the buffer to retrieve the value.
+    /// The buffer is only read-locked, so transactions reading the same buffer do not wait for each other.
     pub fn get_int(&mut self, block: &BlockId, offset: usize) -> Result<i32> {
         self.concurrency_manager.slock(block)?;
 
         if let Some(idx) = self.buffers.get_buffer_idx(block) {
-            let (lock, _) = &*self.buffer_manager.lock().unwrap().state;
-            let mut state = lock.lock().unwrap();
-            return Ok(state.buffer_pool[idx].contents().get_int(offset)?);
+            let buffer = self.buffer_manager.buffer(idx);
+            return Ok(buffer.contents().get_int(offset)?);
         }
 
         Err(TransactionError::TransactionAbort.into())
@@ -130,9 +130,8 @@ impl Transaction {
         self.concurrency_manager.slock(block)?;
 
         if let Some(idx) = self.buffers.get_buffer_idx(block) {
-            let (lock, _) = &*self.buffer_manager.lock().unwrap().state;
-            let mut state = lock.lock().unwrap();
-            return Ok(state.buffer_pool[idx].contents().get_string(offset)?);
+            let buffer = self.buffer_manager.buffer(idx);
+            return Ok(buffer.contents().get_string(offset)?.to_string());
         }
 
         Err(TransactionError::TransactionAbort.into())
@@ -152,15 +151,14 @@ impl Transaction {
         self.concurrency_manager.xlock(block)?;
 
         if let Some(idx) = self.buffers.get_buffer_idx(block) {
-            let (lock, _) = &*self.buffer_manager.lock().unwrap().state;
-            let mut state = lock.lock().unwrap();
+            let mut buffer = self.buffer_manager.buffer_mut(idx);
             let mut lsn = -1;
             if ok_to_log {
-                lsn = self.recovery_manager.set_int(&mut state.buffer_pool[idx], offset, val)?;
+                lsn = self.recovery_manager.set_int(&mut buffer, offset, val)?;
             }
 
-            state.buffer_pool[idx].contents().set_int(offset, val)?;
-            state.buffer_pool[idx].set_modified(self.txnum, lsn)?;
+            buffer.contents_mut().set_int(offset, val)?;
+            buffer.set_modified(self.txnum, lsn)?;
             return Ok(());
         }
 
@@ -181,15 +179,14 @@ impl Transaction {
         self.concurrency_manager.xlock(block)?;
 
         if let Some(idx) = self.buffers.get_buffer_idx(block) {
-            let (lock, _) = &*self.buffer_manager.lock().unwrap().state;
-            let mut state = lock.lock().unwrap();
+            let mut buffer = self.buffer_manager.buffer_mut(idx);
             let mut lsn = -1;
             if ok_to_log {
-                lsn = self.recovery_manager.set_string(&mut state.buffer_pool[idx], offset, val)?;
+                lsn = self.recovery_manager.set_string(&mut buffer, offset, val)?;
             }
 
-            state.buffer_pool[idx].contents().set_string(offset, val)?;
-            state.buffer_pool[idx].set_modified(self.txnum, lsn)?;
+            buffer.contents_mut().set_string(offset, val)?;
+            buffer.set_modified(self.txnum, lsn)?;
             return Ok(());
         }
 
@@ -201,7 +198,7 @@ impl Transaction {
     pub fn size(&mut self, filename: &str) -> Result<usize> {
         let dummy_block = BlockId::new(filename, END_OF_FILE as usize);
         self.concurrency_manager.slock(&dummy_block)?;
-        Ok(self.file_manager.lock().unwrap().length(filename)?)
+        Ok(self.file_manager.length(filename)?)
     }
 
     /// Append a new block to the end of the specified file and returns a reference to it.
@@ -209,67 +206,42 @@ impl Transaction {
     pub fn append(&mut self, filename: &str) -> Result<BlockId> {
         let dummy_block = BlockId::new(filename, END_OF_FILE as usize);
         self.concurrency_manager.xlock(&dummy_block)?;
-        Ok(self.file_manager.lock().unwrap().append(filename)?)
+        Ok(self.file_manager.append(filename)?)
+    }
+
+    pub fn txnum(&self) -> i32 {
+        self.txnum
     }
 
     pub fn block_size(&self) -> usize {
-        self.file_manager.lock().unwrap().block_size()
+        self.file_manager.block_size()
     }
 
     pub fn available_buffs(&self) -> usize {
-        self.buffer_manager.lock().unwrap().available()
+        self.buffer_manager.available()
     }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use std::sync::{Arc, Mutex};
+    use std::{sync::mpsc, thread, time::Duration};
 
     use tempfile::tempdir;
 
-    use crate::{
-        buffer::manager::BufferManager, file::{block_id::BlockId, manager::FileManager}, log::manager::LogManager, tx::concurrency::lock_table::LockTable
-    };
-
-    use super::Transaction;
+    use crate::{file::block_id::BlockId, server::simpledb::SimpleDB};
 
     #[test]
     fn test_transaction_lifecycle() {
         let temp_dir = tempdir().unwrap();
-        let db_dir = temp_dir.path().to_str().unwrap();
-        let test_file = temp_dir
-            .path()
-            .join("simpledb.log")
-            .to_str()
-            .unwrap()
-            .to_string();
-
-        let block_size = 400;
-        let num_buffers = 8;
-
-        let file_manager = Arc::new(Mutex::new(FileManager::new(db_dir, block_size).unwrap()));
-        let log_manager = Arc::new(Mutex::new(
-            LogManager::new(Arc::clone(&file_manager), &test_file).unwrap(),
-        ));
-        let buffer_manager = Arc::new(Mutex::new(BufferManager::new(
-            Arc::clone(&file_manager),
-            Arc::clone(&log_manager),
-            num_buffers,
-        )));
-
-        let lock_table = Arc::new(Mutex::new(LockTable::new()));
-
-         // The block initially contains unknown bytes, so we don't log the initial values
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+
+        // The block initially contains unknown bytes, so we don't log the initial values
         let block = BlockId::new("testfile", 1);
 
         // Transaction 1: Initialize the block's values
-        let mut tx1 = Transaction::new(
-            Arc::clone(&file_manager),
-            Arc::clone(&log_manager),
-            Arc::clone(&buffer_manager),
-            Arc::clone(&lock_table),
-        ).unwrap();
+        let tx1 = db.new_tx().unwrap();
+        let mut tx1 = tx1.lock().unwrap();
 
         tx1.pin(&block).unwrap();
         tx1.set_int(&block, 80, 1, false).unwrap();
@@ -277,59 +249,100 @@ mod tests {
         tx1.commit().unwrap();
 
         // Transaction 2: Read initial values and modify them
-        let mut tx2 = Transaction::new(
-            Arc::clone(&file_manager),
-            Arc::clone(&log_manager),
-            Arc::clone(&buffer_manager),
-            Arc::clone(&lock_table),
-        ).unwrap();
+        let tx2 = db.new_tx().unwrap();
+        let mut tx2 = tx2.lock().unwrap();
 
         tx2.pin(&block).unwrap();
-            
+
         // Read and verify initial values
         let ival = tx2.get_int(&block, 80).unwrap();
         let sval = tx2.get_string(&block, 40).unwrap();
         assert_eq!(ival, 1, "Initial integer value should be 1");
         assert_eq!(sval, "one", "Initial string value should be 'one'");
-        
+
         // Modify values - increment int and append "!" to string
         tx2.set_int(&block, 80, ival + 1, true).unwrap();
-        tx2.set_string(&block, 40, &format!("{}!", sval), true).unwrap();
+        tx2.set_string(&block, 40, &format!("{}!", sval), true)
+            .unwrap();
         tx2.commit().unwrap();
 
         // Transaction 3: Verify modifications and test rollback
-        let mut tx3 = Transaction::new(
-            Arc::clone(&file_manager),
-            Arc::clone(&log_manager),
-            Arc::clone(&buffer_manager),
-            Arc::clone(&lock_table),
-        ).unwrap();
+        let tx3 = db.new_tx().unwrap();
+        let mut tx3 = tx3.lock().unwrap();
 
         tx3.pin(&block).unwrap();
-            
+
         // Verify the modifications from tx2
-        assert_eq!(tx3.get_int(&block, 80).unwrap(), 2, "Integer should be incremented to 2");
-        assert_eq!(tx3.get_string(&block, 40).unwrap(), "one!", "String should have exclamation mark added");
-        
+        assert_eq!(
+            tx3.get_int(&block, 80).unwrap(),
+            2,
+            "Integer should be incremented to 2"
+        );
+        assert_eq!(
+            tx3.get_string(&block, 40).unwrap(),
+            "one!",
+            "String should have exclamation mark added"
+        );
+
         // Make a change that will be rolled back
         tx3.set_int(&block, 80, 9999, true).unwrap();
-        assert_eq!(tx3.get_int(&block, 80).unwrap(), 9999, "Value should be 9999");
-        
+        assert_eq!(
+            tx3.get_int(&block, 80).unwrap(),
+            9999,
+            "Value should be 9999"
+        );
+
         // Rollback the transaction
         tx3.rollback().unwrap();
 
         // Transaction 4: Verify rollback was successful
-        let mut tx4 = Transaction::new(
-            Arc::clone(&file_manager),
-            Arc::clone(&log_manager),
-            Arc::clone(&buffer_manager),
-            Arc::clone(&lock_table),
-        ).unwrap();
+        let tx4 = db.new_tx().unwrap();
+        let mut tx4 = tx4.lock().unwrap();
 
         tx4.pin(&block).unwrap();
-            
+
         // Verify that the value is back to what it was before tx3
-        assert_eq!(tx4.get_int(&block, 80).unwrap(), 2, "After rollback, integer should be back to 2");
+        assert_eq!(
+            tx4.get_int(&block, 80).unwrap(),
+            2,
+            "After rollback, integer should be back to 2"
+        );
         tx4.commit().unwrap();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_concurrent_readers() {
+        let temp_dir = tempdir().unwrap();
+        let db = SimpleDB::new(temp_dir.path().to_str().unwrap(), 800, 8).unwrap();
+        let block = BlockId::new("testfile", 1);
+
+        let tx = db.new_tx().unwrap();
+        tx.lock().unwrap().pin(&block).unwrap();
+        tx.lock().unwrap().set_int(&block, 80, 42, false).unwrap();
+        tx.lock().unwrap().commit().unwrap();
+
+        // a first reader is in the middle of reading the block's buffer
+        let buffer_manager = db.buffer_manager();
+        let idx = buffer_manager.pin(&block).unwrap();
+        let reading = buffer_manager.buffer(idx);
+        assert_eq!(reading.contents().get_int(80).unwrap(), 42);
+
+        // a second reader is not blocked by the first one
+        let reader = db.new_tx().unwrap();
+        let (sender, receiver) = mpsc::channel();
+        let handle = {
+            let block = block.clone();
+            thread::spawn(move || {
+                let mut reader = reader.lock().unwrap();
+                reader.pin(&block).unwrap();
+                sender.send(reader.get_int(&block, 80).unwrap()).unwrap();
+                reader.commit().unwrap();
+            })
+        };
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(5)).unwrap(), 42);
+
+        drop(reading);
+        buffer_manager.unpin(idx).unwrap();
+        handle.join().unwrap();
+    }
+}